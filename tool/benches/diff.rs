@@ -0,0 +1,112 @@
+//! Time `openrpc diff` on large generated documents, where only one method
+//! has changed and where every method has.
+//!
+//! Unchanged methods aren't deserialized, so the first should be much
+//! faster.
+//!
+//! Run with `cargo bench --bench diff`.
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Map, Value};
+
+const METHODS: usize = 2000;
+const PROPERTIES: usize = 20;
+const RUNS: usize = 3;
+
+fn main() {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |name: &str, changed: &dyn Fn(usize) -> bool| {
+        let path = dir.path().join(name);
+        fs::write(
+            &path,
+            serde_json::to_vec_pretty(&document(changed)).unwrap(),
+        )
+        .unwrap();
+        path
+    };
+    let base = write("base.json", &|_| false);
+    let one = write("one.json", &|ix| ix == 0);
+    let all = write("all.json", &|_| true);
+    println!(
+        "{} methods, {} bytes",
+        METHODS,
+        fs::metadata(&base).unwrap().len()
+    );
+    for (name, right) in [
+        ("unchanged", &base),
+        ("one changed", &one),
+        ("all changed", &all),
+    ] {
+        let fastest = (0..RUNS).map(|_| diff(&base, right)).min().unwrap();
+        println!("{:>12}: {:?}", name, fastest)
+    }
+}
+
+fn diff(left: &Path, right: &Path) -> Duration {
+    let start = Instant::now();
+    let status = Command::new(env!("CARGO_BIN_EXE_tool"))
+        .args(["--no-progress", "openrpc", "diff"])
+        .args([left, right])
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    start.elapsed()
+}
+
+/// Each method has a param with its own schema, and a result with a shared
+/// one.
+fn document(changed: &dyn Fn(usize) -> bool) -> Value {
+    let methods = (0..METHODS)
+        .map(|ix| {
+            json!({
+                "name": format!("Method{}", ix),
+                "params": [{
+                    "name": "param",
+                    "required": true,
+                    "schema": { "$ref": format!("#/components/schemas/Param{}", ix) }
+                }],
+                "result": {
+                    "name": "result",
+                    "schema": { "$ref": "#/components/schemas/Shared" }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    let schemas = (0..METHODS)
+        .map(|ix| (format!("Param{}", ix), object(changed(ix))))
+        .chain([(String::from("Shared"), object(false))])
+        .collect::<Map<_, _>>();
+    json!({
+        "openrpc": "1.3.2",
+        "info": { "title": "bench", "version": "0.0.0" },
+        "methods": methods,
+        "components": { "schemas": schemas }
+    })
+}
+
+fn object(changed: bool) -> Value {
+    let properties = (0..PROPERTIES)
+        .map(|ix| {
+            let kind = match changed && ix == 0 {
+                true => "integer",
+                false => "string",
+            };
+            (
+                format!("field{}", ix),
+                json!({ "type": kind, "description": "A field." }),
+            )
+        })
+        .collect::<Map<_, _>>();
+    json!({
+        "type": "object",
+        "required": ["field0"],
+        "properties": properties
+    })
+}
@@ -0,0 +1,108 @@
+//! Time `openrpc report-errors` on a directory of generated documents, all in
+//! one invocation, which checks them in parallel, and one invocation per
+//! document.
+//!
+//! Run with `cargo bench --bench report_errors`.
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Map, Value};
+
+const DOCUMENTS: usize = 40;
+const METHODS: usize = 200;
+const PROPERTIES: usize = 20;
+const RUNS: usize = 3;
+
+fn main() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = (0..DOCUMENTS)
+        .map(|ix| {
+            let path = dir.path().join(format!("part{}.json", ix));
+            fs::write(&path, serde_json::to_vec_pretty(&document(ix)).unwrap()).unwrap();
+            path
+        })
+        .collect::<Vec<_>>();
+    println!(
+        "{} documents of {} methods, {} bytes each",
+        DOCUMENTS,
+        METHODS,
+        fs::metadata(&paths[0]).unwrap().len()
+    );
+    let together = (0..RUNS).map(|_| report_errors(&paths)).min().unwrap();
+    println!("{:>12}: {:?}", "together", together);
+    let apart = (0..RUNS)
+        .map(|_| {
+            paths
+                .iter()
+                .map(|it| report_errors(std::slice::from_ref(it)))
+                .sum::<Duration>()
+        })
+        .min()
+        .unwrap();
+    println!("{:>12}: {:?}", "one by one", apart);
+}
+
+fn report_errors(paths: &[PathBuf]) -> Duration {
+    let start = Instant::now();
+    let status = Command::new(env!("CARGO_BIN_EXE_tool"))
+        .args(["--no-progress", "openrpc", "report-errors"])
+        .args(paths)
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    start.elapsed()
+}
+
+/// Each method has a param with its own schema, and a result with a shared
+/// one.
+fn document(part: usize) -> Value {
+    let methods = (0..METHODS)
+        .map(|ix| {
+            json!({
+                "name": format!("Part{}.Method{}", part, ix),
+                "params": [{
+                    "name": "param",
+                    "required": true,
+                    "schema": { "$ref": format!("#/components/schemas/Param{}", ix) }
+                }],
+                "result": {
+                    "name": "result",
+                    "schema": { "$ref": "#/components/schemas/Shared" }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    let schemas = (0..METHODS)
+        .map(|ix| format!("Param{}", ix))
+        .chain([String::from("Shared")])
+        .map(|key| (key, object()))
+        .collect::<Map<_, _>>();
+    json!({
+        "openrpc": "1.3.2",
+        "info": { "title": "bench", "version": "0.0.0" },
+        "methods": methods,
+        "components": { "schemas": schemas }
+    })
+}
+
+fn object() -> Value {
+    let properties = (0..PROPERTIES)
+        .map(|ix| {
+            (
+                format!("field{}", ix),
+                json!({ "type": "string", "description": "A field." }),
+            )
+        })
+        .collect::<Map<_, _>>();
+    json!({
+        "type": "object",
+        "required": ["field0"],
+        "properties": properties
+    })
+}
@@ -0,0 +1,158 @@
+//! Re-apply downstream tweaks to a document.
+//!
+//! An overlay is a list of [`Operation`]s, which address methods by name and
+//! schemas by their key in `#/components/schemas`, so that they survive
+//! reordering upstream.
+//! Every operation is idempotent, so applying an overlay twice is the same as
+//! applying it once.
+
+use std::fmt;
+
+use openrpc_types::{Error, Method, OpenRPC, ReferenceOr};
+use schemars::schema::Schema;
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum Operation {
+    SetDescription {
+        #[serde(flatten)]
+        target: Target,
+        description: String,
+    },
+    SetExtension {
+        #[serde(flatten)]
+        target: Target,
+        key: ExtensionKey,
+        value: Value,
+    },
+    Deprecate {
+        method: String,
+    },
+    ReplaceSchema {
+        schema: String,
+        value: Schema,
+    },
+    /// Does nothing if the method already has an identical error.
+    AddError {
+        method: String,
+        error: ReferenceOr<Error>,
+    },
+    /// Does nothing if the method is already absent.
+    RemoveMethod {
+        method: String,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Target {
+    Method(String),
+    Schema(String),
+}
+
+/// A specification extension key, which must start with `x-`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ExtensionKey(String);
+
+impl TryFrom<String> for ExtensionKey {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.starts_with("x-") {
+            true => Ok(Self(value)),
+            false => Err(format!("extension key `{}` doesn't start with `x-`", value)),
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Method(it) => write!(f, "method {}", it),
+            Target::Schema(it) => write!(f, "schema {}", it),
+        }
+    }
+}
+
+/// Apply each of `operations` in turn, returning a description of each one
+/// which couldn't be applied, usually because its target is missing.
+pub fn apply(document: &mut OpenRPC, operations: Vec<Operation>) -> Vec<String> {
+    let mut missing = vec![];
+    for operation in operations {
+        if let Err(it) = one(document, operation) {
+            missing.push(it)
+        }
+    }
+    missing
+}
+
+fn one(document: &mut OpenRPC, operation: Operation) -> Result<(), String> {
+    match operation {
+        Operation::SetDescription {
+            target: Target::Method(name),
+            description,
+        } => method(document, &name)?.description = Some(description),
+        Operation::SetDescription {
+            target: Target::Schema(key),
+            description,
+        } => match schema(document, &key)? {
+            Schema::Object(it) => it.metadata().description = Some(description),
+            Schema::Bool(_) => return Err(format!("schema {} is a boolean", key)),
+        },
+        Operation::SetExtension {
+            target: Target::Method(name),
+            key,
+            value,
+        } => {
+            method(document, &name)?.extensions.0.insert(key.0, value);
+        }
+        Operation::SetExtension {
+            target: Target::Schema(schema_key),
+            key,
+            value,
+        } => match schema(document, &schema_key)? {
+            Schema::Object(it) => {
+                it.extensions.insert(key.0, value);
+            }
+            Schema::Bool(_) => return Err(format!("schema {} is a boolean", schema_key)),
+        },
+        Operation::Deprecate { method: name } => method(document, &name)?.deprecated = Some(true),
+        Operation::ReplaceSchema { schema: key, value } => *schema(document, &key)? = value,
+        Operation::AddError {
+            method: name,
+            error,
+        } => {
+            let errors = method(document, &name)?.errors.get_or_insert_with(Vec::new);
+            if !errors.contains(&error) {
+                errors.push(error)
+            }
+        }
+        Operation::RemoveMethod { method: name } => document
+            .methods
+            .retain(|it| !matches!(it, ReferenceOr::Item(it) if it.name == name)),
+    }
+    Ok(())
+}
+
+fn method<'a>(document: &'a mut OpenRPC, name: &str) -> Result<&'a mut Method, String> {
+    document
+        .methods
+        .iter_mut()
+        .find_map(|it| match it {
+            ReferenceOr::Item(it) if it.name == name => Some(it),
+            _ => None,
+        })
+        .ok_or_else(|| format!("method {}", name))
+}
+
+fn schema<'a>(document: &'a mut OpenRPC, key: &str) -> Result<&'a mut Schema, String> {
+    document
+        .components
+        .as_mut()
+        .and_then(|it| it.schemas.as_mut())
+        .and_then(|it| it.get_mut(key))
+        .ok_or_else(|| format!("schema {}", key))
+}
@@ -0,0 +1,261 @@
+//! When each method last changed semantically, from the git history of a
+//! document.
+
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    process::{Command, Output},
+};
+
+use anyhow::{bail, Context as _};
+use openrpc_types::{OpenRPC, ReferenceOr};
+
+use crate::{
+    chains::resolve_within,
+    openrpc_diff::{self, Fingerprint},
+};
+
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub short: String,
+    pub date: String,
+    pub subject: String,
+    hash: String,
+}
+
+#[derive(Debug)]
+pub struct Event {
+    pub revision: Revision,
+    /// A one-line description of the change.
+    pub summary: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Blame {
+    /// Oldest first, for each method.
+    pub events: BTreeMap<String, Vec<Event>>,
+    /// Revisions which couldn't be loaded.
+    pub skipped: Vec<String>,
+}
+
+/// Walk the revisions of the document at `path`, oldest first, recording the
+/// revisions at which each of `methods` (or every method, if [`None`])
+/// changed.
+pub fn blame(path: &Path, methods: Option<&[String]>) -> anyhow::Result<Blame> {
+    let wanted = |name: &str| methods.is_none_or(|it| it.iter().any(|it| it == name));
+    let mut blame = Blame::default();
+    let mut previous: Option<(OpenRPC, BTreeMap<String, Fingerprint>)> = None;
+    for revision in revisions(path)? {
+        let (document, new) = match show(path, &revision.hash).and_then(|it| {
+            let document = serde_json::from_slice::<OpenRPC>(&it)?;
+            let fingerprints = openrpc_diff::fingerprints(&resolve_within(document.clone())?)?;
+            Ok((document, fingerprints))
+        }) {
+            Ok(it) => it,
+            Err(e) => {
+                blame
+                    .skipped
+                    .push(format!("{} {}: {:#}", revision.short, revision.subject, e));
+                continue;
+            }
+        };
+        let mut push = |method: &str, summary: String| {
+            blame
+                .events
+                .entry(method.to_owned())
+                .or_default()
+                .push(Event {
+                    revision: revision.clone(),
+                    summary,
+                })
+        };
+        let Some((before, old)) = previous.replace((document.clone(), new.clone())) else {
+            for it in names(&document).filter(|it| wanted(it)) {
+                push(it, String::from("added"))
+            }
+            continue;
+        };
+        // Only diff methods whose schemas have changed, since diffing is
+        // expensive.
+        let changed = new
+            .iter()
+            .filter(|(name, it)| wanted(name) && old.get(*name).is_some_and(|old| old != *it))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        for name in old.keys().filter(|it| wanted(it) && !new.contains_key(*it)) {
+            push(name, String::from("removed"))
+        }
+        for name in new.keys().filter(|it| wanted(it) && !old.contains_key(*it)) {
+            push(name, String::from("added"))
+        }
+        if changed.is_empty() {
+            continue;
+        }
+        let summary = openrpc_diff::diff(only(&before, &changed), only(&document, &changed))?;
+        for (name, change) in &summary.different {
+            push(name, describe(change))
+        }
+    }
+    Ok(blame)
+}
+
+fn describe(change: &openrpc_diff::MethodChange) -> String {
+    let mut parts = vec![];
+    for (ix, it) in &change.parameter {
+        match (&it.required, it.changes.len()) {
+            (Some(openrpc_diff::RequiredChange::Right), _) => {
+                parts.push(format!("param {} became required", ix))
+            }
+            (Some(openrpc_diff::RequiredChange::Left), _) => {
+                parts.push(format!("param {} became optional", ix))
+            }
+            (None, n) => parts.push(format!("param {} schema changed ({} changes)", ix, n)),
+        }
+    }
+    if let Some(it) = &change.result {
+        match (&it.required, it.changes.len()) {
+            (Some(openrpc_diff::RequiredChange::Left), _) => {
+                parts.push(String::from("result became optional"))
+            }
+            (Some(openrpc_diff::RequiredChange::Right), _) => {
+                parts.push(String::from("result became required"))
+            }
+            (None, n) => parts.push(format!("result schema changed ({} changes)", n)),
+        }
+    }
+    parts.join("; ")
+}
+
+fn names(document: &OpenRPC) -> impl Iterator<Item = &str> {
+    document.methods.iter().filter_map(|it| match it {
+        ReferenceOr::Item(it) => Some(it.name.as_str()),
+        ReferenceOr::Reference(_) => None,
+    })
+}
+
+fn only(document: &OpenRPC, methods: &[String]) -> OpenRPC {
+    let mut document = document.clone();
+    document.methods.retain(|it| match it {
+        ReferenceOr::Item(it) => methods.contains(&it.name),
+        ReferenceOr::Reference(_) => false,
+    });
+    document
+}
+
+/// The commits touching `path`, oldest first.
+fn revisions(path: &Path) -> anyhow::Result<Vec<Revision>> {
+    let (dir, file) = split(path)?;
+    let stdout = git(
+        dir,
+        &[
+            "log",
+            "--reverse",
+            "--date=short",
+            "--format=%H%x09%h%x09%ad%x09%s",
+            "--",
+            file,
+        ],
+    )?;
+    Ok(String::from_utf8_lossy(&stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            Some(Revision {
+                hash: fields.next()?.to_owned(),
+                short: fields.next()?.to_owned(),
+                date: fields.next()?.to_owned(),
+                subject: fields.next().unwrap_or_default().to_owned(),
+            })
+        })
+        .collect())
+}
+
+fn show(path: &Path, hash: &str) -> anyhow::Result<Vec<u8>> {
+    let (dir, file) = split(path)?;
+    git(dir, &["show", &format!("{}:./{}", hash, file)])
+}
+
+fn split(path: &Path) -> anyhow::Result<(&Path, &str)> {
+    match (path.parent(), path.file_name().and_then(|it| it.to_str())) {
+        (Some(dir), Some(file)) => Ok((dir, file)),
+        _ => bail!("{} isn't a file", path.display()),
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let dir = match dir.as_os_str().is_empty() {
+        true => Path::new("."),
+        false => dir,
+    };
+    let Output {
+        status,
+        stdout,
+        stderr,
+    } = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .context("couldn't run git")?;
+    if !status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&stderr).trim()
+        )
+    }
+    Ok(stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    #[test]
+    fn changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spec.json");
+        let commit = |document: &Value, subject: &str| {
+            fs::write(&path, serde_json::to_vec_pretty(document).unwrap()).unwrap();
+            git(dir.path(), &["add", "spec.json"]).unwrap();
+            git(
+                dir.path(),
+                &[
+                    "-c",
+                    "user.name=test",
+                    "-c",
+                    "user.email=test@example.com",
+                    "-c",
+                    "commit.gpgsign=false",
+                    "commit",
+                    "--message",
+                    subject,
+                ],
+            )
+            .unwrap();
+        };
+        git(dir.path(), &["init", "--quiet"]).unwrap();
+        let mut document = serde_json::from_str::<Value>(include_str!("../../spec.json")).unwrap();
+        commit(&document, "add");
+        document["info"]["version"] = json!("2.0.0");
+        commit(&document, "bump");
+        document["methods"][0]["params"][0]["required"] = json!(false);
+        commit(&document, "relax");
+
+        let method = String::from("Filecoin.ChainGetMessage");
+        let blame = blame(&path, Some(&[method.clone()])).unwrap();
+        assert!(blame.skipped.is_empty());
+        let events = blame.events[&method]
+            .iter()
+            .map(|it| (it.revision.subject.as_str(), it.summary.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [("add", "added"), ("relax", "param 0 became optional")]
+        );
+        assert_eq!(blame.events.len(), 1);
+    }
+}
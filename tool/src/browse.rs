@@ -0,0 +1,319 @@
+//! A read-only terminal browser for a document.
+//!
+//! Keys:
+//! - `Up`/`Down` or `k`/`j` select, `PageUp`/`PageDown` scroll the details.
+//! - `Tab` switches between methods and component schemas.
+//! - `/` filters the list by substring, until `Enter` or `Esc`.
+//! - `d` toggles the raw JSON of the selection.
+//! - `q` quits.
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use openrpc_types::resolved;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListState, Paragraph, Tabs, Wrap},
+    DefaultTerminal, Frame,
+};
+use schemars::schema::Schema;
+
+use crate::{
+    reference_index::ReferenceIndex,
+    schema_summary,
+    schema_visit::Provenance,
+};
+
+struct Entry {
+    name: String,
+    details: String,
+    raw: String,
+}
+
+#[derive(Default)]
+struct State {
+    /// Index into `tabs`.
+    tab: usize,
+    filter: String,
+    filtering: bool,
+    raw: bool,
+    scroll: u16,
+    /// The selection within the filtered list of each tab.
+    selected: [ListState; 2],
+}
+
+pub fn browse(document: &resolved::OpenRPC) -> anyhow::Result<()> {
+    let tabs = [methods(document)?, schemas(document)?];
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &tabs);
+    ratatui::restore();
+    result
+}
+
+fn run(terminal: &mut DefaultTerminal, tabs: &[Vec<Entry>; 2]) -> anyhow::Result<()> {
+    let mut state = State::default();
+    for it in &mut state.selected {
+        it.select_first()
+    }
+    loop {
+        terminal.draw(|frame| draw(frame, tabs, &mut state))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let selected = &mut state.selected[state.tab];
+        if state.filtering {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => state.filtering = false,
+                KeyCode::Backspace => {
+                    state.filter.pop();
+                }
+                KeyCode::Char(c) => state.filter.push(c),
+                _ => continue,
+            }
+            selected.select_first();
+            state.scroll = 0;
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') => return Ok(()),
+            KeyCode::Char('/') => state.filtering = true,
+            KeyCode::Char('d') => state.raw = !state.raw,
+            KeyCode::Tab => {
+                state.tab = (state.tab + 1) % tabs.len();
+                state.scroll = 0
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                selected.select_next();
+                state.scroll = 0
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                selected.select_previous();
+                state.scroll = 0
+            }
+            KeyCode::PageDown => state.scroll = state.scroll.saturating_add(10),
+            KeyCode::PageUp => state.scroll = state.scroll.saturating_sub(10),
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, tabs: &[Vec<Entry>; 2], state: &mut State) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [left, right] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(body);
+
+    frame.render_widget(
+        Tabs::new(["Methods", "Schemas"])
+            .select(state.tab)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+        header,
+    );
+
+    let needle = state.filter.to_lowercase();
+    let entries = tabs[state.tab]
+        .iter()
+        .filter(|it| it.name.to_lowercase().contains(&needle))
+        .collect::<Vec<_>>();
+    let selected = &mut state.selected[state.tab];
+    if selected.selected().is_some_and(|it| it >= entries.len()) {
+        selected.select(entries.len().checked_sub(1))
+    }
+    frame.render_stateful_widget(
+        List::new(entries.iter().map(|it| it.name.as_str()))
+            .block(Block::new().borders(Borders::RIGHT))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+        left,
+        selected,
+    );
+
+    let text = match selected.selected().and_then(|it| entries.get(it)) {
+        Some(Entry { raw, .. }) if state.raw => raw.as_str(),
+        Some(Entry { details, .. }) => details.as_str(),
+        None => "",
+    };
+    frame.render_widget(
+        Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .scroll((state.scroll, 0)),
+        right,
+    );
+
+    frame.render_widget(
+        Line::from(match state.filtering {
+            true => format!("/{}", state.filter),
+            false => format!(
+                "{}/{} shown  /: filter  d: raw json  tab: switch  q: quit",
+                entries.len(),
+                tabs[state.tab].len()
+            ),
+        }),
+        footer,
+    );
+}
+
+fn methods(document: &resolved::OpenRPC) -> anyhow::Result<Vec<Entry>> {
+    let empty = BTreeMap::new();
+    let schemas = schemas_of(document).unwrap_or(&empty);
+    let mut entries = vec![];
+    for method in &document.methods {
+        let mut out = String::new();
+        writeln!(out, "{}", method.name)?;
+        if method.deprecated == Some(true) {
+            writeln!(out, "(deprecated)")?;
+        }
+        for it in method.summary.iter().chain(&method.description) {
+            writeln!(out, "\n{}", it.trim_end())?;
+        }
+        writeln!(out, "\nParams:")?;
+        for it in &method.params {
+            writeln!(out, "  {}", descriptor(schemas, it))?;
+        }
+        if let Some(it) = &method.result {
+            writeln!(out, "\nResult:\n  {}", descriptor(schemas, it))?;
+        }
+        if let Some(errors) = method.errors.as_ref().filter(|it| !it.is_empty()) {
+            writeln!(out, "\nErrors:")?;
+            for it in errors {
+                writeln!(out, "  {} {}", it.code, it.message)?;
+            }
+        }
+        if let Some(examples) = method.examples.as_ref().filter(|it| !it.is_empty()) {
+            writeln!(out, "\nExamples:")?;
+            for it in examples {
+                writeln!(out, "  {}", it.name)?;
+                let params = it
+                    .params
+                    .iter()
+                    .map(|it| it.value.clone().unwrap_or_default())
+                    .collect::<Vec<_>>();
+                writeln!(out, "    params: {}", serde_json::to_string(&params)?)?;
+                if let Some(value) = it.result.as_ref().and_then(|it| it.value.as_ref()) {
+                    writeln!(out, "    result: {}", value)?;
+                }
+            }
+        }
+        entries.push(Entry {
+            name: method.name.clone(),
+            details: out,
+            raw: serde_json::to_string_pretty(method)?,
+        })
+    }
+    Ok(entries)
+}
+
+/// With the methods which (transitively) use each schema, and the schemas
+/// which reference it directly.
+fn schemas(document: &resolved::OpenRPC) -> anyhow::Result<Vec<Entry>> {
+    let Some(schemas) = schemas_of(document) else {
+        return Ok(vec![]);
+    };
+    let index = ReferenceIndex::new(document);
+    let mut used_by = BTreeMap::<&str, Vec<&str>>::new();
+    for method in &document.methods {
+        for key in index.reachable(&method.name)? {
+            used_by.entry(key).or_default().push(&method.name)
+        }
+    }
+    let mut referenced_by = BTreeMap::<&str, Vec<&str>>::new();
+    for key in schemas.keys() {
+        for it in index.uses(key) {
+            if let Provenance::Component { key: from } = it.provenance {
+                let by = referenced_by.entry(key.as_str()).or_default();
+                if by.last() != Some(&from) {
+                    by.push(from)
+                }
+            }
+        }
+    }
+    let mut entries = vec![];
+    for (key, schema) in schemas {
+        let mut out = String::new();
+        writeln!(out, "{}\n", key)?;
+        if let Schema::Object(object) = schema {
+            if let Some(it) = object
+                .metadata
+                .as_ref()
+                .and_then(|it| it.description.as_ref())
+            {
+                writeln!(out, "{}\n", it.trim_end())?;
+            }
+            writeln!(out, "{}", schema_summary::summarize(schema, schemas))?;
+            if let Some(it) = object
+                .object
+                .as_ref()
+                .filter(|it| !it.properties.is_empty())
+            {
+                writeln!(out, "\nProperties:")?;
+                for (name, property) in &it.properties {
+                    let required = match it.required.contains(name) {
+                        true => " (required)",
+                        false => "",
+                    };
+                    writeln!(
+                        out,
+                        "  {}{}: {}",
+                        name,
+                        required,
+                        schema_summary::summarize(property, schemas)
+                    )?;
+                }
+            }
+        } else {
+            writeln!(out, "{}", schema_summary::summarize(schema, schemas))?;
+        }
+        let methods = used_by.get(key.as_str()).map_or(&[][..], Vec::as_slice);
+        writeln!(out, "\nUsed by {} methods:", methods.len())?;
+        for it in methods {
+            writeln!(out, "  {}", it)?;
+        }
+        if let Some(it) = referenced_by.get(key.as_str()) {
+            writeln!(out, "\nReferenced by {} schemas:", it.len())?;
+            for it in it {
+                writeln!(out, "  {}", it)?;
+            }
+        }
+        entries.push(Entry {
+            name: key.clone(),
+            details: out,
+            raw: serde_json::to_string_pretty(schema)?,
+        })
+    }
+    Ok(entries)
+}
+
+fn schemas_of(document: &resolved::OpenRPC) -> Option<&BTreeMap<String, Schema>> {
+    document
+        .components
+        .as_ref()
+        .and_then(|it| it.schemas.as_ref())
+}
+
+fn descriptor(
+    schemas: &BTreeMap<String, Schema>,
+    it: &openrpc_types::ContentDescriptor,
+) -> String {
+    let required = match it.required.unwrap_or_default() {
+        true => " (required)",
+        false => "",
+    };
+    let mut out = format!(
+        "{}{}: {}",
+        it.name,
+        required,
+        schema_summary::summarize(&it.schema, schemas)
+    );
+    if let Some(description) = it.description.as_ref().or(it.summary.as_ref()) {
+        write!(out, " - {}", description.trim_end()).unwrap();
+    }
+    out
+}
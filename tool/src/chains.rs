@@ -0,0 +1,536 @@
+//! Follow components which are themselves references.
+//!
+//! [`openrpc_types`] expects each component to be an item, so a component
+//! content descriptor, error, tag, example, example pairing or link which is a
+//! `$ref` to another component fails to parse.
+//! [`follow`] replaces each such component with the item at the end of its
+//! chain, before the document is parsed.
+//!
+//! Schema `$ref`s are left alone.
+//!
+//! [`resolve_within`] wraps [`openrpc_types::resolve_within`], saying which
+//! method uses a broken `$ref`.
+//! [`resolve_within_lenient`] instead replaces each use of a broken `$ref`
+//! with a placeholder, so the rest of a document can still be checked.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use openrpc_types::{resolved, BrokenReference, OpenRPC, ReferenceOr};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use tracing::{debug, trace};
+
+use crate::{
+    component_ref::{ComponentRef, ComponentSection},
+    reference_index,
+};
+
+/// The longest chain followed, unless `--max-ref-depth` is given.
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+/// The sections of `components` whose entries may be chained.
+const SECTIONS: &[ComponentSection] = &[
+    ComponentSection::ContentDescriptors,
+    ComponentSection::Errors,
+    ComponentSection::Tags,
+    ComponentSection::Examples,
+    ComponentSection::ExamplePairingObjects,
+    ComponentSection::Links,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// Nothing is at `reference`.
+    BrokenReference {
+        reference: String,
+        /// Where `reference` is used, like `methods/Filecoin.StateCall/errors/0`.
+        at: Option<String>,
+    },
+    /// Each `$ref` in the chain, ending with the one which was already
+    /// followed.
+    ReferenceCycle(Vec<String>),
+    /// A component in `section` refers to one in another section.
+    WrongComponentSection { reference: String, section: String },
+    /// The chain is longer than the limit.
+    TooDeep(Vec<String>),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BrokenReference { reference, at } => {
+                write!(f, "error resolving $ref '{}'", reference)?;
+                match at {
+                    Some(at) => write!(f, " at {}", at),
+                    None => Ok(()),
+                }
+            }
+            Self::ReferenceCycle(chain) => {
+                write!(f, "`$ref`s form a cycle: {}", chain.join(" -> "))
+            }
+            Self::WrongComponentSection { reference, section } => write!(
+                f,
+                "`$ref` {} is in {}, but points outside #/components/{}",
+                reference, section, section
+            ),
+            Self::TooDeep(chain) => write!(
+                f,
+                "`$ref`s chain more than {} times: {}",
+                // the start, and the reference past the limit
+                chain.len() - 2,
+                chain.join(" -> ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+impl From<BrokenReference> for ResolveError {
+    fn from(BrokenReference(reference): BrokenReference) -> Self {
+        Self::BrokenReference {
+            reference,
+            at: None,
+        }
+    }
+}
+
+/// As [`openrpc_types::resolve_within`], with the first use of a broken
+/// `$ref` in the methods.
+pub fn resolve_within(document: OpenRPC) -> Result<resolved::OpenRPC, ResolveError> {
+    let uses = uses(&document);
+    match openrpc_types::resolve_within(document) {
+        Ok(it) => {
+            for (reference, at) in &uses {
+                trace!(reference = reference.as_str(), at = at.as_str(), "resolved")
+            }
+            Ok(it)
+        }
+        Err(BrokenReference(reference)) => {
+            let at = uses
+                .into_iter()
+                .find_map(|(it, at)| (it == reference).then_some(at));
+            debug!(reference = reference.as_str(), at = at.as_deref(), "broken");
+            Err(ResolveError::BrokenReference { reference, at })
+        }
+    }
+}
+
+/// On a placeholder from [`resolve_within_lenient`], the `$ref` it replaces.
+pub const UNRESOLVED: &str = "x-unresolved";
+
+/// A use of a broken `$ref` which [`resolve_within_lenient`] replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveIssue {
+    pub reference: String,
+    /// Like `methods/Filecoin.StateCall/errors/0`.
+    pub at: String,
+}
+
+impl fmt::Display for ResolveIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unresolved $ref '{}' at {}", self.reference, self.at)
+    }
+}
+
+/// As [`resolve_within`], but each use of a broken `$ref` in the methods is
+/// replaced with a placeholder carrying [`UNRESOLVED`].
+///
+/// Placeholders are named after the `$ref`, and content descriptors have an
+/// empty schema.
+/// Still fails on a broken `$ref` outside the methods.
+pub fn resolve_within_lenient(
+    mut document: OpenRPC,
+) -> Result<(resolved::OpenRPC, Vec<ResolveIssue>), ResolveError> {
+    let issues = replace(&mut document);
+    Ok((resolve_within(document)?, issues))
+}
+
+/// Replace each use of a broken `$ref` in the methods of `document` with a
+/// placeholder.
+fn replace(document: &mut OpenRPC) -> Vec<ResolveIssue> {
+    struct Replace<'a> {
+        keys: BTreeMap<ComponentSection, BTreeSet<&'a str>>,
+        issues: Vec<ResolveIssue>,
+    }
+    impl Replace<'_> {
+        /// Replace `it` if it is a `$ref` to nothing in `section`, or if there
+        /// is no section it may point into.
+        fn swap<T: DeserializeOwned>(
+            &mut self,
+            it: &mut ReferenceOr<T>,
+            section: Option<ComponentSection>,
+            placeholder: impl FnOnce(&str) -> Value,
+            at: impl FnOnce() -> String,
+        ) {
+            let ReferenceOr::Reference(reference) = it else {
+                return;
+            };
+            let resolves = section.is_some_and(|section| {
+                ComponentRef::parse(reference)
+                    .key_in(section)
+                    .is_some_and(|key| self.keys.get(&section).is_some_and(|it| it.contains(key)))
+            });
+            if resolves {
+                return;
+            }
+            let reference = reference.clone();
+            let mut placeholder = placeholder(&reference);
+            placeholder[UNRESOLVED] = Value::String(reference.clone());
+            *it = ReferenceOr::Item(
+                serde_json::from_value(placeholder).expect("placeholders are valid"),
+            );
+            self.issues.push(ResolveIssue {
+                reference,
+                at: at(),
+            })
+        }
+    }
+    let named = |reference: &str| json!({ "name": reference });
+    let descriptor = |reference: &str| json!({ "name": reference, "schema": {} });
+    let mut replace = Replace {
+        keys: document
+            .components
+            .as_ref()
+            .map(reference_index::keys)
+            .unwrap_or_default(),
+        issues: vec![],
+    };
+    for (ix, method) in document.methods.iter_mut().enumerate() {
+        let placeholder = |reference: &str| json!({ "name": reference, "params": [] });
+        replace.swap(method, None, placeholder, || format!("methods/{}", ix));
+        let ReferenceOr::Item(method) = method else {
+            continue;
+        };
+        let at = format!("methods/{}", method.name);
+        for (ix, it) in method.params.iter_mut().enumerate() {
+            let section = Some(ComponentSection::ContentDescriptors);
+            replace.swap(it, section, descriptor, || format!("{}/params/{}", at, ix))
+        }
+        if let Some(it) = &mut method.result {
+            let section = Some(ComponentSection::ContentDescriptors);
+            replace.swap(it, section, descriptor, || format!("{}/result", at))
+        }
+        for (ix, it) in method.errors.iter_mut().flatten().enumerate() {
+            let error = |reference: &str| json!({ "code": 0, "message": reference });
+            let section = Some(ComponentSection::Errors);
+            replace.swap(it, section, error, || format!("{}/errors/{}", at, ix))
+        }
+        for (ix, it) in method.tags.iter_mut().flatten().enumerate() {
+            let section = Some(ComponentSection::Tags);
+            replace.swap(it, section, named, || format!("{}/tags/{}", at, ix))
+        }
+        for (ix, it) in method.examples.iter_mut().flatten().enumerate() {
+            let pairing = |reference: &str| json!({ "name": reference, "params": [] });
+            let section = Some(ComponentSection::ExamplePairingObjects);
+            replace.swap(it, section, pairing, || format!("{}/examples/{}", at, ix));
+            if let ReferenceOr::Item(pairing) = it {
+                let section = Some(ComponentSection::Examples);
+                for (param, it) in pairing.params.iter_mut().enumerate() {
+                    replace.swap(it, section, named, || {
+                        format!("{}/examples/{}/params/{}", at, ix, param)
+                    })
+                }
+                if let Some(it) = &mut pairing.result {
+                    replace.swap(it, section, named, || {
+                        format!("{}/examples/{}/result", at, ix)
+                    })
+                }
+            }
+        }
+    }
+    replace.issues
+}
+
+/// Each `$ref` in the methods of `document`, and where it is.
+fn uses(document: &OpenRPC) -> Vec<(String, String)> {
+    fn push<T>(uses: &mut Vec<(String, String)>, it: &ReferenceOr<T>, at: impl FnOnce() -> String) {
+        if let ReferenceOr::Reference(reference) = it {
+            uses.push((reference.clone(), at()))
+        }
+    }
+    let mut uses = vec![];
+    for (ix, method) in document.methods.iter().enumerate() {
+        let method = match method {
+            ReferenceOr::Reference(it) => {
+                uses.push((it.clone(), format!("methods/{}", ix)));
+                continue;
+            }
+            ReferenceOr::Item(it) => it,
+        };
+        let at = format!("methods/{}", method.name);
+        for (ix, it) in method.params.iter().enumerate() {
+            push(&mut uses, it, || format!("{}/params/{}", at, ix))
+        }
+        if let Some(it) = &method.result {
+            push(&mut uses, it, || format!("{}/result", at))
+        }
+        for (ix, it) in method.errors.iter().flatten().enumerate() {
+            push(&mut uses, it, || format!("{}/errors/{}", at, ix))
+        }
+        for (ix, it) in method.tags.iter().flatten().enumerate() {
+            push(&mut uses, it, || format!("{}/tags/{}", at, ix))
+        }
+        for (ix, it) in method.examples.iter().flatten().enumerate() {
+            push(&mut uses, it, || format!("{}/examples/{}", at, ix));
+            if let ReferenceOr::Item(pairing) = it {
+                for (param, it) in pairing.params.iter().enumerate() {
+                    push(&mut uses, it, || {
+                        format!("{}/examples/{}/params/{}", at, ix, param)
+                    })
+                }
+                if let Some(it) = &pairing.result {
+                    push(&mut uses, it, || format!("{}/examples/{}/result", at, ix))
+                }
+            }
+        }
+    }
+    uses
+}
+
+/// Replace each chained component in `document` with the item at the end of
+/// its chain, following at most `max_depth` references.
+///
+/// Returns whether anything was replaced.
+pub fn follow(document: &mut Value, max_depth: usize) -> Result<bool, ResolveError> {
+    let Some(components) = document
+        .get_mut("components")
+        .and_then(Value::as_object_mut)
+    else {
+        return Ok(false);
+    };
+    let mut replaced = false;
+    for &section in SECTIONS {
+        let Some(entries) = components.get(section.name()).and_then(Value::as_object) else {
+            continue;
+        };
+        let mut items = vec![];
+        for (name, entry) in entries {
+            if reference(entry).is_some() {
+                items.push((name.clone(), end(entries, section, name, max_depth)?))
+            }
+        }
+        if let Some(Value::Object(entries)) = components.get_mut(section.name()) {
+            for (name, item) in items {
+                entries.insert(name, item);
+                replaced = true
+            }
+        }
+    }
+    Ok(replaced)
+}
+
+/// The item at the end of the chain starting at the entry `name`.
+fn end(
+    entries: &Map<String, Value>,
+    section: ComponentSection,
+    name: &str,
+    max_depth: usize,
+) -> Result<Value, ResolveError> {
+    let start = ComponentRef::new(section, name);
+    let mut seen = vec![start.clone()];
+    let mut chain = vec![start.to_string()];
+    let mut entry = &entries[name];
+    while let Some(it) = reference(entry) {
+        let parsed = ComponentRef::parse(it);
+        chain.push(it.to_owned());
+        if seen.contains(&parsed) {
+            return Err(ResolveError::ReferenceCycle(chain));
+        }
+        if chain.len() > max_depth + 1 {
+            return Err(ResolveError::TooDeep(chain));
+        }
+        let broken = || ResolveError::BrokenReference {
+            reference: it.to_owned(),
+            at: Some(chain[0][2..].to_owned()),
+        };
+        entry = match parsed.key_in(section) {
+            Some(key) => entries.get(key).ok_or_else(broken)?,
+            None => {
+                return Err(match &parsed {
+                    ComponentRef::Component { .. } => ResolveError::WrongComponentSection {
+                        reference: it.to_owned(),
+                        section: section.name().to_owned(),
+                    },
+                    ComponentRef::External(_) | ComponentRef::Malformed(_) => broken(),
+                })
+            }
+        };
+        seen.push(parsed)
+    }
+    Ok(entry.clone())
+}
+
+/// The `$ref` of `entry`, if it is a reference.
+fn reference(entry: &Value) -> Option<&str> {
+    entry.get("$ref")?.as_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: Value) -> Value {
+        json!({ "openrpc": "1.3.2", "components": { "tags": tags } })
+    }
+
+    /// With `reference` as the param of an example of a method.
+    fn example(reference: &str) -> OpenRPC {
+        serde_json::from_value(json!({
+            "openrpc": "1.3.2",
+            "info": { "title": "chains", "version": "0.0.0" },
+            "methods": [
+                {
+                    "name": "Get",
+                    "params": [],
+                    "examples": [{ "name": "e", "params": [{ "$ref": reference }] }]
+                }
+            ],
+            "components": { "examples": { "A": { "name": "a", "value": 1 } } }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn lenient() {
+        let document = serde_json::from_value::<OpenRPC>(json!({
+            "openrpc": "1.3.2",
+            "info": { "title": "chains", "version": "0.0.0" },
+            "methods": [
+                {
+                    "name": "Get",
+                    "params": [
+                        { "$ref": "#/components/contentDescriptors/A" },
+                        { "$ref": "#/components/contentDescriptors/Missing" }
+                    ],
+                    "errors": [{ "$ref": "#/components/errors/Missing" }],
+                    "tags": [{ "$ref": "#/components/tags/T" }]
+                },
+                {
+                    "name": "Wrong",
+                    "params": [{ "$ref": "#/components/schemas/A" }]
+                }
+            ],
+            "components": {
+                "contentDescriptors": { "A": { "name": "a", "schema": {} } },
+                "schemas": { "A": { "type": "string" } },
+                "tags": { "T": { "name": "t" } }
+            }
+        }))
+        .unwrap();
+        let (resolved, issues) = resolve_within_lenient(document).unwrap();
+        assert_eq!(
+            issues,
+            [
+                ResolveIssue {
+                    reference: String::from("#/components/contentDescriptors/Missing"),
+                    at: String::from("methods/Get/params/1"),
+                },
+                ResolveIssue {
+                    reference: String::from("#/components/errors/Missing"),
+                    at: String::from("methods/Get/errors/0"),
+                },
+                ResolveIssue {
+                    reference: String::from("#/components/schemas/A"),
+                    at: String::from("methods/Wrong/params/0"),
+                },
+            ]
+        );
+        let resolved = serde_json::to_value(resolved).unwrap();
+        let get = &resolved["methods"][0];
+        assert_eq!(get["params"][0]["name"], "a");
+        assert_eq!(
+            get["params"][1]["name"],
+            "#/components/contentDescriptors/Missing"
+        );
+        assert_eq!(get["errors"][0]["message"], "#/components/errors/Missing");
+        assert_eq!(get["tags"][0]["name"], "t");
+    }
+
+    #[test]
+    fn nested_use() {
+        let resolved = resolve_within(example("#/components/examples/A")).unwrap();
+        assert_eq!(
+            serde_json::to_value(resolved).unwrap()["methods"][0]["examples"][0]["params"][0]
+                ["value"],
+            1
+        );
+
+        let error = resolve_within(example("#/components/examples/B")).unwrap_err();
+        assert_eq!(
+            error,
+            ResolveError::BrokenReference {
+                reference: String::from("#/components/examples/B"),
+                at: Some(String::from("methods/Get/examples/0/params/0")),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "error resolving $ref '#/components/examples/B' at methods/Get/examples/0/params/0"
+        );
+    }
+
+    #[test]
+    fn two_hops() {
+        let mut document = tags(json!({
+            "A": { "$ref": "#/components/tags/B" },
+            "B": { "$ref": "#/components/tags/C" },
+            "C": { "name": "c" },
+        }));
+        assert_eq!(follow(&mut document, 2), Ok(true));
+        assert_eq!(
+            document,
+            tags(json!({
+                "A": { "name": "c" },
+                "B": { "name": "c" },
+                "C": { "name": "c" },
+            }))
+        );
+        // nothing left to follow
+        assert_eq!(follow(&mut document, 2), Ok(false));
+    }
+
+    #[test]
+    fn cycle() {
+        let mut document = tags(json!({
+            "A": { "$ref": "#/components/tags/B" },
+            "B": { "$ref": "#/components/tags/C" },
+            "C": { "$ref": "#/components/tags/A" },
+        }));
+        assert_eq!(
+            follow(&mut document, DEFAULT_MAX_DEPTH),
+            Err(ResolveError::ReferenceCycle(vec![
+                String::from("#/components/tags/A"),
+                String::from("#/components/tags/B"),
+                String::from("#/components/tags/C"),
+                String::from("#/components/tags/A"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn too_deep() {
+        let mut document = tags(json!({
+            "A": { "$ref": "#/components/tags/B" },
+            "B": { "$ref": "#/components/tags/C" },
+            "C": { "name": "c" },
+        }));
+        let error = follow(&mut document, 1).unwrap_err();
+        assert_eq!(
+            error,
+            ResolveError::TooDeep(vec![
+                String::from("#/components/tags/A"),
+                String::from("#/components/tags/B"),
+                String::from("#/components/tags/C"),
+            ])
+        );
+        assert_eq!(
+            error.to_string(),
+            "`$ref`s chain more than 1 times: \
+             #/components/tags/A -> #/components/tags/B -> #/components/tags/C"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Compare component schemas with the Go structs they're serialized from.
+//!
+//! Go types are read from a JSON dump, keyed by type name:
+//! ```json
+//! { "types.Message": [{ "name": "GasLimit", "json": "GasLimit", "type": "int64", "omitempty": false }] }
+//! ```
+//! `json` is the name from the field's `json` tag, if any.
+//! Embedded structs should be flattened into their parent.
+//!
+//! A schema is compared with the Go type whose name, or the part after the last
+//! `.`, is the schema's key or `title`.
+//!
+//! Go types map to JSON Schema types by [`GO_TYPES`], after stripping a
+//! leading `*` (which also allows `null`).
+//! Slices and arrays are `array`, except `[]byte` which is a base64 `string`.
+//! Maps and structs in the dump are `object`.
+//! Other types are reported as unknown rather than guessed.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use openrpc_types::resolved;
+use schemars::schema::{InstanceType, ObjectValidation, Schema, SchemaObject, SingleOrVec};
+use serde::Deserialize;
+
+use crate::component_ref::{ComponentRef, ComponentSection};
+
+/// Each Go type, and the JSON Schema types it can be serialized as.
+pub const GO_TYPES: &[(&str, &[InstanceType])] = {
+    use InstanceType::{Boolean, Integer, Number, Object, String};
+    &[
+        ("string", &[String]),
+        ("bool", &[Boolean]),
+        ("int", &[Integer]),
+        ("int8", &[Integer]),
+        ("int16", &[Integer]),
+        ("int32", &[Integer]),
+        ("int64", &[Integer]),
+        ("uint", &[Integer]),
+        ("uint8", &[Integer]),
+        ("uint16", &[Integer]),
+        ("uint32", &[Integer]),
+        ("uint64", &[Integer]),
+        ("float32", &[Number, Integer]),
+        ("float64", &[Number, Integer]),
+        // Serialized by their `MarshalJSON` methods.
+        ("big.Int", &[String]),
+        ("abi.TokenAmount", &[String]),
+        ("address.Address", &[String]),
+        ("cid.Cid", &[Object]),
+        ("abi.ChainEpoch", &[Integer]),
+        ("abi.MethodNum", &[Integer]),
+        ("abi.ActorID", &[Integer]),
+        ("abi.SectorNumber", &[Integer]),
+        ("abi.DealID", &[Integer]),
+        ("exitcode.ExitCode", &[Integer]),
+        ("time.Time", &[String]),
+    ]
+};
+
+/// Types which can be anything.
+const ANY: &[&str] = &["interface{}", "any", "json.RawMessage"];
+
+#[derive(Debug, Deserialize)]
+pub struct Field {
+    pub name: String,
+    #[serde(default)]
+    pub json: Option<String>,
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(default)]
+    pub omitempty: bool,
+}
+
+impl Field {
+    /// The JSON property name, or [`None`] if it isn't serialized.
+    fn key(&self) -> Option<&str> {
+        match self.json.as_deref() {
+            Some("-") => None,
+            Some("") | None => Some(&self.name),
+            Some(it) => Some(it),
+        }
+    }
+}
+
+/// Mismatches, as `<schema>: <description>`.
+pub fn check(document: &resolved::OpenRPC, go: &BTreeMap<String, Vec<Field>>) -> Vec<String> {
+    let empty = BTreeMap::new();
+    let schemas = document
+        .components
+        .as_ref()
+        .and_then(|it| it.schemas.as_ref())
+        .unwrap_or(&empty);
+    let mut mismatches = vec![];
+    for (key, schema) in schemas {
+        let Schema::Object(SchemaObject {
+            metadata,
+            object: Some(object),
+            ..
+        }) = schema
+        else {
+            continue;
+        };
+        let title = metadata.as_ref().and_then(|it| it.title.as_deref());
+        let Some((go_name, fields)) = go.iter().find(|(name, _)| {
+            let names = [Some(key.as_str()), title];
+            names.contains(&Some(name)) || names.contains(&name.rsplit('.').next())
+        }) else {
+            continue;
+        };
+        let mut push = |it: String| mismatches.push(format!("{} ({}): {}", key, go_name, it));
+        let ObjectValidation {
+            required,
+            properties,
+            ..
+        } = &**object;
+        let fields = fields
+            .iter()
+            .filter_map(|it| Some((it.key()?, it)))
+            .collect::<BTreeMap<_, _>>();
+        for property in properties.keys() {
+            if !fields.contains_key(property.as_str()) {
+                push(format!("property {} is missing in Go", property))
+            }
+        }
+        for (property, field) in &fields {
+            let Some(schema) = properties.get(*property) else {
+                push(format!("field {} is missing in the schema", field.name));
+                continue;
+            };
+            match (required.contains(*property), field.omitempty) {
+                (true, true) => push(format!(
+                    "property {} is required, but {} is omitempty",
+                    property, field.name
+                )),
+                (false, false) => push(format!(
+                    "property {} is optional, but {} isn't omitempty",
+                    property, field.name
+                )),
+                _ => {}
+            }
+            let Some(allowed) = go_types(&field.ty, go) else {
+                push(format!(
+                    "field {} has unknown Go type {}",
+                    field.name, field.ty
+                ));
+                continue;
+            };
+            let Some(allowed) = allowed else { continue };
+            let actual = schema_types(schema, schemas, &mut BTreeSet::new());
+            if !actual.is_empty() && actual.is_disjoint(&allowed) {
+                push(format!(
+                    "property {} is {}, but {} is {}",
+                    property,
+                    names(&actual),
+                    field.name,
+                    field.ty
+                ))
+            }
+        }
+    }
+    mismatches
+}
+
+/// The JSON Schema types a Go type may be serialized as.
+///
+/// [`None`] if the type is unknown, and `Some(None)` if it may be anything.
+fn go_types(ty: &str, go: &BTreeMap<String, Vec<Field>>) -> Option<Option<BTreeSet<InstanceType>>> {
+    if let Some(inner) = ty.strip_prefix('*') {
+        return Some(go_types(inner, go)?.map(|mut it| {
+            it.insert(InstanceType::Null);
+            it
+        }));
+    }
+    if ANY.contains(&ty) {
+        return Some(None);
+    }
+    let set = |it: &[InstanceType]| Some(Some(it.iter().copied().collect()));
+    if ty == "[]byte" || ty == "[]uint8" {
+        // A nil slice is `null`.
+        return set(&[InstanceType::String, InstanceType::Null]);
+    }
+    if ty.starts_with('[') {
+        return set(&[InstanceType::Array, InstanceType::Null]);
+    }
+    if ty.starts_with("map[") {
+        return set(&[InstanceType::Object, InstanceType::Null]);
+    }
+    if let Some((_, it)) = GO_TYPES.iter().find(|(name, _)| *name == ty) {
+        return set(it);
+    }
+    if go.contains_key(ty) {
+        return set(&[InstanceType::Object]);
+    }
+    None
+}
+
+/// The types `schema` admits, following `$ref`s and unions, or empty if
+/// unconstrained.
+fn schema_types<'a>(
+    schema: &'a Schema,
+    schemas: &'a BTreeMap<String, Schema>,
+    visiting: &mut BTreeSet<&'a str>,
+) -> BTreeSet<InstanceType> {
+    let Schema::Object(object) = schema else {
+        return BTreeSet::new();
+    };
+    if let Some(reference) = &object.reference {
+        return match ComponentRef::parse(reference)
+            .key_in(ComponentSection::Schemas)
+            .and_then(|it| schemas.get_key_value(it))
+        {
+            Some((key, target)) if visiting.insert(key) => {
+                let it = schema_types(target, schemas, visiting);
+                visiting.remove(key.as_str());
+                it
+            }
+            _ => BTreeSet::new(),
+        };
+    }
+    match &object.instance_type {
+        Some(SingleOrVec::Single(it)) => BTreeSet::from([**it]),
+        Some(SingleOrVec::Vec(it)) => it.iter().copied().collect(),
+        None => {
+            let mut types = BTreeSet::new();
+            for it in object
+                .subschemas
+                .iter()
+                .flat_map(|it| it.any_of.iter().chain(&it.one_of))
+                .flatten()
+            {
+                let it = schema_types(it, schemas, visiting);
+                if it.is_empty() {
+                    return it;
+                }
+                types.extend(it)
+            }
+            types
+        }
+    }
+}
+
+fn names(types: &BTreeSet<InstanceType>) -> String {
+    types
+        .iter()
+        .map(|it| match it {
+            InstanceType::Null => "null",
+            InstanceType::Boolean => "boolean",
+            InstanceType::Object => "object",
+            InstanceType::Array => "array",
+            InstanceType::Number => "number",
+            InstanceType::String => "string",
+            InstanceType::Integer => "integer",
+        })
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
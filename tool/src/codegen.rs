@@ -0,0 +1,57 @@
+//! Generate client code from an OpenRPC document.
+
+pub mod rust;
+pub mod typescript;
+
+/// Split an identifier-ish string into words, on non-alphanumeric characters
+/// and `camelCase`/`PascalCase`/`ACRONYMCase` boundaries.
+fn words(s: &str) -> Vec<String> {
+    let mut words = vec![];
+    for part in s.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let chars = part.chars().collect::<Vec<_>>();
+        let mut current = String::new();
+        for (ix, &c) in chars.iter().enumerate() {
+            let prev = ix.checked_sub(1).map(|it| chars[it]);
+            let next = chars.get(ix + 1);
+            let boundary = c.is_ascii_uppercase()
+                && match prev {
+                    Some(prev) => {
+                        prev.is_ascii_lowercase()
+                            || prev.is_ascii_digit()
+                            || (prev.is_ascii_uppercase()
+                                && next.is_some_and(|it| it.is_ascii_lowercase()))
+                    }
+                    None => false,
+                };
+            if boundary && !current.is_empty() {
+                words.push(std::mem::take(&mut current))
+            }
+            current.push(c)
+        }
+        if !current.is_empty() {
+            words.push(current)
+        }
+    }
+    words
+}
+
+fn snake_case(s: &str) -> String {
+    words(s)
+        .iter()
+        .map(|it| it.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn pascal_case(s: &str) -> String {
+    words(s)
+        .iter()
+        .map(|it| {
+            let mut chars = it.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
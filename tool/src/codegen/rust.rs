@@ -0,0 +1,548 @@
+//! Rust client bindings.
+//!
+//! - Component schemas become named types:
+//!   objects with `properties` become structs, string `enum`s become enums,
+//!   and everything else becomes a type alias.
+//! - Each method becomes an `async fn` on a `Client` which is generic over a
+//!   `Transport`.
+//! - Constructs we can't map fall back to `serde_json::Value`, with a
+//!   `// TODO` above the generated item.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+};
+
+use anyhow::bail;
+use openrpc_types::{resolved, ContentDescriptor, ParamStructure};
+use schemars::schema::{InstanceType, ObjectValidation, Schema, SchemaObject, SingleOrVec};
+use serde_json::Value;
+
+use super::{pascal_case, snake_case};
+use crate::component_ref::{ComponentRef, ComponentSection};
+
+const VALUE: &str = "serde_json::Value";
+
+pub struct Generated {
+    pub code: String,
+    /// Locations where we fell back to [`VALUE`], and why.
+    pub fallbacks: Vec<String>,
+}
+
+pub fn generate(document: &resolved::OpenRPC) -> anyhow::Result<Generated> {
+    let empty = BTreeMap::new();
+    let schemas = document
+        .components
+        .as_ref()
+        .and_then(|it| it.schemas.as_ref())
+        .unwrap_or(&empty);
+
+    let mut names = BTreeMap::<String, String>::new();
+    for key in schemas.keys() {
+        let name = ident(&pascal_case(key));
+        if let Some((other, _)) = names.iter().find(|(_, it)| **it == name) {
+            bail!("component schemas `{other}` and `{key}` would both be named `{name}`")
+        }
+        names.insert(key.clone(), name);
+    }
+    let mut cx = Context {
+        names: &names,
+        fallbacks: vec![],
+    };
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// This file is @generated by `tool openrpc codegen rust` from {} {}",
+        document.info.title, document.info.version
+    )
+    .unwrap();
+    out.push_str(PRELUDE);
+
+    for (key, schema) in schemas {
+        writeln!(out).unwrap();
+        item(&mut out, &mut cx, &names[key], key, schema);
+    }
+
+    writeln!(out, "\nimpl<T: Transport> Client<T> {{").unwrap();
+    let mut fn_names = BTreeSet::new();
+    for method in &document.methods {
+        let short = method.name.rsplit('.').next().unwrap_or(&method.name);
+        let mut fn_name = ident(&snake_case(short));
+        if !fn_names.insert(fn_name.clone()) {
+            fn_name = ident(&snake_case(&method.name));
+            if !fn_names.insert(fn_name.clone()) {
+                bail!(
+                    "method `{}` would be named `{fn_name}`, which is already taken",
+                    method.name
+                )
+            }
+        }
+        self::method(&mut out, &mut cx, &fn_name, method);
+    }
+    writeln!(out, "}}").unwrap();
+
+    Ok(Generated {
+        code: out,
+        fallbacks: cx.fallbacks,
+    })
+}
+
+const PRELUDE: &str = r#"
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+/// Sends a single JSON-RPC request, returning the `result` member of the response.
+pub trait Transport {
+    type Error: From<serde_json::Error>;
+    fn call(
+        &self,
+        method: &'static str,
+        params: serde_json::Value,
+    ) -> impl Future<Output = Result<serde_json::Value, Self::Error>> + Send;
+}
+
+pub struct Client<T>(pub T);
+"#;
+
+struct Context<'a> {
+    /// Component key to type name.
+    names: &'a BTreeMap<String, String>,
+    fallbacks: Vec<String>,
+}
+
+impl Context<'_> {
+    /// Record a fallback, returning the comment to place above the item.
+    fn fallback(&mut self, location: String, reason: String) -> String {
+        let comment = format!("// TODO: {location}: {reason}");
+        self.fallbacks.push(format!("{location}: {reason}"));
+        comment
+    }
+}
+
+fn item(out: &mut String, cx: &mut Context, name: &str, key: &str, schema: &Schema) {
+    if let Schema::Object(object) = schema {
+        docs(
+            out,
+            "",
+            object
+                .metadata
+                .as_ref()
+                .and_then(|it| it.description.as_deref()),
+        );
+        if let Some(variants) = string_enum(object) {
+            writeln!(
+                out,
+                "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]"
+            )
+            .unwrap();
+            writeln!(out, "pub enum {name} {{").unwrap();
+            for variant in variants {
+                writeln!(out, "    #[serde(rename = {:?})]", variant).unwrap();
+                writeln!(out, "    {},", ident(&pascal_case(variant))).unwrap();
+            }
+            writeln!(out, "}}").unwrap();
+            return;
+        }
+        if let (Some(SingleOrVec::Single(ty)), Some(object_validation)) =
+            (&object.instance_type, &object.object)
+        {
+            if **ty == InstanceType::Object && !object_validation.properties.is_empty() {
+                strukt(out, cx, name, key, object_validation);
+                return;
+            }
+        }
+    }
+    let mut todos = vec![];
+    let ty = ty(cx, schema, &mut |reason| todos.push(reason));
+    for reason in todos {
+        writeln!(
+            out,
+            "{}",
+            cx.fallback(format!("#/components/schemas/{key}"), reason)
+        )
+        .unwrap();
+    }
+    writeln!(out, "pub type {name} = {ty};").unwrap();
+}
+
+fn strukt(
+    out: &mut String,
+    cx: &mut Context,
+    name: &str,
+    key: &str,
+    ObjectValidation {
+        required,
+        properties,
+        ..
+    }: &ObjectValidation,
+) {
+    writeln!(
+        out,
+        "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]"
+    )
+    .unwrap();
+    writeln!(out, "pub struct {name} {{").unwrap();
+    let mut fields = BTreeSet::new();
+    for (ix, (property, schema)) in properties.iter().enumerate() {
+        let mut field = ident(&snake_case(property));
+        if field == "_" || !fields.insert(field.clone()) {
+            field = format!("field{ix}");
+            fields.insert(field.clone());
+        }
+        let mut todos = vec![];
+        let mut ty = ty(cx, schema, &mut |reason| todos.push(reason));
+        for reason in todos {
+            let comment = cx.fallback(format!("#/components/schemas/{key}/{property}"), reason);
+            writeln!(out, "    {comment}").unwrap();
+        }
+        if let Schema::Object(it) = schema {
+            docs(
+                out,
+                "    ",
+                it.metadata
+                    .as_ref()
+                    .and_then(|it| it.description.as_deref()),
+            );
+        }
+        writeln!(out, "    #[serde(rename = {:?})]", property).unwrap();
+        if !required.contains(property) {
+            writeln!(
+                out,
+                "    #[serde(default, skip_serializing_if = \"Option::is_none\")]"
+            )
+            .unwrap();
+            ty = optional(ty)
+        }
+        writeln!(out, "    pub {field}: {ty},").unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+}
+
+fn method(out: &mut String, cx: &mut Context, fn_name: &str, method: &resolved::Method) {
+    let resolved::Method {
+        name,
+        summary,
+        description,
+        params,
+        result,
+        deprecated,
+        param_structure,
+        ..
+    } = method;
+    let mut todos = vec![];
+    let mut args = vec![];
+    let mut names = BTreeSet::new();
+    for (
+        ix,
+        ContentDescriptor {
+            name: param,
+            required,
+            schema,
+            ..
+        },
+    ) in params.iter().enumerate()
+    {
+        let mut arg = ident(&snake_case(param));
+        if arg == "_" || arg == "self" || !names.insert(arg.clone()) {
+            arg = format!("param{ix}");
+            names.insert(arg.clone());
+        }
+        let ty = ty(cx, schema, &mut |reason| {
+            todos.push((format!("{name}/params/{param}"), reason))
+        });
+        let ty = match required.unwrap_or_default() {
+            true => ty,
+            false => optional(ty),
+        };
+        args.push((param, arg, ty));
+    }
+    let ret = match result {
+        Some(it) => ty(cx, &it.schema, &mut |reason| {
+            todos.push((format!("{name}/result"), reason))
+        }),
+        None => String::from("()"),
+    };
+
+    writeln!(out).unwrap();
+    for (location, reason) in todos {
+        writeln!(out, "    {}", cx.fallback(location, reason)).unwrap();
+    }
+    let text = summary
+        .iter()
+        .chain(description)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    docs(out, "    ", Some(text.as_str()).filter(|it| !it.is_empty()));
+    if deprecated.unwrap_or_default() {
+        writeln!(out, "    #[deprecated]").unwrap();
+    }
+    write!(out, "    pub async fn {fn_name}(&self").unwrap();
+    for (_, arg, ty) in &args {
+        write!(out, ", {arg}: {ty}").unwrap();
+    }
+    writeln!(out, ") -> Result<{ret}, T::Error> {{").unwrap();
+    match param_structure {
+        Some(ParamStructure::ByName) => {
+            writeln!(out, "        let mut params = serde_json::Map::new();").unwrap();
+            for (param, arg, _) in &args {
+                writeln!(
+                    out,
+                    "        params.insert(String::from({:?}), serde_json::to_value({arg})?);",
+                    param
+                )
+                .unwrap();
+            }
+            writeln!(
+                out,
+                "        let params = serde_json::Value::Object(params);"
+            )
+            .unwrap();
+        }
+        _ => {
+            write!(out, "        let params = serde_json::Value::Array(vec![").unwrap();
+            for (ix, (_, arg, _)) in args.iter().enumerate() {
+                if ix != 0 {
+                    write!(out, ", ").unwrap();
+                }
+                write!(out, "serde_json::to_value({arg})?").unwrap();
+            }
+            writeln!(out, "]);").unwrap();
+        }
+    }
+    match result {
+        Some(_) => writeln!(
+            out,
+            "        Ok(serde_json::from_value(self.0.call({:?}, params).await?)?)",
+            name
+        ),
+        None => writeln!(
+            out,
+            "        self.0.call({:?}, params).await?;\n        Ok(())",
+            name
+        ),
+    }
+    .unwrap();
+    writeln!(out, "    }}").unwrap();
+}
+
+/// Map `schema` to a Rust type, calling `fallback` with a reason for each
+/// part that had to fall back to [`VALUE`].
+fn ty(cx: &Context, schema: &Schema, fallback: &mut dyn FnMut(String)) -> String {
+    let object = match schema {
+        Schema::Bool(true) => return String::from(VALUE),
+        Schema::Bool(false) => {
+            fallback(String::from("`false` schema"));
+            return String::from(VALUE);
+        }
+        Schema::Object(it) => it,
+    };
+    let SchemaObject {
+        instance_type,
+        format,
+        enum_values,
+        const_value,
+        subschemas,
+        array,
+        object,
+        reference,
+        ..
+    } = object;
+    if let Some(reference) = reference {
+        return match ComponentRef::parse(reference)
+            .key_in(ComponentSection::Schemas)
+            .and_then(|it| cx.names.get(it))
+        {
+            Some(name) => name.clone(),
+            None => {
+                fallback(format!("unsupported $ref `{reference}`"));
+                String::from(VALUE)
+            }
+        };
+    }
+    if enum_values.is_some() || const_value.is_some() {
+        fallback(String::from("inline `enum` or `const`"));
+        return String::from(VALUE);
+    }
+    if let Some(subschemas) = subschemas {
+        if let Some(alternatives) = subschemas.any_of.as_ref().or(subschemas.one_of.as_ref()) {
+            if let [a, b] = alternatives.as_slice() {
+                match (is_null(a), is_null(b)) {
+                    (false, true) => return optional(ty(cx, a, fallback)),
+                    (true, false) => return optional(ty(cx, b, fallback)),
+                    _ => {}
+                }
+            }
+        }
+        fallback(String::from(
+            "`anyOf`, `oneOf` or `allOf` other than nullability",
+        ));
+        return String::from(VALUE);
+    }
+    let types = match instance_type {
+        None => return String::from(VALUE),
+        Some(SingleOrVec::Single(it)) => vec![**it],
+        Some(SingleOrVec::Vec(it)) => it.clone(),
+    };
+    let (nullable, types) = types
+        .into_iter()
+        .partition::<Vec<_>, _>(|it| *it == InstanceType::Null);
+    let ty = match types.as_slice() {
+        [] => String::from("()"),
+        [InstanceType::Boolean] => String::from("bool"),
+        [InstanceType::String] => String::from("String"),
+        [InstanceType::Number] => String::from("f64"),
+        [InstanceType::Integer] => match format.as_deref() {
+            Some(
+                it @ ("int8" | "int16" | "int32" | "int64" | "uint8" | "uint16" | "uint32"
+                | "uint64"),
+            ) => it.replace("int", "i").replace("ui", "u"),
+            _ => String::from("i64"),
+        },
+        [InstanceType::Array] => match array.as_ref().and_then(|it| it.items.as_ref()) {
+            Some(SingleOrVec::Single(it)) => format!("Vec<{}>", self::ty(cx, it, fallback)),
+            Some(SingleOrVec::Vec(it)) => format!(
+                "({},)",
+                it.iter()
+                    .map(|it| self::ty(cx, it, fallback))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            None => format!("Vec<{VALUE}>"),
+        },
+        [InstanceType::Object] => match object.as_deref() {
+            Some(ObjectValidation { properties, .. }) if !properties.is_empty() => {
+                fallback(String::from("inline object with `properties`"));
+                String::from(VALUE)
+            }
+            Some(ObjectValidation {
+                additional_properties: Some(it),
+                ..
+            }) => format!(
+                "std::collections::BTreeMap<String, {}>",
+                self::ty(cx, it, fallback)
+            ),
+            _ => format!("serde_json::Map<String, {VALUE}>"),
+        },
+        _ => {
+            fallback(String::from("multiple non-null `type`s"));
+            String::from(VALUE)
+        }
+    };
+    match nullable.is_empty() || types.is_empty() {
+        true => ty,
+        false => optional(ty),
+    }
+}
+
+fn is_null(schema: &Schema) -> bool {
+    matches!(
+        schema,
+        Schema::Object(SchemaObject {
+            instance_type: Some(SingleOrVec::Single(it)),
+            ..
+        }) if **it == InstanceType::Null
+    )
+}
+
+/// The variants of a schema of the form `{"type": "string", "enum": [...]}`.
+fn string_enum(object: &SchemaObject) -> Option<Vec<&str>> {
+    match (&object.instance_type, &object.enum_values) {
+        (Some(SingleOrVec::Single(ty)), Some(values)) if **ty == InstanceType::String => values
+            .iter()
+            .map(Value::as_str)
+            .collect::<Option<Vec<_>>>()
+            .filter(|it| {
+                let idents = it
+                    .iter()
+                    .map(|it| ident(&pascal_case(it)))
+                    .collect::<BTreeSet<_>>();
+                !it.is_empty() && idents.len() == it.len() && !idents.contains("_")
+            }),
+        _ => None,
+    }
+}
+
+fn optional(ty: String) -> String {
+    match ty.starts_with("Option<") {
+        true => ty,
+        false => format!("Option<{ty}>"),
+    }
+}
+
+fn docs(out: &mut String, indent: &str, text: Option<&str>) {
+    if let Some(text) = text {
+        for line in text.trim().lines() {
+            match line.is_empty() {
+                true => writeln!(out, "{indent}///").unwrap(),
+                false => writeln!(out, "{indent}/// {line}").unwrap(),
+            }
+        }
+    }
+}
+
+/// Make `s` a valid Rust identifier.
+fn ident(s: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe",
+        "use", "where", "while", "abstract", "become", "box", "do", "final", "macro", "override",
+        "priv", "try", "typeof", "unsized", "virtual", "yield",
+    ];
+    match s {
+        "" => String::from("_"),
+        "self" | "Self" | "super" | "crate" => format!("{s}_"),
+        s if KEYWORDS.contains(&s) => format!("r#{s}"),
+        s if s.starts_with(|c: char| c.is_ascii_digit()) => format!("_{s}"),
+        s => s.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, process::Command};
+
+    use openrpc_types::OpenRPC;
+
+    use super::*;
+    use crate::chains::resolve_within;
+
+    /// The bindings for the spec compile.
+    #[test]
+    fn compiles() {
+        let spec = serde_json::from_str::<OpenRPC>(include_str!("../../../spec.json")).unwrap();
+        let Generated { code, .. } = generate(&resolve_within(spec).unwrap()).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            r#"
+[package]
+name = "bindings"
+version = "0.0.0"
+edition = "2021"
+
+[lib]
+path = "lib.rs"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+serde_json = "1"
+"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("lib.rs"), code).unwrap();
+        let output = Command::new(env!("CARGO"))
+            .args(["check", "--quiet", "--manifest-path"])
+            .arg(dir.path().join("Cargo.toml"))
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
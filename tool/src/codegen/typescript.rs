@@ -0,0 +1,518 @@
+//! TypeScript type definitions.
+//!
+//! - Component schemas become an `interface` if they are objects with
+//!   `properties`, or a `type` alias otherwise.
+//! - `Methods` maps each method name to its `params` tuple and `result`.
+//!
+//! Component keys are sanitized into identifiers by replacing every character
+//! other than `[A-Za-z0-9_$]` with `_`, prefixing a `_` if the key starts with
+//! a digit, and suffixing a `_` to reserved words.
+//! Two keys sanitizing to the same identifier is an error.
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use anyhow::bail;
+use openrpc_types::{resolved, ParamStructure};
+use schemars::schema::{InstanceType, ObjectValidation, Schema, SchemaObject, SingleOrVec};
+
+use crate::component_ref::{ComponentRef, ComponentSection};
+
+pub fn generate(document: &resolved::OpenRPC) -> anyhow::Result<String> {
+    let empty = BTreeMap::new();
+    let schemas = document
+        .components
+        .as_ref()
+        .and_then(|it| it.schemas.as_ref())
+        .unwrap_or(&empty);
+
+    let mut names = BTreeMap::<String, String>::new();
+    for key in schemas.keys() {
+        let name = ident(key);
+        if let Some((other, _)) = names.iter().find(|(_, it)| **it == name) {
+            bail!("component schemas `{other}` and `{key}` would both be named `{name}`")
+        }
+        names.insert(key.clone(), name);
+    }
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// This file is @generated by `tool openrpc codegen typescript` from {} {}",
+        document.info.title, document.info.version
+    )
+    .unwrap();
+
+    for (key, schema) in schemas {
+        let name = &names[key];
+        writeln!(out).unwrap();
+        if let Schema::Object(object) = schema {
+            jsdoc(&mut out, "", description(object));
+        }
+        match schema {
+            Schema::Object(SchemaObject {
+                instance_type: Some(SingleOrVec::Single(ty)),
+                object: Some(object),
+                ..
+            }) if **ty == InstanceType::Object && !object.properties.is_empty() => {
+                writeln!(out, "export interface {name} {}", body(&names, object, "")).unwrap()
+            }
+            schema => writeln!(out, "export type {name} = {};", ty(&names, schema, "")).unwrap(),
+        }
+    }
+
+    writeln!(out, "\nexport interface Methods {{").unwrap();
+    for resolved::Method {
+        name,
+        summary,
+        description,
+        params,
+        result,
+        deprecated,
+        param_structure,
+        ..
+    } in &document.methods
+    {
+        let mut text = summary
+            .iter()
+            .chain(description)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        if deprecated.unwrap_or_default() {
+            text.push_str("\n\n@deprecated");
+        }
+        jsdoc(&mut out, "  ", Some(text.as_str()));
+        writeln!(out, "  {:?}: {{", name).unwrap();
+        let params = match param_structure {
+            Some(ParamStructure::ByName) => format!(
+                "{{ {} }}",
+                params
+                    .iter()
+                    .map(|it| format!(
+                        "{}{}: {};",
+                        property(&it.name),
+                        if it.required.unwrap_or_default() {
+                            ""
+                        } else {
+                            "?"
+                        },
+                        ty(&names, &it.schema, "    ")
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            _ => format!(
+                "[{}]",
+                params
+                    .iter()
+                    .map(|it| format!(
+                        "{}{}: {}",
+                        ident(&it.name),
+                        if it.required.unwrap_or_default() {
+                            ""
+                        } else {
+                            "?"
+                        },
+                        ty(&names, &it.schema, "    ")
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        writeln!(out, "    params: {params};").unwrap();
+        let result = match result {
+            Some(it) => ty(&names, &it.schema, "    "),
+            None => String::from("void"),
+        };
+        writeln!(out, "    result: {result};").unwrap();
+        writeln!(out, "  }};").unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    Ok(out)
+}
+
+/// The body of an interface or inline object type, `indent` is that of the
+/// line the body opens on.
+fn body(names: &BTreeMap<String, String>, object: &ObjectValidation, indent: &str) -> String {
+    let ObjectValidation {
+        required,
+        properties,
+        additional_properties,
+        ..
+    } = object;
+    let inner = format!("{indent}  ");
+    let mut out = String::from("{\n");
+    for (name, schema) in properties {
+        if let Schema::Object(it) = schema {
+            jsdoc(&mut out, &inner, description(it));
+        }
+        writeln!(
+            out,
+            "{inner}{}{}: {};",
+            property(name),
+            if required.contains(name) { "" } else { "?" },
+            ty(names, schema, &inner)
+        )
+        .unwrap();
+    }
+    match additional_properties.as_deref() {
+        None | Some(Schema::Bool(false)) => {}
+        Some(schema) => {
+            writeln!(out, "{inner}[key: string]: {};", ty(names, schema, &inner)).unwrap()
+        }
+    }
+    write!(out, "{indent}}}").unwrap();
+    out
+}
+
+fn ty(names: &BTreeMap<String, String>, schema: &Schema, indent: &str) -> String {
+    let object = match schema {
+        Schema::Bool(true) => return String::from("unknown"),
+        Schema::Bool(false) => return String::from("never"),
+        Schema::Object(it) => it,
+    };
+    let SchemaObject {
+        instance_type,
+        enum_values,
+        const_value,
+        subschemas,
+        array,
+        object,
+        reference,
+        ..
+    } = object;
+    if let Some(reference) = reference {
+        return match ComponentRef::parse(reference)
+            .key_in(ComponentSection::Schemas)
+            .and_then(|it| names.get(it))
+        {
+            Some(name) => name.clone(),
+            None => String::from("unknown"),
+        };
+    }
+    if let Some(it) = const_value {
+        return it.to_string();
+    }
+    if let Some(it) = enum_values {
+        return union(it.iter().map(ToString::to_string));
+    }
+    if let Some(subschemas) = subschemas {
+        if let Some(it) = subschemas.any_of.as_ref().or(subschemas.one_of.as_ref()) {
+            return union(it.iter().map(|it| ty(names, it, indent)));
+        }
+        if let Some(it) = &subschemas.all_of {
+            return it
+                .iter()
+                .map(|it| ty(names, it, indent))
+                .collect::<Vec<_>>()
+                .join(" & ");
+        }
+    }
+    let types = match instance_type {
+        None => return String::from("unknown"),
+        Some(SingleOrVec::Single(it)) => vec![**it],
+        Some(SingleOrVec::Vec(it)) => it.clone(),
+    };
+    union(types.into_iter().map(|it| match it {
+        InstanceType::Null => String::from("null"),
+        InstanceType::Boolean => String::from("boolean"),
+        InstanceType::Number | InstanceType::Integer => String::from("number"),
+        InstanceType::String => String::from("string"),
+        InstanceType::Array => match array.as_ref().and_then(|it| it.items.as_ref()) {
+            Some(SingleOrVec::Single(it)) => match ty(names, it, indent) {
+                it if it.contains(' ') => format!("Array<{it}>"),
+                it => format!("{it}[]"),
+            },
+            Some(SingleOrVec::Vec(it)) => format!(
+                "[{}]",
+                it.iter()
+                    .map(|it| ty(names, it, indent))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            None => String::from("unknown[]"),
+        },
+        InstanceType::Object => match object.as_deref() {
+            Some(it) if !it.properties.is_empty() || it.additional_properties.is_some() => {
+                body(names, it, indent)
+            }
+            _ => String::from("Record<string, unknown>"),
+        },
+    }))
+}
+
+fn union(members: impl IntoIterator<Item = String>) -> String {
+    let mut members = members.into_iter().collect::<Vec<_>>();
+    members.dedup();
+    match members.len() {
+        0 => String::from("never"),
+        _ => members.join(" | "),
+    }
+}
+
+fn description(object: &SchemaObject) -> Option<&str> {
+    object
+        .metadata
+        .as_ref()
+        .and_then(|it| it.description.as_deref())
+}
+
+fn jsdoc(out: &mut String, indent: &str, text: Option<&str>) {
+    let Some(text) = text.map(str::trim).filter(|it| !it.is_empty()) else {
+        return;
+    };
+    writeln!(out, "{indent}/**").unwrap();
+    for line in text.lines() {
+        match line.is_empty() {
+            true => writeln!(out, "{indent} *").unwrap(),
+            false => writeln!(out, "{indent} * {}", line.replace("*/", "*\\/")).unwrap(),
+        }
+    }
+    writeln!(out, "{indent} */").unwrap();
+}
+
+/// A property name, quoted if it isn't a valid identifier.
+fn property(name: &str) -> String {
+    match is_ident(name) {
+        true => name.to_owned(),
+        false => format!("{:?}", name),
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// See the [module documentation](self) for the sanitization scheme.
+fn ident(s: &str) -> String {
+    const RESERVED: &[&str] = &[
+        "any",
+        "boolean",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "never",
+        "new",
+        "null",
+        "number",
+        "object",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "static",
+        "string",
+        "super",
+        "switch",
+        "symbol",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "type",
+        "typeof",
+        "undefined",
+        "unknown",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+    ];
+    let mut s = s
+        .chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || c == '_' || c == '$' {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect::<String>();
+    if s.is_empty() || s.starts_with(|c: char| c.is_ascii_digit()) {
+        s.insert(0, '_')
+    }
+    if RESERVED.contains(&s.as_str()) {
+        s.push('_')
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use openrpc_types::OpenRPC;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::chains::resolve_within;
+
+    fn schema(it: Value) -> Schema {
+        serde_json::from_value(it).unwrap()
+    }
+
+    #[test]
+    fn golden() {
+        let names = BTreeMap::from([(String::from("Cid"), String::from("Cid"))]);
+        for (it, expected) in [
+            (json!(true), "unknown"),
+            (json!(false), "never"),
+            (json!({}), "unknown"),
+            (json!({ "const": 1 }), "1"),
+            (json!({ "const": "bls" }), r#""bls""#),
+            (json!({ "enum": [1, "two"] }), r#"1 | "two""#),
+            (json!({ "$ref": "#/components/schemas/Cid" }), "Cid"),
+            (json!({ "$ref": "#/components/schemas/Missing" }), "unknown"),
+            (json!({ "type": "integer" }), "number"),
+            (json!({ "type": ["string", "null"] }), "string | null"),
+            (json!({ "type": ["number", "integer"] }), "number"),
+            (
+                json!({ "type": "array", "items": { "$ref": "#/components/schemas/Cid" } }),
+                "Cid[]",
+            ),
+            (
+                json!({ "type": "array", "items": { "type": ["string", "null"] } }),
+                "Array<string | null>",
+            ),
+            (
+                json!({ "type": "array", "items": [{ "type": "string" }, { "type": "integer" }] }),
+                "[string, number]",
+            ),
+            (json!({ "type": "array" }), "unknown[]"),
+            (json!({ "type": "object" }), "Record<string, unknown>"),
+            (
+                json!({ "type": "object", "additionalProperties": { "type": "integer" } }),
+                "{\n  [key: string]: number;\n}",
+            ),
+            (
+                json!({
+                    "type": "object",
+                    "properties": { "a": { "type": "string" }, "b-c": {} },
+                    "required": ["a"]
+                }),
+                "{\n  a: string;\n  \"b-c\"?: unknown;\n}",
+            ),
+            (
+                json!({ "oneOf": [{ "type": "integer" }, { "type": "null" }] }),
+                "number | null",
+            ),
+            (
+                json!({ "allOf": [{ "$ref": "#/components/schemas/Cid" }, { "type": "object" }] }),
+                "Cid & Record<string, unknown>",
+            ),
+        ] {
+            assert_eq!(ty(&names, &schema(it.clone()), ""), expected, "{}", it)
+        }
+    }
+
+    #[test]
+    fn idents() {
+        for (it, expected) in [
+            ("Cid", "Cid"),
+            ("a.b", "a_b"),
+            ("0x", "_0x"),
+            ("type", "type_"),
+            ("", "_"),
+        ] {
+            assert_eq!(ident(it), expected)
+        }
+    }
+
+    #[test]
+    fn document() {
+        let document = serde_json::from_value::<OpenRPC>(json!({
+            "openrpc": "1.3.2",
+            "info": { "title": "golden", "version": "0.0.0" },
+            "methods": [
+                {
+                    "name": "Filecoin.ChainHead",
+                    "summary": "The head.",
+                    "deprecated": true,
+                    "params": [
+                        {
+                            "name": "tipset key",
+                            "required": true,
+                            "schema": { "$ref": "#/components/schemas/Cid" }
+                        },
+                        { "name": "n", "schema": { "type": "integer" } }
+                    ],
+                    "result": {
+                        "name": "head",
+                        "schema": { "$ref": "#/components/schemas/Nullable" }
+                    }
+                },
+                {
+                    "name": "Filecoin.Version",
+                    "paramStructure": "by-name",
+                    "params": [{ "name": "verbose", "schema": { "type": "boolean" } }]
+                }
+            ],
+            "components": {
+                "schemas": {
+                    "Cid": {
+                        "description": "A CID.",
+                        "type": "object",
+                        "properties": { "/": { "type": "string" } },
+                        "required": ["/"]
+                    },
+                    "Nullable": { "type": ["string", "null"] }
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            generate(&resolve_within(document).unwrap()).unwrap(),
+            r#"// This file is @generated by `tool openrpc codegen typescript` from golden 0.0.0
+
+/**
+ * A CID.
+ */
+export interface Cid {
+  "/": string;
+}
+
+export type Nullable = string | null;
+
+export interface Methods {
+  /**
+   * The head.
+   *
+   * @deprecated
+   */
+  "Filecoin.ChainHead": {
+    params: [tipset_key: Cid, n?: number];
+    result: Nullable;
+  };
+  "Filecoin.Version": {
+    params: { verbose?: boolean; };
+    result: void;
+  };
+}
+"#
+        )
+    }
+}
@@ -0,0 +1,244 @@
+//! `$ref`s into `#/components`, parsed once rather than with `strip_prefix`
+//! at each use.
+//!
+//! The key is the component's key as written in the document, so `~1` and
+//! `~0` are unescaped as JSON pointer segments, and `%xx` as in a URI
+//! fragment.
+//! [`Display`](fmt::Display) writes the canonical form back.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComponentSection {
+    Schemas,
+    ContentDescriptors,
+    Examples,
+    Errors,
+    Tags,
+    ExamplePairingObjects,
+    Links,
+}
+
+impl ComponentSection {
+    pub const ALL: &'static [Self] = &[
+        Self::Schemas,
+        Self::ContentDescriptors,
+        Self::Examples,
+        Self::Errors,
+        Self::Tags,
+        Self::ExamplePairingObjects,
+        Self::Links,
+    ];
+    /// As it appears in `components`, like `contentDescriptors`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Schemas => "schemas",
+            Self::ContentDescriptors => "contentDescriptors",
+            Self::Examples => "examples",
+            Self::Errors => "errors",
+            Self::Tags => "tags",
+            Self::ExamplePairingObjects => "examplePairingObjects",
+            Self::Links => "links",
+        }
+    }
+}
+
+impl fmt::Display for ComponentSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComponentRef {
+    /// Like `#/components/schemas/TipSetKey`.
+    Component {
+        section: ComponentSection,
+        key: String,
+    },
+    /// Into another document, like `common.json#/components/schemas/Cid`.
+    External(String),
+    /// Anything else, like `#/$defs/Cid`, `#/components/schemas` or
+    /// `#/components/schemas/Cid/properties`.
+    Malformed(String),
+}
+
+impl ComponentRef {
+    pub fn new(section: ComponentSection, key: impl Into<String>) -> Self {
+        Self::Component {
+            section,
+            key: key.into(),
+        }
+    }
+    pub fn schema(key: impl Into<String>) -> Self {
+        Self::new(ComponentSection::Schemas, key)
+    }
+    pub fn parse(reference: &str) -> Self {
+        let malformed = || Self::Malformed(reference.to_owned());
+        let fragment = match reference.split_once('#') {
+            Some(("", fragment)) => fragment,
+            Some(_) => return Self::External(reference.to_owned()),
+            None => return malformed(),
+        };
+        let Some(fragment) = percent_decode(fragment) else {
+            return malformed();
+        };
+        let Some(rest) = fragment.strip_prefix("/components/") else {
+            return malformed();
+        };
+        let Some((section, key)) = rest.split_once('/') else {
+            return malformed();
+        };
+        let Some(&section) = ComponentSection::ALL.iter().find(|it| it.name() == section) else {
+            return malformed();
+        };
+        match key.is_empty() || key.contains('/') {
+            true => malformed(),
+            false => Self::new(section, unescape(key)),
+        }
+    }
+    /// The key, if this is a `$ref` into `section`.
+    pub fn key_in(&self, section: ComponentSection) -> Option<&str> {
+        match self {
+            Self::Component { section: it, key } if *it == section => Some(key),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ComponentRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Component { section, key } => {
+                write!(
+                    f,
+                    "#/components/{}/{}",
+                    section,
+                    percent_encode(&escape(key))
+                )
+            }
+            Self::External(it) | Self::Malformed(it) => f.write_str(it),
+        }
+    }
+}
+
+/// As a JSON pointer segment.
+pub fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+fn percent_decode(it: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut rest = it.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match byte {
+            b'%' => {
+                let hex = std::str::from_utf8(tail.get(..2)?)
+                    .ok()
+                    .filter(|it| it.bytes().all(|it| it.is_ascii_hexdigit()))?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &tail[2..]
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Characters which may appear as they are in a URI fragment are left alone.
+fn percent_encode(it: &str) -> String {
+    let mut encoded = String::new();
+    for byte in it.bytes() {
+        match byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&byte) {
+            true => encoded.push(char::from(byte)),
+            false => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for (key, written) in [
+            ("TipSetKey", "TipSetKey"),
+            ("a/b", "a~1b"),
+            ("a~b", "a~0b"),
+            ("~1", "~01"),
+            ("a b", "a%20b"),
+            ("100%", "100%25"),
+        ] {
+            for &section in ComponentSection::ALL {
+                let reference = ComponentRef::new(section, key);
+                let canonical = format!("#/components/{}/{}", section, written);
+                assert_eq!(reference.to_string(), canonical);
+                assert_eq!(ComponentRef::parse(&canonical), reference);
+            }
+        }
+    }
+
+    #[test]
+    fn escapes() {
+        for (reference, key) in [
+            ("#/components/schemas/a~1b", "a/b"),
+            ("#/components/schemas/a~0b", "a~b"),
+            // `~0` is unescaped last, so this isn't a `/`
+            ("#/components/schemas/~01", "~1"),
+            // percent-decoding comes first
+            ("#/components/schemas/a%7E1b", "a/b"),
+            ("#/components/schemas/%54ipSetKey", "TipSetKey"),
+        ] {
+            assert_eq!(
+                ComponentRef::parse(reference).key_in(ComponentSection::Schemas),
+                Some(key),
+                "{}",
+                reference
+            );
+        }
+    }
+
+    #[test]
+    fn external() {
+        let reference = "common.json#/components/schemas/Cid";
+        assert_eq!(
+            ComponentRef::parse(reference),
+            ComponentRef::External(String::from(reference))
+        );
+        assert_eq!(ComponentRef::parse(reference).to_string(), reference);
+    }
+
+    #[test]
+    fn malformed() {
+        for reference in [
+            "",
+            "Cid",
+            "#",
+            "#/$defs/Cid",
+            "#/components/schemas",
+            "#/components/schemas/",
+            "#/components/schemas/Cid/properties",
+            "#/components/unknown/Cid",
+            "#/components/schemas/%zz",
+            "#/components/schemas/%E2%82",
+        ] {
+            let parsed = ComponentRef::parse(reference);
+            assert_eq!(
+                parsed,
+                ComponentRef::Malformed(String::from(reference)),
+                "{}",
+                reference
+            );
+            assert_eq!(parsed.to_string(), reference);
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Defaults for command-line flags from a `tool.toml`.
+//!
+//! Each table is a (sub)command, and each key is the long name of one of its
+//! flags (or the name of a positional argument):
+//! ```toml
+//! [openrpc]
+//! cache = ".cache"
+//!
+//! [openrpc.select]
+//! prefix = "Filecoin."
+//! ```
+//! Values become the flag's default, so explicit flags always win.
+
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context as _};
+use clap::{parser::ValueSource, Arg, ArgAction, ArgMatches, Command};
+
+pub const FILE_NAME: &str = "tool.toml";
+const XDG_DIR: &str = "filecoin-openrpc-tool";
+
+pub struct Config {
+    pub path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    /// The subcommand names leading to the flag.
+    commands: Vec<String>,
+    key: String,
+    values: Vec<String>,
+}
+
+impl Entry {
+    fn dotted(&self) -> String {
+        self.commands
+            .iter()
+            .chain([&self.key])
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+/// The first of `explicit`, `./tool.toml`, and
+/// `$XDG_CONFIG_HOME/filecoin-openrpc-tool/config.toml` which exists.
+///
+/// It is an error for `explicit` not to exist.
+pub fn locate(explicit: Option<PathBuf>) -> anyhow::Result<Option<PathBuf>> {
+    if let Some(path) = explicit {
+        if !path.is_file() {
+            bail!("config file {} doesn't exist", path.display())
+        }
+        return Ok(Some(path));
+    }
+    let xdg = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|it| Path::new(&it).join(".config")))
+        .map(|it| it.join(XDG_DIR).join("config.toml"));
+    Ok([PathBuf::from(FILE_NAME)]
+        .into_iter()
+        .chain(xdg)
+        .find(|it| it.is_file()))
+}
+
+pub fn load(path: PathBuf) -> anyhow::Result<Config> {
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("couldn't read from file {}", path.display()))?;
+    let table = toml::from_str::<toml::Table>(&text)
+        .with_context(|| format!("couldn't parse toml from file {}", path.display()))?;
+    let mut entries = vec![];
+    flatten(&table, &mut vec![], &mut entries)
+        .with_context(|| format!("in config file {}", path.display()))?;
+    Ok(Config { path, entries })
+}
+
+fn flatten(
+    table: &toml::Table,
+    commands: &mut Vec<String>,
+    entries: &mut Vec<Entry>,
+) -> anyhow::Result<()> {
+    for (key, value) in table {
+        let scalar = |it: &toml::Value| match it {
+            toml::Value::String(it) => Ok(it.clone()),
+            toml::Value::Integer(it) => Ok(it.to_string()),
+            toml::Value::Float(it) => Ok(it.to_string()),
+            toml::Value::Boolean(it) => Ok(it.to_string()),
+            toml::Value::Datetime(it) => Ok(it.to_string()),
+            toml::Value::Array(_) | toml::Value::Table(_) => {
+                let mut path = commands.clone();
+                path.push(key.clone());
+                bail!("`{}` must be a list of scalars", path.join("."))
+            }
+        };
+        let values = match value {
+            toml::Value::Table(table) => {
+                commands.push(key.clone());
+                flatten(table, commands, entries)?;
+                commands.pop();
+                continue;
+            }
+            toml::Value::Array(it) => it.iter().map(scalar).collect::<Result<_, _>>()?,
+            it => vec![scalar(it)?],
+        };
+        entries.push(Entry {
+            commands: commands.clone(),
+            key: key.clone(),
+            values,
+        })
+    }
+    Ok(())
+}
+
+/// Set the defaults in `config` on `command`.
+///
+/// Errors name the offending key if it isn't a subcommand or flag.
+pub fn apply(mut command: Command, config: &Config) -> anyhow::Result<Command> {
+    for entry in &config.entries {
+        command = set(command, &entry.commands, entry).with_context(|| {
+            format!(
+                "unknown key `{}` in config file {}",
+                entry.dotted(),
+                config.path.display()
+            )
+        })?;
+    }
+    Ok(command)
+}
+
+fn set(command: Command, commands: &[String], entry: &Entry) -> anyhow::Result<Command> {
+    match commands.split_first() {
+        Some((first, rest)) => {
+            let Some(sub) = command.find_subcommand(first) else {
+                bail!("{} has no subcommand {}", command.get_name(), first)
+            };
+            let sub = set(sub.clone(), rest, entry)?;
+            Ok(command.mut_subcommand(first, |_| sub))
+        }
+        None => {
+            let Some(id) = find(&command, &entry.key).map(|it| it.get_id().clone()) else {
+                bail!("{} has no flag --{}", command.get_name(), entry.key)
+            };
+            let values = entry.values.clone();
+            Ok(command.mut_arg(id, |it| it.default_values(values)))
+        }
+    }
+}
+
+fn find<'a>(command: &'a Command, key: &str) -> Option<&'a Arg> {
+    command.get_arguments().find(|it| {
+        it.get_long() == Some(key)
+            || (it.is_positional() && it.get_id().as_str() == key.replace('-', "_"))
+    })
+}
+
+pub enum Source<'a> {
+    Default,
+    File(&'a Path),
+    Cli,
+}
+
+impl fmt::Display for Source<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => f.write_str("default"),
+            Source::File(it) => write!(f, "file {}", it.display()),
+            Source::Cli => f.write_str("cli"),
+        }
+    }
+}
+
+/// Each value which a flag would take, grouped by (sub)command, as TOML with
+/// the source of each value in a comment.
+///
+/// If `matches` are given, only the values for that invocation are shown.
+/// Otherwise, every flag with a default is.
+pub fn show(command: &Command, config: Option<&Config>, matches: Option<&ArgMatches>) -> String {
+    let mut out = String::new();
+    show_one(command, &mut vec![], config, matches, &mut out);
+    out
+}
+
+fn show_one(
+    command: &Command,
+    path: &mut Vec<String>,
+    config: Option<&Config>,
+    matches: Option<&ArgMatches>,
+    out: &mut String,
+) {
+    let from_file = |key: &str| {
+        config.filter(|config| {
+            config.entries.iter().any(|it| {
+                it.commands == *path
+                    && find(command, &it.key).is_some_and(|it| it.get_id().as_str() == key)
+            })
+        })
+    };
+    let mut lines = vec![];
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        let key = arg
+            .get_long()
+            .map_or_else(|| id.replace('_', "-"), str::to_owned);
+        let (values, source) = match matches {
+            Some(matches) => {
+                let Some(values) = matches.get_raw(id) else {
+                    continue;
+                };
+                let values = values
+                    .map(|it| it.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>();
+                let source = match (matches.value_source(id), from_file(id)) {
+                    (Some(ValueSource::DefaultValue), Some(config)) => Source::File(&config.path),
+                    (Some(ValueSource::DefaultValue), None) => Source::Default,
+                    _ => Source::Cli,
+                };
+                (values, source)
+            }
+            None => {
+                let values = arg
+                    .get_default_values()
+                    .iter()
+                    .map(|it| it.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>();
+                if values.is_empty() || values == ["false"] {
+                    continue;
+                }
+                let source = match from_file(id) {
+                    Some(config) => Source::File(&config.path),
+                    None => Source::Default,
+                };
+                (values, source)
+            }
+        };
+        let value = match values.as_slice() {
+            [one] if !matches!(arg.get_action(), ArgAction::Append) => quote(one),
+            many => format!(
+                "[{}]",
+                many.iter()
+                    .map(|it| quote(it))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        lines.push(format!("{} = {}  # {}\n", key, value, source))
+    }
+    if !lines.is_empty() {
+        if !path.is_empty() {
+            out.push_str(&format!("[{}]\n", path.join(".")));
+        }
+        out.extend(lines);
+        out.push('\n');
+    }
+    for sub in command.get_subcommands() {
+        let sub_matches = match matches {
+            Some(matches) => match matches.subcommand() {
+                Some((name, it)) if name == sub.get_name() => Some(it),
+                _ => continue,
+            },
+            None => None,
+        };
+        path.push(sub.get_name().to_owned());
+        show_one(sub, path, config, sub_matches, out);
+        path.pop();
+    }
+}
+
+fn quote(s: &str) -> String {
+    toml::Value::String(s.to_owned()).to_string()
+}
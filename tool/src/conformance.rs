@@ -0,0 +1,380 @@
+//! Run a file of test cases against a live node.
+//!
+//! Cases are a JSON array of objects like:
+//!
+//! ```json
+//! {
+//!   "name": "head is recent",
+//!   "method": "Filecoin.ChainHead",
+//!   "params": [],
+//!   "assert": ["/Height >= 0"]
+//! }
+//! ```
+//!
+//! - `params` defaults to `[]`.
+//! - unless `error` is given, the response must have a result which matches
+//!   the method's [response envelope](crate::envelopes), as in
+//!   [`verify`](crate::verify).
+//! - `error` is the code of an error which the node must return instead.
+//! - each of `assert` is a JSON pointer into the result, an operator (`==`,
+//!   `!=`, `<`, `<=`, `>` or `>=`) and a JSON value.
+//! - methods which [change state](verify::MUTATING) need
+//!   `"allow_mutation": true`.
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    io::{self, Write as _},
+};
+
+use anyhow::{bail, Context as _};
+use jsonschema::JSONSchema;
+use openrpc_types::resolved;
+use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{envelopes, progress, source, verify};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// A JUnit XML test suite.
+    #[default]
+    Junit,
+    /// A JSON object with `cases`, `failed` and `results`.
+    Json,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Case {
+    /// Defaults to the method.
+    #[serde(default)]
+    pub name: Option<String>,
+    pub method: String,
+    #[serde(default = "no_params")]
+    pub params: Value,
+    #[serde(default)]
+    pub error: Option<i64>,
+    #[serde(default)]
+    pub assert: Vec<Assertion>,
+    #[serde(default)]
+    pub allow_mutation: bool,
+}
+
+fn no_params() -> Value {
+    json!([])
+}
+
+impl Case {
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.method)
+    }
+}
+
+/// Like `/Height >= 0`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Assertion {
+    pub pointer: String,
+    pub operator: Operator,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Operator {
+    const ALL: [Self; 6] = [Self::Eq, Self::Ne, Self::Lt, Self::Le, Self::Gt, Self::Ge];
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        }
+    }
+}
+
+impl TryFrom<String> for Assertion {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let bad = || format!("`{}` isn't of the form `<pointer> <operator> <json>`", s);
+        let mut parts = s.trim().splitn(3, ' ');
+        let (Some(pointer), Some(operator), Some(value)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(bad());
+        };
+        if !(pointer.is_empty() || pointer.starts_with('/')) {
+            return Err(format!("{} isn't a JSON pointer", pointer));
+        }
+        let Some(&operator) = Operator::ALL.iter().find(|it| it.symbol() == operator) else {
+            return Err(bad());
+        };
+        let value = serde_json::from_str(value.trim()).map_err(|e| format!("{}: {}", bad(), e))?;
+        Ok(Self {
+            pointer: pointer.to_owned(),
+            operator,
+            value,
+        })
+    }
+}
+
+impl Assertion {
+    /// Why `result` fails this assertion, if it does.
+    fn check(&self, result: &Value) -> Option<String> {
+        let Self {
+            pointer,
+            operator,
+            value,
+        } = self;
+        let Some(actual) = result.pointer(pointer) else {
+            return Some(format!("nothing at {}", pointer));
+        };
+        let ordering = match (actual, value) {
+            (Value::Number(l), Value::Number(r)) => l.as_f64().partial_cmp(&r.as_f64()),
+            (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+            _ => None,
+        };
+        let holds = match operator {
+            Operator::Eq => actual == value,
+            Operator::Ne => actual != value,
+            Operator::Lt => ordering == Some(Ordering::Less),
+            Operator::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Operator::Gt => ordering == Some(Ordering::Greater),
+            Operator::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        };
+        (!holds).then(|| {
+            format!(
+                "{} is {}, expected {} {}",
+                pointer,
+                actual,
+                operator.symbol(),
+                value
+            )
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Outcome {
+    pub name: String,
+    pub method: String,
+    /// Empty if the case passed.
+    pub failures: Vec<String>,
+}
+
+/// Check that each case names a method in `document`, and only mutates when
+/// allowed.
+pub fn load(document: &resolved::OpenRPC, cases: &[Case]) -> anyhow::Result<()> {
+    let methods = document
+        .methods
+        .iter()
+        .map(|it| (it.name.as_str(), it))
+        .collect::<BTreeMap<_, _>>();
+    for case in cases {
+        let Some(method) = methods.get(case.method.as_str()) else {
+            bail!(
+                "case {} calls {}, which isn't in the document",
+                case.name(),
+                case.method
+            )
+        };
+        if verify::is_mutating(method) && !case.allow_mutation {
+            bail!(
+                "case {} calls {}, which changes state, without `allow_mutation: true`",
+                case.name(),
+                case.method
+            )
+        }
+    }
+    Ok(())
+}
+
+/// Run `cases` against `remote`, `parallel` at a time, which must already be
+/// [`load`]ed.
+pub fn run(
+    document: &resolved::OpenRPC,
+    cases: &[Case],
+    remote: &str,
+    headers: &[(String, String)],
+    parallel: usize,
+) -> anyhow::Result<Vec<Outcome>> {
+    let envelopes = envelopes::envelopes(document)?;
+    let validators = cases
+        .iter()
+        .map(|it| it.method.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|method| Ok((method, verify::compile(&envelopes[method].response)?)))
+        .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
+    let bar = progress::Bar::new(cases.len(), "ran", "cases");
+    let one = |case: &Case| {
+        bar.show(case.name());
+        let failures = self::one(case, &validators[case.method.as_str()], remote, headers);
+        bar.inc();
+        Outcome {
+            name: case.name().to_owned(),
+            method: case.method.clone(),
+            failures,
+        }
+    };
+    let outcomes: Vec<Outcome> = match parallel {
+        0 | 1 => cases.iter().map(one).collect(),
+        n => rayon::ThreadPoolBuilder::new()
+            .num_threads(n)
+            .build()
+            .context("couldn't start threads")?
+            .install(|| cases.par_iter().map(one).collect()),
+    };
+    bar.finish();
+    Ok(outcomes)
+}
+
+fn one(
+    case: &Case,
+    validator: &JSONSchema,
+    remote: &str,
+    headers: &[(String, String)],
+) -> Vec<String> {
+    let response = match source::call(remote, headers, &case.method, case.params.clone()) {
+        Ok(it) => it,
+        Err(e) => return vec![format!("couldn't call {}: {:#}", case.method, e)],
+    };
+    let mut failures = verify::problems(validator, &response);
+    match (case.error, response.get("error")) {
+        (Some(code), Some(error)) => {
+            if error.get("code").and_then(Value::as_i64) != Some(code) {
+                failures.push(format!("expected error {}, got {}", code, error))
+            }
+        }
+        (Some(code), None) => failures.push(format!("expected error {}, got a result", code)),
+        (None, Some(error)) => failures.push(format!("returned an error: {}", error)),
+        (None, None) => {
+            let result = response.get("result").unwrap_or(&Value::Null);
+            failures.extend(case.assert.iter().filter_map(|it| it.check(result)))
+        }
+    }
+    failures
+}
+
+/// Write `outcomes` in `format`.
+pub fn report(outcomes: &[Outcome], format: Format, out: &mut dyn io::Write) -> anyhow::Result<()> {
+    let failed = outcomes.iter().filter(|it| !it.failures.is_empty()).count();
+    match format {
+        Format::Json => serde_json::to_writer_pretty(
+            out,
+            &json!({
+                "cases": outcomes.len(),
+                "failed": failed,
+                "results": outcomes,
+            }),
+        )?,
+        Format::Junit => {
+            let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+            writeln!(
+                xml,
+                "<testsuite name=\"conformance\" tests=\"{}\" failures=\"{}\">",
+                outcomes.len(),
+                failed
+            )?;
+            for Outcome {
+                name,
+                method,
+                failures,
+            } in outcomes
+            {
+                write!(
+                    xml,
+                    "  <testcase classname=\"{}\" name=\"{}\"",
+                    escape(method),
+                    escape(name)
+                )?;
+                match failures.first() {
+                    None => xml.push_str("/>\n"),
+                    Some(first) => writeln!(
+                        xml,
+                        ">\n    <failure message=\"{}\">{}</failure>\n  </testcase>",
+                        escape(first),
+                        escape(&failures.join("\n"))
+                    )?,
+                }
+            }
+            xml.push_str("</testsuite>\n");
+            out.write_all(xml.as_bytes())?
+        }
+    }
+    Ok(())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assertion(s: &str) -> Assertion {
+        Assertion::try_from(String::from(s)).unwrap()
+    }
+
+    #[test]
+    fn assertions() {
+        let result = json!({ "Height": 10, "Cids": [{ "/": "bafy" }] });
+        assert_eq!(assertion("/Height >= 0").check(&result), None);
+        assert_eq!(assertion("/Height < 10.5").check(&result), None);
+        assert_eq!(assertion("/Cids/0/~1 == \"bafy\"").check(&result), None);
+        assert!(assertion("/Height > 10").check(&result).is_some());
+        assert!(assertion("/Missing == 1").check(&result).is_some());
+        // values of different types aren't ordered
+        assert!(assertion("/Height >= \"0\"").check(&result).is_some());
+        assert!(Assertion::try_from(String::from("Height >= 0")).is_err());
+        assert!(Assertion::try_from(String::from("/Height => 0")).is_err());
+    }
+
+    #[test]
+    fn junit() {
+        let outcomes = [
+            Outcome {
+                name: String::from("head"),
+                method: String::from("Filecoin.ChainHead"),
+                failures: vec![],
+            },
+            Outcome {
+                name: String::from("<bad>"),
+                method: String::from("Filecoin.ChainHead"),
+                failures: vec![String::from("/Height is -1, expected >= 0")],
+            },
+        ];
+        let mut out = vec![];
+        report(&outcomes, Format::Junit, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="conformance" tests="2" failures="1">
+  <testcase classname="Filecoin.ChainHead" name="head"/>
+  <testcase classname="Filecoin.ChainHead" name="&lt;bad&gt;">
+    <failure message="/Height is -1, expected &gt;= 0">/Height is -1, expected &gt;= 0</failure>
+  </testcase>
+</testsuite>
+"#
+        );
+    }
+}
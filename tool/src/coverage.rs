@@ -0,0 +1,171 @@
+//! Which methods in a document are exercised by captured traffic.
+//!
+//! Traffic is newline-delimited JSON, optionally gzipped.
+//! Each line is a JSON-RPC request, a batch of requests, or an object with the
+//! request(s) in a `request` field.
+//! Lines which are none of these, like responses, are ignored.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufRead as _, BufReader, Read},
+    path::Path,
+};
+
+use anyhow::Context as _;
+use flate2::bufread::MultiGzDecoder;
+use openrpc_types::resolved;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::source::SpecSource;
+
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    /// Every method in the document.
+    pub methods: BTreeMap<String, Method>,
+    /// Methods which were called, but aren't in the document, and how many
+    /// times.
+    pub unknown: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Method {
+    pub calls: usize,
+    /// How many calls supplied each param.
+    pub params: BTreeMap<String, usize>,
+}
+
+impl Report {
+    pub fn new(document: &resolved::OpenRPC) -> Self {
+        Self {
+            methods: document
+                .methods
+                .iter()
+                .map(|method| {
+                    (
+                        method.name.clone(),
+                        Method {
+                            calls: 0,
+                            params: method
+                                .params
+                                .iter()
+                                .map(|it| (it.name.clone(), 0))
+                                .collect(),
+                        },
+                    )
+                })
+                .collect(),
+            unknown: BTreeMap::new(),
+        }
+    }
+
+    /// Count the requests in the traffic file at `path`, where `-` is stdin.
+    pub fn add(&mut self, document: &resolved::OpenRPC, path: &Path) -> anyhow::Result<()> {
+        let source = SpecSource::from_path(path);
+        let mut reader = BufReader::new(match &source {
+            SpecSource::Stdin => Box::new(io::stdin()) as Box<dyn Read>,
+            _ => Box::new(File::open(path).with_context(|| format!("couldn't open {}", source))?),
+        });
+        let gzipped = reader
+            .fill_buf()
+            .with_context(|| format!("couldn't read from {}", source))?
+            .starts_with(&[0x1f, 0x8b]);
+        let reader: Box<dyn Read> = match gzipped {
+            true => Box::new(MultiGzDecoder::new(reader)),
+            false => Box::new(reader),
+        };
+        for (ix, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.with_context(|| format!("couldn't read from {}", source))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let value = serde_json::from_str::<Value>(&line)
+                .with_context(|| format!("couldn't parse json on line {} of {}", ix + 1, source))?;
+            self.value(document, &value)
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, document: &resolved::OpenRPC, value: &Value) {
+        match value {
+            Value::Array(it) => {
+                for it in it {
+                    self.value(document, it)
+                }
+            }
+            Value::Object(it) => match (it.get("method"), it.get("request")) {
+                (Some(Value::String(name)), _) => self.request(document, name, it.get("params")),
+                (None, Some(request)) => self.value(document, request),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn request(&mut self, document: &resolved::OpenRPC, name: &str, params: Option<&Value>) {
+        let Some((method, coverage)) = document
+            .methods
+            .iter()
+            .find(|it| it.name == name)
+            .zip(self.methods.get_mut(name))
+        else {
+            *self.unknown.entry(name.to_owned()).or_default() += 1;
+            return;
+        };
+        coverage.calls += 1;
+        for (ix, param) in method.params.iter().enumerate() {
+            let supplied = match params {
+                Some(Value::Array(it)) => it.get(ix).is_some_and(|it| !it.is_null()),
+                Some(Value::Object(it)) => it.contains_key(&param.name),
+                _ => false,
+            };
+            if supplied {
+                *coverage.params.entry(param.name.clone()).or_default() += 1
+            }
+        }
+    }
+
+    /// The percentage of methods which were called at least once.
+    pub fn percent(&self) -> f64 {
+        match self.methods.len() {
+            0 => 100.0,
+            total => {
+                let called = self.methods.values().filter(|it| it.calls != 0).count();
+                called as f64 / total as f64 * 100.0
+            }
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let width = self
+            .methods
+            .values()
+            .map(|it| it.calls)
+            .chain(self.unknown.values().copied())
+            .max()
+            .unwrap_or_default()
+            .to_string()
+            .len();
+        for (name, Method { calls, params }) in &self.methods {
+            out.push_str(&format!("{calls:>width$} {name}\n"));
+            for (param, supplied) in params {
+                out.push_str(&format!("{:width$}   {param}: {supplied}/{calls}\n", ""));
+            }
+        }
+        if !self.unknown.is_empty() {
+            out.push_str("\nnot in the document:\n");
+            for (name, calls) in &self.unknown {
+                out.push_str(&format!("{calls:>width$} {name}\n"));
+            }
+        }
+        out.push_str(&format!(
+            "\n{}/{} methods called ({:.1}%)\n",
+            self.methods.values().filter(|it| it.calls != 0).count(),
+            self.methods.len(),
+            self.percent()
+        ));
+        out
+    }
+}
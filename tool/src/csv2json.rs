@@ -0,0 +1,845 @@
+//! Convert CSV records to JSON objects, keyed by the header.
+//!
+//! Cells are strings unless types are inferred or pinned, and empty cells are
+//! left out.
+//! With `nested`, a dotted header like `Limits.MaxParams` becomes a key in a
+//! nested object.
+//!
+//! Several inputs may be given, if their headers have the same columns, in
+//! any order.
+//!
+//! Records may be checked against a [`Validator`] as they are converted.
+//!
+//! The csv crate only splits on single bytes, so a longer [`Delimiter`] is
+//! split on before parsing, without regard to quotes.
+//!
+//! A record which can't be read or converted is a [`RowError`], which callers
+//! may skip.
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fs::File,
+    io::{self, Read as _},
+    path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+};
+
+use anyhow::{bail, Context as _};
+use clap::ValueEnum as _;
+use csv::StringRecord;
+use itertools::Itertools as _;
+use jsonschema::{Draft, JSONSchema};
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+use crate::{glob, source};
+
+pub struct Options {
+    /// Convert `true` and `false` to booleans, and numbers to numbers.
+    pub infer_types: bool,
+    /// Types for particular columns, which win over inference.
+    pub types: Vec<(String, Type)>,
+    pub nested: bool,
+    /// Add a column of this name, with the input each record came from.
+    pub source_column: Option<String>,
+    pub validator: Option<Validator>,
+}
+
+/// Parsed from a command-line argument, where `select` is the table read by
+/// the `select` subcommand, and anything else is the path to a JSON Schema.
+#[derive(Debug, Clone)]
+pub enum Schema {
+    Select,
+    Path(PathBuf),
+}
+
+impl FromStr for Schema {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "select" => Self::Select,
+            s => Self::Path(PathBuf::from(s)),
+        })
+    }
+}
+
+/// The columns of [`Select`](crate::Select).
+const SELECT_COLUMNS: &[&str] = &["Method", "Description", "Include"];
+
+/// Checks each converted record.
+pub enum Validator {
+    /// Records must deserialize as a [`Select`](crate::Select), with a
+    /// `Method` matching `method_pattern`, if given.
+    Select {
+        method_pattern: Option<String>,
+    },
+    JsonSchema(JSONSchema),
+}
+
+impl Validator {
+    pub fn new(schema: &Schema, method_pattern: Option<String>) -> anyhow::Result<Self> {
+        match schema {
+            Schema::Select => Ok(Self::Select { method_pattern }),
+            Schema::Path(path) => {
+                let schema = source::load_document::<Value>(
+                    &source::SpecSource::from_path(path),
+                    &source::FetchOptions::default(),
+                )?;
+                let compiled = JSONSchema::options()
+                    .with_draft(Draft::Draft202012)
+                    .compile(&schema)
+                    .map_err(|it| anyhow::anyhow!("{}", it))
+                    .with_context(|| format!("the schema {} doesn't compile", path.display()))?;
+                Ok(Self::JsonSchema(compiled))
+            }
+        }
+    }
+
+    /// What is wrong with `record`, each naming the column where it can.
+    fn check(&self, record: &Value) -> Vec<String> {
+        match self {
+            Self::Select { method_pattern } => {
+                let select: Result<crate::Select, _> = serde_path_to_error::deserialize(record);
+                match select {
+                    Err(e) => vec![match e.path().to_string().as_str() {
+                        "." => e.inner().to_string(),
+                        column => format!("column {}: {}", column, e.inner()),
+                    }],
+                    Ok(select) if select.method.is_empty() => {
+                        vec![String::from("column Method: is empty")]
+                    }
+                    Ok(select) => match method_pattern {
+                        Some(pattern) if !glob::matches(pattern, &select.method) => {
+                            vec![format!(
+                                "column Method: `{}` doesn't match `{}`",
+                                select.method, pattern
+                            )]
+                        }
+                        _ => vec![],
+                    },
+                }
+            }
+            Self::JsonSchema(compiled) => match compiled.validate(record) {
+                Ok(()) => vec![],
+                Err(errors) => errors
+                    .map(|it| match it.instance_path.to_string().as_str() {
+                        "" => it.to_string(),
+                        column => format!("column {}: {}", &column[1..], it),
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// How the inputs are written.
+pub struct Dialect {
+    pub delimiter: Delimiter,
+    /// Ignored for a [`Delimiter::Text`].
+    pub quote: u8,
+    /// Ignored for a [`Delimiter::Text`].
+    pub quoting: bool,
+    /// Trim whitespace around headers and cells.
+    pub trim: bool,
+}
+
+/// Parsed from a command-line argument, which may use the escapes that
+/// [`unescape`] accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Delimiter {
+    Byte(u8),
+    /// More than one character, or a single non-ASCII one.
+    Text(String),
+}
+
+impl FromStr for Delimiter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = unescape(s)?;
+        match text.as_bytes() {
+            [] => bail!("the delimiter is empty, {}", ACCEPTED),
+            [byte] => Ok(Self::Byte(*byte)),
+            _ => Ok(Self::Text(text)),
+        }
+    }
+}
+
+impl Delimiter {
+    fn to_text(&self) -> String {
+        match self {
+            Self::Byte(it) => char::from(*it).to_string(),
+            Self::Text(it) => it.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Delimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&escape(&self.to_text()))
+    }
+}
+
+pub const ACCEPTED: &str = "but expected a character like `,` or `|`, or an escape like `\\t`, \
+                        `\\0`, `\\\\` or `\\x1f`";
+
+/// Replace `\t`, `\r`, `\n`, `\0`, `\\` and `\xHH`, for an ASCII `HH`, in `s`.
+pub fn unescape(s: &str) -> anyhow::Result<String> {
+    let mut unescaped = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        unescaped.push(match chars.next() {
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('n') => '\n',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('x') => {
+                let hex = chars.by_ref().take(2).collect::<String>();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(it)
+                        if hex.len() == 2
+                            && hex.bytes().all(|it| it.is_ascii_hexdigit())
+                            && it.is_ascii() =>
+                    {
+                        char::from(it)
+                    }
+                    _ => bail!(
+                        "`\\x{}` in `{}` isn't an ASCII escape, {}",
+                        hex,
+                        s,
+                        ACCEPTED
+                    ),
+                }
+            }
+            _ => bail!("`{}` has an unknown escape, {}", s, ACCEPTED),
+        })
+    }
+    Ok(unescaped)
+}
+
+/// The reverse of [`unescape`], escaping control characters.
+pub fn escape(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\t' => String::from("\\t"),
+            '\r' => String::from("\\r"),
+            '\n' => String::from("\\n"),
+            '\0' => String::from("\\0"),
+            '\\' => String::from("\\\\"),
+            c if c.is_ascii_control() => format!("\\x{:02x}", u32::from(c)),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// A record which couldn't be read or converted.
+#[derive(Debug)]
+pub struct RowError {
+    /// The input the record is in.
+    pub source: String,
+    pub line: Option<u64>,
+    /// The line the record starts on, if it couldn't be split into the
+    /// header's cells, as with an unbalanced quote.
+    /// Otherwise, the cells of the record, joined by the delimiter.
+    pub raw: String,
+    pub error: anyhow::Error,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            source,
+            line,
+            raw,
+            error,
+        } = self;
+        match line {
+            Some(line) => write!(f, "{}, on line {}: {:#}", source, line, error)?,
+            None => write!(f, "{}: {:#}", source, error)?,
+        }
+        if !raw.is_empty() {
+            write!(f, "\n    {}", snippet(raw))?
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RowError {}
+
+/// The start of `raw`, if it is long.
+fn snippet(raw: &str) -> String {
+    const LENGTH: usize = 120;
+    match raw.char_indices().nth(LENGTH) {
+        Some((ix, _)) => format!("{}...", &raw[..ix]),
+        None => raw.to_owned(),
+    }
+}
+
+pub struct Input {
+    /// The path, or `-` for stdin.
+    pub name: String,
+    reader: csv::Reader<Box<dyn io::Read>>,
+    /// For each column of the first input, where it is in this one.
+    order: Vec<usize>,
+    /// For [`RowError::raw`].
+    delimiter: String,
+    /// For [`RowError::raw`], when the record couldn't be parsed.
+    tail: Tail,
+}
+
+impl Input {
+    /// Convert each record in turn.
+    ///
+    /// A record which can't be read or converted is a [`RowError`], after
+    /// which the rest are still converted.
+    pub fn convert(
+        self,
+        converter: &Converter,
+    ) -> impl Iterator<Item = anyhow::Result<Record<'_>>> + '_ {
+        let Self {
+            name,
+            reader,
+            order,
+            delimiter,
+            tail,
+        } = self;
+        reader.into_byte_records().map(move |record| {
+            let row_error = |line: Option<u64>, raw: String, error: anyhow::Error| RowError {
+                source: name.clone(),
+                line,
+                raw,
+                error,
+            };
+            let record = match record {
+                Ok(it) => it,
+                Err(e) if e.is_io_error() => {
+                    return Err(anyhow::Error::new(e)
+                        .context(format!("couldn't read a record from {}", name)))
+                }
+                Err(e) => {
+                    let (line, raw) = match e.position() {
+                        Some(it) => (Some(it.line()), tail.line(it.byte())),
+                        None => (None, String::new()),
+                    };
+                    return Err(row_error(line, raw, e.into()).into());
+                }
+            };
+            let line = record.position().map(|it| it.line());
+            let start = record.position().map(|it| it.byte());
+            let raw = |record: &csv::ByteRecord| {
+                record.iter().map(String::from_utf8_lossy).join(&delimiter)
+            };
+            if record.len() != order.len() {
+                return Err(row_error(
+                    line,
+                    start.map(|it| tail.line(it)).unwrap_or_default(),
+                    anyhow::anyhow!(
+                        "the record has {} cells, but the header has {}",
+                        record.len(),
+                        order.len()
+                    ),
+                )
+                .into());
+            }
+            if let Some(it) = start {
+                tail.forget(it)
+            }
+            let record = match StringRecord::from_byte_record(record) {
+                Ok(it) => it,
+                Err(e) => {
+                    let error = anyhow::anyhow!("{}", e.utf8_error());
+                    let record = e.into_byte_record();
+                    return Err(row_error(line, raw(&record), error).into());
+                }
+            };
+            let mut reordered = order
+                .iter()
+                .map(|ix| &record[*ix])
+                .collect::<StringRecord>();
+            reordered.set_position(record.position().cloned());
+            converter
+                .convert(&reordered, &name)
+                .map_err(|e| row_error(line, record.iter().join(&delimiter), e).into())
+        })
+    }
+}
+
+/// Open each of `paths`, where `-` is stdin, and read their headers.
+///
+/// Inputs with a [`Delimiter::Text`] are read whole.
+///
+/// Fails if the headers don't all have the columns of the first, listing the
+/// differences for each input.
+pub fn open(
+    paths: &[PathBuf],
+    dialect: &Dialect,
+    options: Options,
+) -> anyhow::Result<(Converter, Vec<Input>)> {
+    let mut inputs = vec![];
+    let mut headers = vec![];
+    for path in paths {
+        let name = path.display().to_string();
+        let read: Box<dyn io::Read> = match path == Path::new("-") {
+            true => Box::new(io::stdin().lock()),
+            false => {
+                Box::new(File::open(path).with_context(|| format!("couldn't open file {}", name))?)
+            }
+        };
+        let mut builder = csv::ReaderBuilder::new();
+        // Records with the wrong number of cells are reported as a `RowError`.
+        builder.flexible(true).trim(match dialect.trim {
+            true => csv::Trim::All,
+            false => csv::Trim::None,
+        });
+        let read = match &dialect.delimiter {
+            Delimiter::Byte(it) => {
+                builder
+                    .delimiter(*it)
+                    .quote(dialect.quote)
+                    .quoting(dialect.quoting);
+                read
+            }
+            Delimiter::Text(it) => Box::new(io::Cursor::new(
+                presplit(read, it).with_context(|| format!("couldn't read from {}", name))?,
+            )),
+        };
+        let tail = Tail::default();
+        let mut reader = builder.from_reader(Box::new(Recording {
+            read,
+            tail: tail.clone(),
+        }) as Box<dyn io::Read>);
+        let header = reader
+            .headers()
+            .with_context(|| format!("couldn't read the header of {}", name))?
+            .clone();
+        if let Ok(duplicated) = nunny::Vec::new(header.iter().duplicates().collect()) {
+            bail!(
+                "the following columns of {} are duplicated: {}",
+                name,
+                duplicated.join(", ")
+            )
+        }
+        headers.push(header);
+        inputs.push((name, reader, tail));
+    }
+    let Some(first) = headers.first() else {
+        bail!("no inputs")
+    };
+    let columns = first.iter().collect::<BTreeSet<_>>();
+    let mut mismatches = vec![];
+    for ((name, _, _), header) in inputs.iter().zip(&headers).skip(1) {
+        let these = header.iter().collect::<BTreeSet<_>>();
+        if these != columns {
+            mismatches.push(format!(
+                "{} is missing [{}] and has extra [{}]",
+                name,
+                columns.difference(&these).join(", "),
+                these.difference(&columns).join(", ")
+            ))
+        }
+    }
+    if !mismatches.is_empty() {
+        bail!(
+            "the inputs don't have the same columns as {}:\n{}",
+            inputs[0].0,
+            mismatches.join("\n")
+        )
+    }
+    let converter = Converter::new(first, options)?;
+    let inputs = inputs
+        .into_iter()
+        .zip(&headers)
+        .map(|((name, reader, tail), header)| Input {
+            name,
+            reader,
+            tail,
+            delimiter: dialect.delimiter.to_text(),
+            order: first
+                .iter()
+                .map(|column| {
+                    header
+                        .iter()
+                        .position(|it| it == column)
+                        .expect("headers have the same columns")
+                })
+                .collect(),
+        })
+        .collect();
+    Ok((converter, inputs))
+}
+
+/// What has been read from an input since the start of the last record, so
+/// that a record which can't be parsed can be shown as written.
+#[derive(Clone, Default)]
+struct Tail(Rc<RefCell<(u64, Vec<u8>)>>);
+
+impl Tail {
+    /// Forget what was read before the byte offset `to`.
+    fn forget(&self, to: u64) {
+        let (start, bytes) = &mut *self.0.borrow_mut();
+        let len = usize::try_from(to.saturating_sub(*start))
+            .map_or(bytes.len(), |it| it.min(bytes.len()));
+        bytes.drain(..len);
+        *start += len as u64
+    }
+
+    /// The line starting at the byte offset `at`, if it hasn't been
+    /// forgotten.
+    fn line(&self, at: u64) -> String {
+        let (start, bytes) = &*self.0.borrow();
+        let Some(rest) = at
+            .checked_sub(*start)
+            .and_then(|it| usize::try_from(it).ok())
+            .and_then(|it| bytes.get(it..))
+        else {
+            return String::new();
+        };
+        let line = rest.split(|it| *it == b'\n').next().unwrap_or_default();
+        String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)).into_owned()
+    }
+}
+
+/// Keeps what is read in a [`Tail`].
+struct Recording {
+    read: Box<dyn io::Read>,
+    tail: Tail,
+}
+
+impl io::Read for Recording {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.read.read(buf)?;
+        self.tail.0.borrow_mut().1.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Type {
+    String,
+    Boolean,
+    Number,
+}
+
+/// Parse `column=type`.
+pub fn parse_type(s: &str) -> anyhow::Result<(String, Type)> {
+    match s.split_once('=') {
+        Some((column, ty)) if !column.is_empty() => Ok((
+            column.to_owned(),
+            Type::from_str(ty, true).map_err(|e| anyhow::anyhow!(e))?,
+        )),
+        _ => bail!("expected a column type in the form `column=type`"),
+    }
+}
+
+/// Rewrite `read`, split on `delimiter`, as comma-separated lines.
+fn presplit(mut read: Box<dyn io::Read>, delimiter: &str) -> anyhow::Result<Vec<u8>> {
+    let mut text = String::new();
+    read.read_to_string(&mut text)?;
+    let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(vec![]);
+    for line in text.lines() {
+        writer.write_record(line.split(delimiter))?
+    }
+    Ok(writer.into_inner()?)
+}
+
+enum Conversion {
+    Pinned(Type),
+    Infer,
+}
+
+struct Column {
+    /// The keys leading to the cell, which is only one key unless `nested`.
+    path: Vec<String>,
+    conversion: Conversion,
+}
+
+pub struct Converter {
+    columns: Vec<Column>,
+    source_column: Option<String>,
+    validator: Option<Validator>,
+}
+
+impl Converter {
+    /// Fails if a pinned column isn't in `headers`, if the source column is,
+    /// or if nesting would put a cell where an object must go, as with
+    /// headers `A` and `A.B`.
+    pub fn new(headers: &StringRecord, options: Options) -> anyhow::Result<Self> {
+        for (column, _) in &options.types {
+            if !headers.iter().any(|it| it == column) {
+                bail!("there is no column {} to give a type to", column)
+            }
+        }
+        if let Some(column) = &options.source_column {
+            if headers.iter().any(|it| it == column) {
+                bail!("the source column {} is already a column", column)
+            }
+        }
+        let columns = headers
+            .iter()
+            .map(|header| Column {
+                path: match options.nested {
+                    true => header.split('.').map(str::to_owned).collect(),
+                    false => vec![header.to_owned()],
+                },
+                conversion: match options.types.iter().rfind(|(it, _)| it == header) {
+                    Some((_, ty)) => Conversion::Pinned(*ty),
+                    None if options.infer_types => Conversion::Infer,
+                    None => Conversion::Pinned(Type::String),
+                },
+            })
+            .collect::<Vec<_>>();
+        for (ix, column) in columns.iter().enumerate() {
+            for other in &columns[ix + 1..] {
+                if column.path.starts_with(&other.path) || other.path.starts_with(&column.path) {
+                    bail!(
+                        "columns {} and {} would be written to the same key",
+                        column.path.join("."),
+                        other.path.join(".")
+                    )
+                }
+            }
+        }
+        Ok(Self {
+            columns,
+            source_column: options.source_column,
+            validator: options.validator,
+        })
+    }
+
+    /// Columns which the validator doesn't expect, and so would be ignored.
+    pub fn unknown_columns(&self) -> Vec<String> {
+        match &self.validator {
+            Some(Validator::Select { .. }) => self
+                .columns
+                .iter()
+                .map(|it| it.path.join("."))
+                .filter(|it| !SELECT_COLUMNS.contains(&it.as_str()))
+                .collect(),
+            Some(Validator::JsonSchema(_)) | None => vec![],
+        }
+    }
+
+    /// `source` is the name of the input `record` came from.
+    pub fn convert(&self, record: &StringRecord, source: &str) -> anyhow::Result<Record<'_>> {
+        let mut object = BTreeMap::new();
+        if let Some(column) = &self.source_column {
+            object.insert(
+                column.as_str(),
+                Cell::Value(Value::String(source.to_owned())),
+            );
+        }
+        for (column, cell) in self.columns.iter().zip(record) {
+            if cell.is_empty() {
+                continue;
+            }
+            let value = match column.conversion {
+                Conversion::Infer => infer(cell),
+                Conversion::Pinned(ty) => pinned(cell, ty)
+                    .with_context(|| format!("in column {}", column.path.join(".")))?,
+            };
+            let (last, parents) = column.path.split_last().expect("paths aren't empty");
+            let mut parent = &mut object;
+            for key in parents {
+                parent = match parent
+                    .entry(key.as_str())
+                    .or_insert_with(|| Cell::Object(BTreeMap::new()))
+                {
+                    Cell::Object(it) => it,
+                    Cell::Value(_) => unreachable!("columns are checked not to overlap"),
+                }
+            }
+            parent.insert(last.as_str(), Cell::Value(value));
+        }
+        let record = Record(object);
+        if let Some(validator) = &self.validator {
+            let problems = validator.check(&serde_json::to_value(&record)?);
+            if !problems.is_empty() {
+                bail!("the record is invalid:\n{}", problems.join("\n"))
+            }
+        }
+        Ok(record)
+    }
+}
+
+/// A converted record, which serializes as a JSON object.
+///
+/// Keys borrow from the [`Converter`]'s columns, so none are allocated per
+/// record.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Record<'c>(BTreeMap<&'c str, Cell<'c>>);
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Cell<'c> {
+    Value(Value),
+    Object(BTreeMap<&'c str, Cell<'c>>),
+}
+
+fn pinned(cell: &str, ty: Type) -> anyhow::Result<Value> {
+    match ty {
+        Type::String => Ok(Value::String(cell.to_owned())),
+        Type::Boolean => match boolean(cell) {
+            Some(it) => Ok(Value::Bool(it)),
+            None => bail!("`{}` isn't a boolean", cell),
+        },
+        Type::Number => match number(cell) {
+            Some(it) => Ok(Value::Number(it)),
+            None => bail!("`{}` isn't a number", cell),
+        },
+    }
+}
+
+/// A boolean or a number if `cell` looks like one, else a string.
+fn infer(cell: &str) -> Value {
+    match (boolean(cell), number(cell)) {
+        (Some(it), _) => Value::Bool(it),
+        (None, Some(it)) => Value::Number(it),
+        (None, None) => Value::String(cell.to_owned()),
+    }
+}
+
+fn boolean(cell: &str) -> Option<bool> {
+    match cell {
+        "true" | "TRUE" => Some(true),
+        "false" | "FALSE" => Some(false),
+        _ => None,
+    }
+}
+
+/// Only cells written as JSON would write the number, so that leading zeros
+/// (like `007`), `+1`, `.5`, `NaN` and the like are kept as written.
+/// Integers too large for 64 bits, and `-0`, which would lose its sign, are
+/// also kept as written.
+fn number(cell: &str) -> Option<Number> {
+    let digits = |it: &str| !it.is_empty() && it.bytes().all(|it| it.is_ascii_digit());
+    let unsigned = cell.strip_prefix('-').unwrap_or(cell);
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (unsigned, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+    if !digits(integer)
+        || (integer.len() > 1 && integer.starts_with('0'))
+        || !fraction.is_none_or(digits)
+        || !exponent.is_none_or(|it| digits(it.strip_prefix(['+', '-']).unwrap_or(it)))
+    {
+        return None;
+    }
+    match (fraction, exponent) {
+        (None, None) if cell == "-0" => None,
+        (None, None) => cell
+            .parse::<i64>()
+            .map(Number::from)
+            .or_else(|_| cell.parse::<u64>().map(Number::from))
+            .ok(),
+        _ => cell.parse::<f64>().ok().and_then(Number::from_f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        for (cell, expected) in [
+            ("0", json!(0)),
+            ("42", json!(42)),
+            ("-42", json!(-42)),
+            ("0.5", json!(0.5)),
+            ("-0.5", json!(-0.5)),
+            ("1e5", json!(100000.0)),
+            ("1E+5", json!(100000.0)),
+            ("2.5e-3", json!(0.0025)),
+            ("-9223372036854775808", json!(i64::MIN)),
+            ("18446744073709551615", json!(u64::MAX)),
+        ] {
+            assert_eq!(infer(cell), expected, "{}", cell);
+        }
+    }
+
+    #[test]
+    fn not_numbers() {
+        for cell in [
+            "007",
+            "00",
+            "-0",
+            "+1",
+            ".5",
+            "5.",
+            "1e",
+            "e5",
+            "-",
+            "1_000",
+            "1,000",
+            "0x10",
+            "NaN",
+            "inf",
+            "18446744073709551616",
+        ] {
+            assert_eq!(infer(cell), json!(cell), "{}", cell);
+        }
+        assert!(pinned("007", Type::Number).is_err());
+    }
+
+    #[test]
+    fn unbalanced_quote() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"a,b\n1,2\n\"3,4\n5,6\n").unwrap();
+        let (converter, inputs) = open(
+            &[file.path().to_owned()],
+            &Dialect {
+                delimiter: Delimiter::Byte(b','),
+                quote: b'"',
+                quoting: true,
+                trim: false,
+            },
+            Options {
+                infer_types: true,
+                types: vec![],
+                nested: false,
+                source_column: None,
+                validator: None,
+            },
+        )
+        .unwrap();
+        let [input] = <[Input; 1]>::try_from(inputs).ok().unwrap();
+        let mut records = input.convert(&converter);
+        let record = records.next().unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(record).unwrap(),
+            json!({ "a": 1, "b": 2 })
+        );
+        let error = records.next().unwrap().unwrap_err();
+        let error = error.downcast_ref::<RowError>().unwrap();
+        assert_eq!(error.line, Some(3));
+        assert_eq!(error.raw, "\"3,4");
+        // the quote runs to the end of the input
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn booleans() {
+        assert_eq!(infer("true"), json!(true));
+        assert_eq!(infer("FALSE"), json!(false));
+        assert_eq!(infer("True"), json!("True"));
+        assert_eq!(pinned("true", Type::String).unwrap(), json!("true"));
+    }
+}
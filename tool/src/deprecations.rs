@@ -0,0 +1,176 @@
+//! The deprecated methods and params in a document, and when they were
+//! deprecated.
+
+use openrpc_types::resolved;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::docs::cell;
+
+/// On a method, the name of the method to use instead.
+/// On a param, the name of the param to use instead.
+pub const REPLACED_BY: &str = "x-replaced-by";
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub deprecated: Vec<Deprecation>,
+    /// Methods in the newest historical document which aren't in the current
+    /// one, and weren't deprecated in any historical document.
+    pub removed_without_deprecation: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Deprecation {
+    pub method: String,
+    /// If only a param is deprecated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub param: Option<String>,
+    pub description: Option<String>,
+    pub replaced_by: Option<String>,
+    /// The version of the first document, in history order, with this item
+    /// deprecated, if there's any history.
+    pub since: Option<String>,
+    /// A replacement which is itself deprecated or missing.
+    pub problems: Vec<String>,
+}
+
+/// `history` is ordered from oldest to newest, and shouldn't include `document`.
+pub fn report(document: &resolved::OpenRPC, history: &[resolved::OpenRPC]) -> Report {
+    let since = |method: &str, param: Option<&str>| {
+        if history.is_empty() {
+            return None;
+        }
+        history
+            .iter()
+            .chain([document])
+            .find(|it| is_deprecated(it, method, param))
+            .map(|it| it.info.version.clone())
+    };
+    let mut deprecated = vec![];
+    for method in &document.methods {
+        if method.deprecated == Some(true) {
+            let replaced_by = replaced_by(&method.extensions);
+            let problems = problems(
+                replaced_by.as_deref(),
+                document.methods.iter().map(|it| (&*it.name, it.deprecated)),
+            );
+            deprecated.push(Deprecation {
+                method: method.name.clone(),
+                param: None,
+                description: method.description.clone().or(method.summary.clone()),
+                since: since(&method.name, None),
+                replaced_by,
+                problems,
+            })
+        }
+        for param in &method.params {
+            if param.deprecated == Some(true) {
+                let replaced_by = replaced_by(&param.extensions);
+                let problems = problems(
+                    replaced_by.as_deref(),
+                    method.params.iter().map(|it| (&*it.name, it.deprecated)),
+                );
+                deprecated.push(Deprecation {
+                    method: method.name.clone(),
+                    param: Some(param.name.clone()),
+                    description: param.description.clone().or(param.summary.clone()),
+                    since: since(&method.name, Some(&param.name)),
+                    replaced_by,
+                    problems,
+                })
+            }
+        }
+    }
+    let removed_without_deprecation = match history.last() {
+        Some(newest) => newest
+            .methods
+            .iter()
+            .filter(|method| {
+                !document.methods.iter().any(|it| it.name == method.name)
+                    && !history
+                        .iter()
+                        .any(|it| is_deprecated(it, &method.name, None))
+            })
+            .map(|it| it.name.clone())
+            .collect(),
+        None => vec![],
+    };
+    Report {
+        deprecated,
+        removed_without_deprecation,
+    }
+}
+
+pub fn markdown(report: &Report) -> String {
+    let Report {
+        deprecated,
+        removed_without_deprecation,
+    } = report;
+    let mut out = String::from(
+        "| Method | Param | Description | Replaced by | Since | Problems |\n\
+         | --- | --- | --- | --- | --- | --- |\n",
+    );
+    for Deprecation {
+        method,
+        param,
+        description,
+        replaced_by,
+        since,
+        problems,
+    } in deprecated
+    {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            cell(method),
+            cell(param.as_deref().unwrap_or_default()),
+            cell(description.as_deref().unwrap_or_default()),
+            cell(replaced_by.as_deref().unwrap_or_default()),
+            cell(since.as_deref().unwrap_or_default()),
+            cell(&problems.join("\n")),
+        ))
+    }
+    if !removed_without_deprecation.is_empty() {
+        out.push_str("\n## Removed without deprecation\n\n");
+        for it in removed_without_deprecation {
+            out.push_str(&format!("- {}\n", it))
+        }
+    }
+    out
+}
+
+fn is_deprecated(document: &resolved::OpenRPC, method: &str, param: Option<&str>) -> bool {
+    let Some(method) = document.methods.iter().find(|it| it.name == method) else {
+        return false;
+    };
+    match param {
+        None => method.deprecated == Some(true),
+        Some(param) => method
+            .params
+            .iter()
+            .any(|it| it.name == param && it.deprecated == Some(true)),
+    }
+}
+
+/// Whether `replacement` is missing from or deprecated in `candidates`, as
+/// `(name, deprecated)`.
+fn problems<'a>(
+    replacement: Option<&str>,
+    mut candidates: impl Iterator<Item = (&'a str, Option<bool>)>,
+) -> Vec<String> {
+    let Some(replacement) = replacement else {
+        return vec![];
+    };
+    match candidates.find(|(name, _)| *name == replacement) {
+        None => vec![format!("replacement {} doesn't exist", replacement)],
+        Some((_, Some(true))) => vec![format!("replacement {} is deprecated", replacement)],
+        Some(_) => vec![],
+    }
+}
+
+fn replaced_by(extensions: &openrpc_types::SpecificationExtensions) -> Option<String> {
+    extensions
+        .0
+        .get(REPLACED_BY)
+        .and_then(Value::as_str)
+        .map(str::to_owned)
+}
@@ -0,0 +1,105 @@
+//! Point at where a problem is in a loaded document:
+//! ```text
+//!   --> file spec.json:12:18
+//!    |
+//! 10 |       "params": [
+//! 11 |         {
+//! 12 |           "name": 1,
+//!    |                  ^
+//! ```
+//! Colored when stderr is a terminal.
+
+use std::io::{self, IsTerminal as _};
+
+use openrpc_types::BrokenReference;
+
+use crate::chains::ResolveError;
+
+/// Lines shown before the one with the problem.
+const CONTEXT: usize = 2;
+
+/// If `error` is because a `$ref` doesn't exist, add an [`excerpt`] at the
+/// first `$ref` to it in `text`, the document named `name` which it was
+/// found in.
+pub fn locate_reference(error: anyhow::Error, name: &str, text: &str) -> anyhow::Error {
+    let message = error
+        .chain()
+        .find_map(|it| match it.downcast_ref::<ResolveError>() {
+            Some(ResolveError::BrokenReference { reference, .. }) => Some(reference.as_str()),
+            _ => Some(it.downcast_ref::<BrokenReference>()?.0.as_str()),
+        })
+        .and_then(|it| {
+            let excerpt = excerpt_at_reference(name, text, it)?;
+            Some(format!("`$ref` {} doesn't exist\n{}", it, excerpt))
+        });
+    match message {
+        Some(it) => error.context(it),
+        None => error,
+    }
+}
+
+fn excerpt_at_reference(name: &str, text: &str, reference: &str) -> Option<String> {
+    let needle = serde_json::to_string(reference).ok()?;
+    let offset = text.match_indices(&needle).find_map(|(ix, _)| {
+        let before = text[..ix].trim_end().strip_suffix(':')?;
+        before.trim_end().ends_with("\"$ref\"").then_some(ix)
+    })?;
+    let line = text[..offset].matches('\n').count() + 1;
+    let column = offset - text[..offset].rfind('\n').map_or(0, |it| it + 1) + 1;
+    Some(excerpt(name, text, line, column))
+}
+
+/// The lines of `text` up to `line`, with a caret under `column`, both
+/// `1`-based.
+pub fn excerpt(name: &str, text: &str, line: usize, column: usize) -> String {
+    let (blue, red, reset) = match io::stderr().is_terminal() {
+        true => ("\x1b[1;34m", "\x1b[1;31m", "\x1b[0m"),
+        false => ("", "", ""),
+    };
+    let width = line.to_string().len();
+    let gutter = |number: &str| format!("{blue}{number:>width$} |{reset}");
+    let mut out = format!(
+        "{blue}{:width$}-->{reset} {}:{}:{}\n{}\n",
+        "",
+        name,
+        line,
+        column,
+        gutter("")
+    );
+    let first = line.saturating_sub(CONTEXT).max(1);
+    for (number, it) in text
+        .lines()
+        .enumerate()
+        .map(|(ix, it)| (ix + 1, it))
+        .skip(first - 1)
+        .take(line + 1 - first)
+    {
+        out.push_str(&format!("{} {}\n", gutter(&number.to_string()), it));
+    }
+    out.push_str(&format!(
+        "{} {:>column$}{red}^{reset}",
+        gutter(""),
+        "",
+        column = column.saturating_sub(1)
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_in_its_own_document() {
+        let reference = "#/components/schemas/Missing";
+        let error = || anyhow::Error::new(BrokenReference(String::from(reference)));
+        let text = "{\n  \"schema\": {\n    \"$ref\": \"#/components/schemas/Missing\"\n  }\n}";
+        let located = locate_reference(error(), "file a.json", text);
+        let message = format!("{:?}", located);
+        assert!(message.contains("file a.json:3:13"), "{}", message);
+
+        // nothing to point at in a document without the `$ref`
+        let located = locate_reference(error(), "file b.json", "{}");
+        assert_eq!(located.chain().count(), 1);
+    }
+}
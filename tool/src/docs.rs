@@ -0,0 +1,330 @@
+//! Render an OpenRPC document as a directory of Markdown files.
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use itertools::Itertools as _;
+use openrpc_types::{resolved, BrokenReference, ContentDescriptor, Example, ParamStructure};
+use schemars::schema::{Schema, SchemaObject};
+use serde_json::{json, Value};
+
+use crate::{reference_index::ReferenceIndex, schema_summary};
+
+/// The file that all component schemas are rendered into.
+pub const TYPES: &str = "types.md";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Split {
+    /// One file per tag, methods without tags are grouped under `untagged`.
+    #[default]
+    PerTag,
+    /// One file per method.
+    PerMethod,
+}
+
+/// Returns a map of file names to their contents.
+///
+/// Fails if any `$ref` in a rendered schema doesn't point into
+/// `#/components/schemas`.
+pub fn render(
+    document: &resolved::OpenRPC,
+    split: Split,
+) -> Result<BTreeMap<String, String>, BrokenReference> {
+    let empty = BTreeMap::new();
+    let schemas = document
+        .components
+        .as_ref()
+        .and_then(|it| it.schemas.as_ref())
+        .unwrap_or(&empty);
+    let index = ReferenceIndex::new(document);
+    let cx = Context {
+        schemas,
+        index: &index,
+        link_prefix: Some(TYPES),
+    };
+
+    let mut groups = BTreeMap::<String, Vec<&resolved::Method>>::new();
+    for method in &document.methods {
+        match split {
+            Split::PerMethod => groups.entry(method.name.clone()).or_default().push(method),
+            Split::PerTag => match method.tags.as_deref() {
+                Some(tags) if !tags.is_empty() => {
+                    for tag in tags {
+                        groups.entry(tag.name.clone()).or_default().push(method)
+                    }
+                }
+                _ => groups
+                    .entry(String::from("untagged"))
+                    .or_default()
+                    .push(method),
+            },
+        }
+    }
+
+    let mut files = BTreeMap::new();
+    for (group, methods) in groups {
+        let mut out = String::new();
+        writeln!(out, "# {}", group).unwrap();
+        for method in methods.into_iter().sorted_by(|l, r| l.name.cmp(&r.name)) {
+            writeln!(out).unwrap();
+            self::method(&mut out, &cx, method)?;
+        }
+        files.insert(format!("{}.md", file_stem(&group)), out);
+    }
+    files.insert(
+        String::from(TYPES),
+        types(&Context {
+            schemas,
+            index: &index,
+            link_prefix: Some(""),
+        })?,
+    );
+    Ok(files)
+}
+
+struct Context<'a> {
+    schemas: &'a BTreeMap<String, Schema>,
+    index: &'a ReferenceIndex<'a>,
+    /// Prepended to `#anchor` links to component schemas, or [`None`] to
+    /// render them as plain text.
+    link_prefix: Option<&'a str>,
+}
+
+fn method(
+    out: &mut String,
+    cx: &Context,
+    resolved::Method {
+        name,
+        tags: _,
+        summary,
+        description,
+        external_docs,
+        params,
+        result,
+        deprecated,
+        servers: _,
+        errors,
+        param_structure,
+        examples,
+        extensions: _,
+    }: &resolved::Method,
+) -> Result<(), BrokenReference> {
+    writeln!(out, "## `{}`", name).unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "```\n{}({}){}\n```",
+        name,
+        params.iter().map(|it| it.name.as_str()).join(", "),
+        match result {
+            Some(it) => format!(" -> {}", it.name),
+            None => String::new(),
+        }
+    )
+    .unwrap();
+    if deprecated.unwrap_or_default() {
+        writeln!(out, "\n> **Deprecated**").unwrap();
+    }
+    for text in summary.iter().chain(description) {
+        writeln!(out, "\n{}", text.trim_end()).unwrap();
+    }
+    if let Some(it) = external_docs {
+        writeln!(out, "\nSee also: <{}>", it.url).unwrap();
+    }
+
+    writeln!(out, "\n### Parameters\n").unwrap();
+    writeln!(
+        out,
+        "Structure: `{}`\n",
+        match param_structure.unwrap_or_default() {
+            ParamStructure::ByName => "by-name",
+            ParamStructure::ByPosition => "by-position",
+            ParamStructure::Either => "either",
+        }
+    )
+    .unwrap();
+    match params.is_empty() {
+        true => writeln!(out, "_None_").unwrap(),
+        false => {
+            writeln!(out, "| Name | Required | Type | Description |").unwrap();
+            writeln!(out, "| ---- | -------- | ---- | ----------- |").unwrap();
+            for param in params {
+                writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} |",
+                    param.name,
+                    if param.required.unwrap_or_default() {
+                        "yes"
+                    } else {
+                        "no"
+                    },
+                    cell(&summarize(cx, &param.schema)?),
+                    cell(&descriptor_text(param)),
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    writeln!(out, "\n### Result\n").unwrap();
+    match result {
+        Some(result) => {
+            writeln!(out, "{}", summarize(cx, &result.schema)?).unwrap();
+            let text = descriptor_text(result);
+            if !text.is_empty() {
+                writeln!(out, "\n{}", text).unwrap();
+            }
+        }
+        None => writeln!(out, "_None, this method is a notification_").unwrap(),
+    }
+
+    if let Some(errors) = errors.as_ref().filter(|it| !it.is_empty()) {
+        writeln!(out, "\n### Errors\n").unwrap();
+        writeln!(out, "| Code | Message |").unwrap();
+        writeln!(out, "| ---- | ------- |").unwrap();
+        for error in errors {
+            writeln!(out, "| {} | {} |", error.code, cell(&error.message)).unwrap();
+        }
+    }
+
+    if let Some(examples) = examples.as_ref().filter(|it| !it.is_empty()) {
+        writeln!(out, "\n### Examples").unwrap();
+        for (ix, example) in examples.iter().enumerate() {
+            writeln!(out, "\n#### {}\n", example.name).unwrap();
+            for text in example.summary.iter().chain(&example.description) {
+                writeln!(out, "{}\n", text.trim_end()).unwrap();
+            }
+            let values = params
+                .iter()
+                .zip(&example.params)
+                .map(|(param, example)| (param.name.clone(), example_value(example)));
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": ix,
+                "method": name,
+                "params": match param_structure {
+                    Some(ParamStructure::ByName) => Value::Object(values.collect()),
+                    _ => Value::Array(values.map(|(_, v)| v).collect()),
+                }
+            });
+            writeln!(out, "Request:\n\n```json\n{:#}\n```", request).unwrap();
+            if let Some(result) = &example.result {
+                let response = json!({
+                    "jsonrpc": "2.0",
+                    "id": ix,
+                    "result": example_value(result),
+                });
+                writeln!(out, "\nResponse:\n\n```json\n{:#}\n```", response).unwrap();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn types(cx: &Context) -> Result<String, BrokenReference> {
+    let mut out = String::from("# Types\n");
+    for (key, schema) in cx.schemas {
+        writeln!(out, "\n## {}\n", key).unwrap();
+        let Schema::Object(object) = schema else {
+            writeln!(out, "{}", summarize(cx, schema)?).unwrap();
+            continue;
+        };
+        if let Some(it) = object
+            .metadata
+            .as_ref()
+            .and_then(|it| it.description.as_ref())
+        {
+            writeln!(out, "{}\n", it.trim_end()).unwrap();
+        }
+        writeln!(out, "{}", summarize(cx, schema)?).unwrap();
+        if let Some(it) = object
+            .object
+            .as_ref()
+            .filter(|it| !it.properties.is_empty())
+        {
+            writeln!(out, "\n| Property | Required | Type | Description |").unwrap();
+            writeln!(out, "| -------- | -------- | ---- | ----------- |").unwrap();
+            for (name, property) in &it.properties {
+                writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} |",
+                    name,
+                    if it.required.contains(name) {
+                        "yes"
+                    } else {
+                        "no"
+                    },
+                    cell(&summarize(cx, property)?),
+                    cell(&match property {
+                        Schema::Object(SchemaObject {
+                            metadata: Some(it), ..
+                        }) => it.description.clone().unwrap_or_default(),
+                        _ => String::new(),
+                    }),
+                )
+                .unwrap();
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// A short, single-line rendering of the type of a schema.
+///
+/// Fails if `schema` uses a `$ref` which isn't a component schema.
+fn summarize(cx: &Context, schema: &Schema) -> Result<String, BrokenReference> {
+    cx.index.reachable_from_schema(schema)?;
+    Ok(match cx.link_prefix {
+        Some(prefix) => schema_summary::summarize_with(
+            schema,
+            cx.schemas,
+            schema_summary::DEFAULT_DEPTH,
+            &|key| format!("[`{}`]({}#{})", key, prefix, anchor(key)),
+        ),
+        None => schema_summary::summarize(schema, cx.schemas),
+    })
+}
+
+fn descriptor_text(it: &ContentDescriptor) -> String {
+    it.summary
+        .iter()
+        .chain(&it.description)
+        .map(|it| it.trim())
+        .join(" ")
+}
+
+fn example_value(it: &Example) -> Value {
+    match (&it.value, &it.external_value) {
+        (Some(it), _) => it.clone(),
+        (None, Some(it)) => json!({ "externalValue": it }),
+        (None, None) => Value::Null,
+    }
+}
+
+/// Make text safe for a single Markdown table cell.
+pub fn cell(text: &str) -> String {
+    text.trim()
+        .lines()
+        .map(|it| it.replace('|', "\\|"))
+        .join("<br>")
+}
+
+/// The anchor GitHub generates for a heading.
+fn anchor(heading: &str) -> String {
+    heading
+        .chars()
+        .filter_map(|it| match it {
+            ' ' => Some('-'),
+            it if it.is_alphanumeric() || it == '-' || it == '_' => Some(it.to_ascii_lowercase()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|it| match it {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            it => it,
+        })
+        .collect()
+}
@@ -0,0 +1,182 @@
+//! Standalone JSON Schemas for the JSON-RPC request and response of each
+//! method.
+//!
+//! Schemas are draft 2020-12, with the component schemas each method needs
+//! under `$defs`.
+//! Params are a `prefixItems` tuple for `by-position` methods, an object for
+//! `by-name` methods, and either otherwise.
+
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+use jsonschema::{Draft, JSONSchema};
+use openrpc_types::{resolved, ParamStructure};
+use schemars::schema::{Schema, SchemaObject};
+use serde_json::{json, Value};
+
+use crate::{
+    component_ref::{self, ComponentRef, ComponentSection},
+    progress,
+    reference_index::ReferenceIndex,
+    schema_visit,
+};
+
+const DEFS: &str = "#/$defs/";
+
+pub struct Envelopes {
+    pub request: Value,
+    pub response: Value,
+}
+
+/// Each schema is compiled, and it is an error if any fail.
+pub fn envelopes(document: &resolved::OpenRPC) -> anyhow::Result<BTreeMap<String, Envelopes>> {
+    let schemas = document
+        .components
+        .as_ref()
+        .and_then(|it| it.schemas.as_ref());
+    let index = ReferenceIndex::new(document);
+    let mut all = BTreeMap::new();
+    let bar = progress::Bar::new(document.methods.len(), "compiled", "methods");
+    for method in &document.methods {
+        bar.show(&method.name);
+        let reachable = index.reachable(&method.name)?;
+        let defs = schemas
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| reachable.contains(key.as_str()))
+            .map(|(key, schema)| (key.clone(), rewrite(schema)))
+            .collect::<BTreeMap<_, _>>();
+        let envelopes = Envelopes {
+            request: request(method, &defs),
+            response: response(method, &defs),
+        };
+        for (what, schema) in [
+            ("request", &envelopes.request),
+            ("response", &envelopes.response),
+        ] {
+            JSONSchema::options()
+                .with_draft(Draft::Draft202012)
+                .compile(schema)
+                .map_err(|it| anyhow::anyhow!("{}", it))
+                .with_context(|| {
+                    format!("the {} schema for {} doesn't compile", what, method.name)
+                })?;
+        }
+        all.insert(method.name.clone(), envelopes);
+        bar.inc()
+    }
+    bar.finish();
+    Ok(all)
+}
+
+fn request(method: &resolved::Method, defs: &BTreeMap<String, Schema>) -> Value {
+    let required = method
+        .params
+        .iter()
+        .filter(|it| it.required.unwrap_or_default())
+        .collect::<Vec<_>>();
+    let by_position = json!({
+        "type": "array",
+        "prefixItems": method.params.iter().map(|it| rewrite(&it.schema)).collect::<Vec<_>>(),
+        "items": false,
+        "minItems": method
+            .params
+            .iter()
+            .rposition(|it| it.required.unwrap_or_default())
+            .map_or(0, |it| it + 1),
+    });
+    let by_name = json!({
+        "type": "object",
+        "properties": method
+            .params
+            .iter()
+            .map(|it| (it.name.clone(), rewrite(&it.schema)))
+            .collect::<BTreeMap<_, _>>(),
+        "required": required.iter().map(|it| &it.name).collect::<Vec<_>>(),
+        "additionalProperties": false,
+    });
+    let params = match method.param_structure {
+        Some(ParamStructure::ByPosition) => by_position,
+        Some(ParamStructure::ByName) => by_name,
+        Some(ParamStructure::Either) | None => json!({ "anyOf": [by_position, by_name] }),
+    };
+    let mut envelope_required = vec!["jsonrpc", "id", "method"];
+    if !required.is_empty() {
+        envelope_required.push("params")
+    }
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": format!("{} request", method.name),
+        "type": "object",
+        "properties": {
+            "jsonrpc": { "const": "2.0" },
+            "id": { "type": ["string", "number", "null"] },
+            "method": { "const": method.name },
+            "params": params,
+        },
+        "required": envelope_required,
+        "$defs": defs,
+    })
+}
+
+fn response(method: &resolved::Method, defs: &BTreeMap<String, Schema>) -> Value {
+    let result = match &method.result {
+        Some(it) => serde_json::to_value(rewrite(&it.schema)).unwrap(),
+        None => json!({ "type": "null" }),
+    };
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": format!("{} response", method.name),
+        "type": "object",
+        "properties": {
+            "jsonrpc": { "const": "2.0" },
+            "id": { "type": ["string", "number", "null"] },
+        },
+        "required": ["jsonrpc", "id"],
+        "oneOf": [
+            {
+                "properties": { "result": result },
+                "required": ["result"],
+                "not": { "required": ["error"] },
+            },
+            {
+                "properties": {
+                    "error": {
+                        "type": "object",
+                        "properties": {
+                            "code": { "type": "integer" },
+                            "message": { "type": "string" },
+                            "data": true,
+                        },
+                        "required": ["code", "message"],
+                    },
+                },
+                "required": ["error"],
+                "not": { "required": ["result"] },
+            },
+        ],
+        "$defs": defs,
+    })
+}
+
+/// Point `$ref`s into `#/components/schemas` at `#/$defs` instead.
+fn rewrite(schema: &Schema) -> Schema {
+    fn imp(schema: &mut Schema) {
+        if let Schema::Object(SchemaObject {
+            reference: Some(reference),
+            ..
+        }) = schema
+        {
+            let parsed = ComponentRef::parse(reference);
+            if let Some(key) = parsed.key_in(ComponentSection::Schemas) {
+                *reference = format!("{}{}", DEFS, component_ref::escape(key))
+            }
+        }
+        for child in schema_visit::children_mut(schema) {
+            imp(child)
+        }
+    }
+    let mut schema = schema.clone();
+    imp(&mut schema);
+    schema
+}
@@ -0,0 +1,164 @@
+//! Exit codes, and how errors are reported.
+//!
+//! | Code | Kind       | When                                                 |
+//! | ---- | ---------- | ---------------------------------------------------- |
+//! | 0    |            | Success.                                             |
+//! | 1    | `findings` | The inputs were loaded, but failed a check.          |
+//! | 2    | `usage`    | Bad command-line arguments.                          |
+//! | 3    | `input`    | A file was missing, unreadable, or couldn't be parsed. |
+//! | 4    | `network`  | A document couldn't be fetched.                      |
+//! | 10   | `internal` | Anything else.                                       |
+
+use std::{fmt, io, process::ExitCode};
+
+use openrpc_types::BrokenReference;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// The error and its causes, as text.
+    #[default]
+    Text,
+    /// A single JSON object with `code`, `kind`, `message` and `causes`.
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    Findings,
+    Usage,
+    Input,
+    Network,
+    Internal,
+}
+
+impl Kind {
+    pub fn code(self) -> u8 {
+        match self {
+            Kind::Findings => 1,
+            Kind::Usage => 2,
+            Kind::Input => 3,
+            Kind::Network => 4,
+            Kind::Internal => 10,
+        }
+    }
+}
+
+/// The inputs failed a check, rather than the command failing to run.
+///
+/// Return with `bail!(Findings(...))`.
+#[derive(Debug)]
+pub struct Findings(pub String);
+
+impl fmt::Display for Findings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Findings {}
+
+/// By the outermost error in the chain with a known type.
+pub fn kind(error: &anyhow::Error) -> Kind {
+    error
+        .chain()
+        .find_map(|it| {
+            if it.is::<Findings>() {
+                Some(Kind::Findings)
+            } else if it.is::<clap::Error>() {
+                Some(Kind::Usage)
+            } else if it.is::<ureq::Error>()
+                || it.is::<ureq::Transport>()
+                || it.is::<crate::source::RemoteError>()
+            {
+                Some(Kind::Network)
+            } else if it.is::<io::Error>()
+                || it.is::<serde_json::Error>()
+                || it.is::<serde_path_to_error::Error<serde_json::Error>>()
+                || it.is::<toml::de::Error>()
+                || it.is::<BrokenReference>()
+                || it.is::<crate::chains::ResolveError>()
+                || it.is::<csv::Error>()
+                || it.is::<crate::csv2json::RowError>()
+            {
+                Some(Kind::Input)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(Kind::Internal)
+}
+
+/// Print `error` to stderr in `format`, returning the exit code for it.
+pub fn report(error: &anyhow::Error, format: ErrorFormat) -> ExitCode {
+    let kind = kind(error);
+    match format {
+        ErrorFormat::Text => eprintln!("Error: {:?}", error),
+        ErrorFormat::Json => eprintln!(
+            "{}",
+            json!({
+                "code": kind.code(),
+                "kind": kind,
+                "message": error.to_string(),
+                "causes": error.chain().skip(1).map(ToString::to_string).collect::<Vec<_>>(),
+            })
+        ),
+    }
+    ExitCode::from(kind.code())
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{anyhow, Context as _};
+
+    use super::*;
+    use crate::source::RemoteError;
+
+    fn code(error: anyhow::Error) -> u8 {
+        kind(&error).code()
+    }
+
+    #[test]
+    fn findings() {
+        let error = anyhow::Error::new(Findings(String::from("1 error"))).context("checking");
+        assert_eq!(code(error), 1)
+    }
+
+    #[test]
+    fn usage() {
+        let error = clap::Error::new(clap::error::ErrorKind::InvalidValue);
+        assert_eq!(code(error.into()), 2);
+        // found to be invalid after parsing, like `--output` being required
+        let error = clap::Command::new("tool").error(
+            clap::error::ErrorKind::MissingRequiredArgument,
+            "--output is required",
+        );
+        assert_eq!(code(anyhow::Error::new(error).context("releasing")), 2)
+    }
+
+    #[test]
+    fn input() {
+        let error = serde_json::from_str::<serde_json::Value>("{")
+            .context("couldn't parse json from stdin")
+            .unwrap_err();
+        assert_eq!(code(error), 3);
+        let error = io::Error::from(io::ErrorKind::NotFound);
+        assert_eq!(code(error.into()), 3)
+    }
+
+    #[test]
+    fn network() {
+        let error = anyhow::Error::new(RemoteError(String::from(
+            "url http://localhost responded with HTTP status 404 Not Found",
+        )))
+        .context("couldn't fetch from url http://localhost");
+        assert_eq!(code(error), 4)
+    }
+
+    #[test]
+    fn internal() {
+        assert_eq!(code(anyhow!("something else")), 10)
+    }
+}
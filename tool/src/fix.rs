@@ -0,0 +1,220 @@
+//! Mechanical repairs to a document.
+//!
+//! Each [`Rule`] only changes what it targets: after each rule is applied,
+//! the document is checked to be otherwise semantically unchanged.
+
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+use openrpc_types::{ContentDescriptor, Method, OpenRPC, ParamStructure, ReferenceOr};
+use serde_json::{json, Value};
+
+use crate::chains::{resolve_within, ResolveError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum Rule {
+    /// Params without `required` are marked `required: true`, as the FIP
+    /// requires it to be explicit.
+    RequiredParams,
+    /// Methods are sorted by name.
+    SortedMethods,
+    /// `deprecated: false` is removed.
+    ExplicitDefaults,
+    /// Trailing whitespace is trimmed from summaries and descriptions of
+    /// methods and content descriptors.
+    TrailingWhitespace,
+    /// Method names are prefixed with `<namespace>.` if the method, or else the
+    /// document, has an `x-namespace` extension and they aren't already.
+    NamespacePrefix,
+}
+
+impl Rule {
+    pub fn id(self) -> &'static str {
+        match self {
+            Rule::RequiredParams => "required-params",
+            Rule::SortedMethods => "sorted-methods",
+            Rule::ExplicitDefaults => "explicit-defaults",
+            Rule::TrailingWhitespace => "trailing-whitespace",
+            Rule::NamespacePrefix => "namespace-prefix",
+        }
+    }
+}
+
+pub const NAMESPACE: &str = "x-namespace";
+
+/// Apply each of `rules`, returning a description of each change, by rule.
+pub fn fix(document: &mut OpenRPC, rules: &[Rule]) -> anyhow::Result<BTreeMap<Rule, Vec<String>>> {
+    let mut report = BTreeMap::new();
+    for rule in rules {
+        let before = fingerprint(document, *rule)?;
+        let changes = apply(document, *rule);
+        if fingerprint(document, *rule)? != before {
+            bail!(
+                "fixing {} changed the semantics of the document, this is a bug",
+                rule.id()
+            )
+        }
+        if !changes.is_empty() {
+            report.insert(*rule, changes);
+        }
+    }
+    Ok(report)
+}
+
+fn apply(document: &mut OpenRPC, rule: Rule) -> Vec<String> {
+    let mut changes = vec![];
+    let namespace = document
+        .extensions
+        .0
+        .get(NAMESPACE)
+        .and_then(Value::as_str)
+        .map(str::to_owned);
+    if rule == Rule::SortedMethods {
+        let mut sorted = document.methods.clone();
+        sorted.sort_by(|l, r| name(l).cmp(name(r)));
+        if sorted != document.methods {
+            changes.push(String::from("sorted methods"));
+            document.methods = sorted
+        }
+        return changes;
+    }
+    for method in document.methods.iter_mut().filter_map(|it| match it {
+        ReferenceOr::Reference(_) => None,
+        ReferenceOr::Item(it) => Some(it),
+    }) {
+        let Method {
+            name,
+            summary,
+            description,
+            params,
+            result,
+            deprecated,
+            extensions,
+            ..
+        } = method;
+        match rule {
+            Rule::RequiredParams => {
+                for param in params.iter_mut().filter_map(|it| match it {
+                    ReferenceOr::Reference(_) => None,
+                    ReferenceOr::Item(it) => Some(it),
+                }) {
+                    if param.required.is_none() {
+                        param.required = Some(true);
+                        changes.push(format!("{} param `{}`", name, param.name))
+                    }
+                }
+            }
+            Rule::ExplicitDefaults => {
+                if *deprecated == Some(false) {
+                    *deprecated = None;
+                    changes.push(format!("{} deprecated", name))
+                }
+                for it in params.iter_mut().chain(result).filter_map(|it| match it {
+                    ReferenceOr::Reference(_) => None,
+                    ReferenceOr::Item(it) => Some(it),
+                }) {
+                    if it.deprecated == Some(false) {
+                        it.deprecated = None;
+                        changes.push(format!("{} `{}` deprecated", name, it.name))
+                    }
+                }
+            }
+            Rule::TrailingWhitespace => {
+                for (what, it) in [("summary", summary), ("description", description)] {
+                    if trim_end(it) {
+                        changes.push(format!("{} {}", name, what))
+                    }
+                }
+                for it in params.iter_mut().chain(result).filter_map(|it| match it {
+                    ReferenceOr::Reference(_) => None,
+                    ReferenceOr::Item(it) => Some(it),
+                }) {
+                    let ContentDescriptor {
+                        name: descriptor,
+                        summary,
+                        description,
+                        ..
+                    } = it;
+                    for (what, it) in [("summary", summary), ("description", description)] {
+                        if trim_end(it) {
+                            changes.push(format!("{} `{}` {}", name, descriptor, what))
+                        }
+                    }
+                }
+            }
+            Rule::NamespacePrefix => {
+                let namespace = extensions
+                    .0
+                    .get(NAMESPACE)
+                    .and_then(Value::as_str)
+                    .or(namespace.as_deref());
+                if let Some(namespace) = namespace {
+                    let prefix = format!("{}.", namespace);
+                    if !name.starts_with(&prefix) {
+                        let new = format!("{}{}", prefix, name);
+                        changes.push(format!("{} renamed to {}", name, new));
+                        *name = new
+                    }
+                }
+            }
+            Rule::SortedMethods => unreachable!(),
+        }
+    }
+    changes
+}
+
+fn trim_end(it: &mut Option<String>) -> bool {
+    match it {
+        Some(s) if s.trim_end().len() != s.len() => {
+            s.truncate(s.trim_end().len());
+            true
+        }
+        _ => false,
+    }
+}
+
+fn name(it: &ReferenceOr<Method>) -> &str {
+    match it {
+        ReferenceOr::Reference(it) => it,
+        ReferenceOr::Item(it) => &it.name,
+    }
+}
+
+/// The parts of `document` which affect what's on the wire, ignoring
+/// whatever `rule` targets.
+fn fingerprint(document: &OpenRPC, rule: Rule) -> Result<Value, ResolveError> {
+    let resolved = resolve_within(document.clone())?;
+    let descriptor = |it: &ContentDescriptor| {
+        json!({
+            "name": it.name,
+            "required": match rule {
+                Rule::RequiredParams => None,
+                _ => Some(it.required.unwrap_or_default()),
+            },
+            "schema": it.schema,
+        })
+    };
+    let mut methods = resolved
+        .methods
+        .iter()
+        .map(|it| {
+            let name = match rule {
+                Rule::NamespacePrefix => it.name.rsplit('.').next().unwrap_or_default(),
+                _ => &it.name,
+            };
+            json!({
+                "name": name,
+                "params": it.params.iter().map(descriptor).collect::<Vec<_>>(),
+                "result": it.result.as_ref().map(descriptor),
+                "paramStructure": it.param_structure.unwrap_or(ParamStructure::Either),
+                "deprecated": it.deprecated.unwrap_or_default(),
+                "errors": it.errors,
+            })
+        })
+        .collect::<Vec<_>>();
+    methods.sort_by(|l, r| l["name"].as_str().cmp(&r["name"].as_str()));
+    Ok(json!({
+        "methods": methods,
+        "schemas": resolved.components.and_then(|it| it.schemas),
+    }))
+}
@@ -1,31 +1,16 @@
-use std::{
-    collections::{BTreeMap, HashSet},
-    iter,
-};
+use std::collections::HashSet;
 
-use either::Either;
 use openrpc_types::{resolved, BrokenReference};
-use schemars::schema::{
-    ArrayValidation, ObjectValidation, Schema, SchemaObject, SingleOrVec, SubschemaValidation,
-};
+use tracing::debug;
 
-pub fn prune_schemas(document: &mut resolved::OpenRPC) -> Result<(), BrokenReference> {
-    let mut alive = HashSet::new();
+use crate::reference_index::ReferenceIndex;
 
-    for root in document
-        .methods
-        .iter()
-        .flat_map(|it| it.params.iter().chain(it.result.as_ref()))
-    {
-        mark(
-            &mut alive,
-            document
-                .components
-                .as_ref()
-                .and_then(|it| it.schemas.as_ref()),
-            &root.schema,
-        )?;
-    }
+pub fn prune_schemas(document: &mut resolved::OpenRPC) -> Result<(), BrokenReference> {
+    let alive = ReferenceIndex::new(document)
+        .reachable_from_methods()?
+        .into_iter()
+        .map(String::from)
+        .collect::<HashSet<_>>();
 
     // sweep
     if let Some(it) = document
@@ -33,114 +18,33 @@ pub fn prune_schemas(document: &mut resolved::OpenRPC) -> Result<(), BrokenRefer
         .as_mut()
         .and_then(|it| it.schemas.as_mut())
     {
-        it.retain(|k, _| alive.contains(k))
+        it.retain(|k, _| {
+            let keep = alive.contains(k);
+            if !keep {
+                debug!(schema = k, "pruned, unreachable from any method")
+            }
+            keep
+        })
     }
 
     Ok(())
 }
 
-fn mark(
-    alive: &mut HashSet<String>,
-    lookup: Option<&BTreeMap<String, Schema>>,
-    schema: &Schema,
-) -> Result<(), BrokenReference> {
-    match schema {
-        Schema::Bool(_) => Ok(()),
-        Schema::Object(SchemaObject {
-            metadata: _,
-            instance_type: _,
-            format: _,
-            enum_values: _,
-            const_value: _,
-            subschemas,
-            number: _,
-            string: _,
-            array,
-            object,
-            reference,
-            extensions: _,
-        }) => {
-            if let Some(SubschemaValidation {
-                all_of,
-                any_of,
-                one_of,
-                not,
-                if_schema,
-                then_schema,
-                else_schema,
-            }) = subschemas.as_deref()
-            {
-                for schema in iter::empty()
-                    .chain(all_of.iter().flatten())
-                    .chain(any_of.iter().flatten())
-                    .chain(one_of.iter().flatten())
-                    .chain(not.as_deref())
-                    .chain(if_schema.as_deref())
-                    .chain(then_schema.as_deref())
-                    .chain(else_schema.as_deref())
-                {
-                    mark(alive, lookup, schema)?
-                }
-            };
-            if let Some(ArrayValidation {
-                items,
-                additional_items,
-                max_items: _,
-                min_items: _,
-                unique_items: _,
-                contains,
-            }) = array.as_deref()
-            {
-                for schema in items
-                    .iter()
-                    .flat_map(iter_single_or_vec)
-                    .chain(additional_items.as_deref())
-                    .chain(contains.as_deref())
-                {
-                    mark(alive, lookup, schema)?
-                }
-            }
-            if let Some(ObjectValidation {
-                max_properties: _,
-                min_properties: _,
-                required: _,
-                properties,
-                pattern_properties,
-                additional_properties,
-                property_names,
-            }) = object.as_deref()
-            {
-                for schema in properties
-                    .values()
-                    .chain(pattern_properties.values())
-                    .chain(additional_properties.as_deref())
-                    .chain(property_names.as_deref())
-                {
-                    mark(alive, lookup, schema)?
-                }
-            }
-            if let Some(reference) = reference {
-                match reference.strip_prefix("#/components/schemas/") {
-                    Some(key) => {
-                        if !alive.contains(key) {
-                            alive.insert(key.to_owned());
-                            match lookup.as_ref().and_then(|it| it.get(key)) {
-                                Some(child) => mark(alive, lookup, child)?,
-                                None => return Err(BrokenReference(reference.clone())),
-                            }
-                        }
-                    }
-                    None => return Err(BrokenReference(reference.clone())),
-                }
-            }
-            Ok(())
-        }
-    }
-}
-
-fn iter_single_or_vec<T>(it: &SingleOrVec<T>) -> impl Iterator<Item = &T> {
-    match it {
-        SingleOrVec::Single(it) => Either::Left(iter::once(&**it)),
-        SingleOrVec::Vec(it) => Either::Right(it.iter()),
-    }
+/// `$ref`s to component schemas which don't exist, described with where they
+/// are.
+pub fn dead_references(document: &resolved::OpenRPC) -> Vec<String> {
+    let index = ReferenceIndex::new(document);
+    index
+        .dead()
+        .map(|(reference, it)| match index.contains(reference) {
+            true => format!(
+                "{} has $ref {} to a component which isn't a schema, in {}",
+                it.provenance, it.reference, it.pointer
+            ),
+            false => format!(
+                "{} has dead $ref {}, in {}",
+                it.provenance, it.reference, it.pointer
+            ),
+        })
+        .collect()
 }
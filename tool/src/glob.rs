@@ -0,0 +1,18 @@
+//! Shell-style patterns for method names.
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters,
+/// and `?` matches a single character.
+pub fn matches(pattern: &str, text: &str) -> bool {
+    fn imp(pattern: &[char], text: &[char]) -> bool {
+        match pattern.split_first() {
+            None => text.is_empty(),
+            Some(('*', rest)) => (0..=text.len()).any(|ix| imp(rest, &text[ix..])),
+            Some(('?', rest)) => !text.is_empty() && imp(rest, &text[1..]),
+            Some((c, rest)) => text.first() == Some(c) && imp(rest, &text[1..]),
+        }
+    }
+    imp(
+        &pattern.chars().collect::<Vec<_>>(),
+        &text.chars().collect::<Vec<_>>(),
+    )
+}
@@ -0,0 +1,195 @@
+//! Which methods and component schemas reference which component schemas.
+//!
+//! Nodes are identified as `method:<name>` or `schema:<key>`, so that methods
+//! and schemas with the same name don't collide.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    ops::Bound,
+};
+
+use openrpc_types::{resolved, BrokenReference};
+use serde_json::{json, Value};
+
+use crate::{component_ref::ComponentRef, reference_index::ReferenceIndex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Format {
+    /// Graphviz.
+    #[default]
+    Dot,
+    Json,
+}
+
+/// Edges are direct references, from a method's params and result or from a
+/// schema, to a component schema.
+#[derive(Debug, Default)]
+pub struct Graph {
+    pub methods: BTreeMap<String, BTreeSet<String>>,
+    pub schemas: BTreeMap<String, BTreeSet<String>>,
+}
+
+pub fn graph(document: &resolved::OpenRPC) -> Result<Graph, BrokenReference> {
+    let empty = BTreeMap::new();
+    let schemas = document
+        .components
+        .as_ref()
+        .and_then(|it| it.schemas.as_ref())
+        .unwrap_or(&empty);
+    let index = ReferenceIndex::new(document);
+    let lookup = |references: &mut dyn Iterator<Item = &ComponentRef>| {
+        references
+            .map(|it| match index.schema(it) {
+                Some(key) => Ok(String::from(key)),
+                None => Err(BrokenReference(it.to_string())),
+            })
+            .collect::<Result<BTreeSet<_>, _>>()
+    };
+    let mut graph = Graph::default();
+    for method in &document.methods {
+        graph.methods.insert(
+            method.name.clone(),
+            lookup(&mut index.references(&method.name))?,
+        );
+    }
+    for key in schemas.keys() {
+        graph.schemas.insert(
+            key.clone(),
+            lookup(&mut index.component_references(&ComponentRef::schema(key)))?,
+        );
+    }
+    Ok(graph)
+}
+
+impl Graph {
+    /// Only `method`, and the schemas it transitively references.
+    pub fn focus(self, method: &str) -> Option<Self> {
+        let roots = self.methods.get(method)?.clone();
+        let mut reachable = BTreeSet::new();
+        let mut stack = Vec::from_iter(roots.iter().cloned());
+        while let Some(key) = stack.pop() {
+            if reachable.insert(key.clone()) {
+                stack.extend(self.schemas[&key].iter().cloned())
+            }
+        }
+        Some(Self {
+            methods: BTreeMap::from_iter([(method.to_owned(), roots)]),
+            schemas: self
+                .schemas
+                .into_iter()
+                .filter(|(key, _)| reachable.contains(key))
+                .collect(),
+        })
+    }
+
+    /// Pairs of methods which directly reference at least one of the same
+    /// schemas, weighted by the number of shared schemas.
+    pub fn project_methods(&self) -> BTreeMap<(&str, &str), usize> {
+        let mut edges = BTreeMap::new();
+        for (left, left_keys) in &self.methods {
+            for (right, right_keys) in self
+                .methods
+                .range::<String, _>((Bound::Excluded(left), Bound::Unbounded))
+            {
+                let weight = left_keys.intersection(right_keys).count();
+                if weight != 0 {
+                    edges.insert((left.as_str(), right.as_str()), weight);
+                }
+            }
+        }
+        edges
+    }
+
+    pub fn dot(&self) -> String {
+        let mut out = String::from("digraph {\n");
+        for method in self.methods.keys() {
+            writeln!(
+                out,
+                "  {} [label={}, shape=box];",
+                quote(&format!("method:{method}")),
+                quote(method)
+            )
+            .unwrap();
+        }
+        for key in self.schemas.keys() {
+            writeln!(
+                out,
+                "  {} [label={}];",
+                quote(&format!("schema:{key}")),
+                quote(key)
+            )
+            .unwrap();
+        }
+        for (kind, edges) in [("method", &self.methods), ("schema", &self.schemas)] {
+            for (from, to) in edges {
+                for to in to {
+                    writeln!(
+                        out,
+                        "  {} -> {};",
+                        quote(&format!("{kind}:{from}")),
+                        quote(&format!("schema:{to}"))
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn json(&self) -> Value {
+        let nodes =
+            self.methods
+                .keys()
+                .map(|it| json!({ "id": format!("method:{it}"), "kind": "method", "name": it }))
+                .chain(self.schemas.keys().map(
+                    |it| json!({ "id": format!("schema:{it}"), "kind": "schema", "name": it }),
+                ))
+                .collect::<Vec<_>>();
+        let edges = [("method", &self.methods), ("schema", &self.schemas)]
+            .into_iter()
+            .flat_map(|(kind, edges)| {
+                edges.iter().flat_map(move |(from, to)| {
+                    to.iter().map(move |to| {
+                        json!({ "from": format!("{kind}:{from}"), "to": format!("schema:{to}") })
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({ "nodes": nodes, "edges": edges })
+    }
+}
+
+pub fn projected_dot(edges: &BTreeMap<(&str, &str), usize>, methods: &[&str]) -> String {
+    let mut out = String::from("graph {\n");
+    for method in methods {
+        writeln!(out, "  {};", quote(method)).unwrap();
+    }
+    for ((left, right), weight) in edges {
+        writeln!(
+            out,
+            "  {} -- {} [weight={weight}, label={weight}];",
+            quote(left),
+            quote(right)
+        )
+        .unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+pub fn projected_json(edges: &BTreeMap<(&str, &str), usize>, methods: &[&str]) -> Value {
+    json!({
+        "nodes": methods,
+        "edges": edges
+            .iter()
+            .map(|((left, right), weight)| json!({ "from": left, "to": right, "weight": weight }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// A Graphviz quoted string.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
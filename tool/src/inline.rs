@@ -0,0 +1,183 @@
+//! Replace `$ref`s with the schemas they point to.
+//!
+//! A `$ref` with sibling keywords is replaced with an `allOf` of the target,
+//! keeping the siblings.
+//! Other OpenRPC references (content descriptors, errors, examples, tags)
+//! are already replaced by [`openrpc_types::resolve_within`].
+
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+use openrpc_types::{resolved, Components};
+use schemars::schema::{Schema, SchemaObject, SubschemaValidation};
+use serde_json::Value;
+
+use crate::{
+    component_ref::{ComponentRef, ComponentSection},
+    gc, schema_visit,
+};
+
+pub const TRUNCATED: &str = "x-cycle-truncated";
+
+#[derive(Debug, Clone, Copy)]
+pub enum Limit {
+    /// Inline every `$ref`.
+    ///
+    /// A recursive `$ref` is an error, unless a limit is given, in which case
+    /// the `$ref` is replaced with an [`x-cycle-truncated`](TRUNCATED) schema
+    /// once it has been inlined that many times within itself.
+    All { cycles: Option<usize> },
+    /// Only inline `$ref`s nested within fewer than this many other inlined
+    /// `$ref`s.
+    Depth(usize),
+}
+
+/// Inline the schemas in the methods of `document`.
+///
+/// With [`Limit::All`], `components` are removed.
+/// Otherwise, only the component schemas which are still referenced are kept,
+/// along with the other sections.
+pub fn inline(document: &mut resolved::OpenRPC, limit: Limit) -> anyhow::Result<()> {
+    let schemas = document
+        .components
+        .as_mut()
+        .and_then(|it| it.schemas.take())
+        .unwrap_or_default();
+    for method in &mut document.methods {
+        for it in method.params.iter_mut().chain(&mut method.result) {
+            imp(&mut it.schema, &schemas, &mut vec![], limit)?
+        }
+    }
+    match limit {
+        Limit::All { .. } => document.components = None,
+        Limit::Depth(_) => {
+            document
+                .components
+                .get_or_insert_with(Components::default)
+                .schemas = Some(schemas);
+            gc::prune_schemas(document)?;
+            if let Some(components) = &mut document.components {
+                if components.schemas.as_ref().is_some_and(BTreeMap::is_empty) {
+                    components.schemas = None
+                }
+            }
+            if document.components.as_ref().is_some_and(is_empty) {
+                document.components = None
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_empty(components: &Components) -> bool {
+    let Components {
+        content_descriptors,
+        schemas,
+        examples,
+        errors,
+        example_pairing_objects,
+        tags,
+        extensions,
+    } = components;
+    content_descriptors.is_none()
+        && schemas.is_none()
+        && examples.is_none()
+        && errors.is_none()
+        && example_pairing_objects.is_none()
+        && tags.is_none()
+        && extensions.0.is_empty()
+}
+
+fn imp(
+    schema: &mut Schema,
+    schemas: &BTreeMap<String, Schema>,
+    stack: &mut Vec<String>,
+    limit: Limit,
+) -> anyhow::Result<()> {
+    let target = match schema {
+        Schema::Object(SchemaObject {
+            reference: Some(reference),
+            ..
+        }) => {
+            let Some((key, target)) = ComponentRef::parse(reference)
+                .key_in(ComponentSection::Schemas)
+                .and_then(|it| schemas.get_key_value(it))
+            else {
+                bail!("broken reference: {}", reference)
+            };
+            let times = stack.iter().filter(|it| *it == key).count();
+            match limit {
+                Limit::Depth(depth) if stack.len() >= depth => return Ok(()),
+                Limit::All { cycles: None } if times > 0 => {
+                    bail!("cyclic reference: {} -> {}", stack.join(" -> "), key)
+                }
+                Limit::All {
+                    cycles: Some(cycles),
+                } if times > cycles => {
+                    *schema = Schema::Object(SchemaObject {
+                        extensions: BTreeMap::from_iter([(
+                            String::from(TRUNCATED),
+                            Value::String(key.clone()),
+                        )]),
+                        ..SchemaObject::default()
+                    });
+                    return Ok(());
+                }
+                _ => {}
+            }
+            let mut target = target.clone();
+            stack.push(key.clone());
+            imp(&mut target, schemas, stack, limit)?;
+            stack.pop();
+            Some(target)
+        }
+        _ => None,
+    };
+    for child in schema_visit::children_mut(schema) {
+        imp(child, schemas, stack, limit)?
+    }
+    if let (Some(target), Schema::Object(object)) = (target, &mut *schema) {
+        object.reference = None;
+        match *object == SchemaObject::default() {
+            true => *schema = target,
+            false => object
+                .subschemas
+                .get_or_insert_with(Box::<SubschemaValidation>::default)
+                .all_of
+                .get_or_insert_with(Vec::new)
+                .insert(0, target),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use openrpc_types::OpenRPC;
+
+    use super::*;
+    use crate::{chains::resolve_within, openrpc_diff, release};
+
+    #[test]
+    fn depth_is_equivalent() {
+        let spec = serde_json::from_str::<OpenRPC>(include_str!("../../spec.json")).unwrap();
+        for depth in [1, 3] {
+            let mut document = resolve_within(spec.clone()).unwrap();
+            inline(&mut document, Limit::Depth(depth)).unwrap();
+            assert!(gc::dead_references(&document).is_empty());
+            let json = serde_json::to_value(&document).unwrap();
+            assert_eq!(
+                json["components"]["tags"],
+                serde_json::to_value(&spec).unwrap()["components"]["tags"]
+            );
+            let inlined = serde_json::from_value::<OpenRPC>(json).unwrap();
+            let summary = openrpc_diff::diff(spec.clone(), inlined).unwrap();
+            assert!(
+                release::is_empty(&summary),
+                "depth {}: {}",
+                depth,
+                serde_json::to_string(&summary).unwrap()
+            );
+        }
+    }
+}
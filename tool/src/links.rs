@@ -0,0 +1,354 @@
+//! `links` on methods, which [`openrpc_types`] doesn't model.
+//!
+//! A link says that a method's result can be fed to another method, like the
+//! CID returned by `Filecoin.MpoolPush` to `Filecoin.StateSearchMsg`.
+//! Links are read from the JSON of a document, so [`check`] can report on
+//! them, [`diff`] can compare them, and [`carry`] can copy them onto a
+//! document which was parsed and written again, like the output of `select`.
+//!
+//! Runtime expressions in link `params` are `$params.<name>`, or `$result`
+//! followed by any number of `.<field>`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    component_ref::{ComponentRef, ComponentSection},
+    openrpc_diff::LinksChange,
+};
+
+/// A link of a method in a document.
+struct Use<'a> {
+    method: &'a Map<String, Value>,
+    /// Like `methods/Filecoin.MpoolPush/links/0`.
+    at: String,
+    /// The key in `components.links`, if the link is a `$ref`.
+    component: Option<String>,
+    /// [`Err`] with the `$ref` if it is broken.
+    link: Result<&'a Value, String>,
+}
+
+fn uses(document: &Value) -> Vec<Use<'_>> {
+    let mut uses = vec![];
+    for method in methods(document) {
+        let Some(links) = method.get("links").and_then(Value::as_array) else {
+            continue;
+        };
+        for (ix, link) in links.iter().enumerate() {
+            let at = format!("methods/{}/links/{}", name(method), ix);
+            let (component, link) = match link.get("$ref").and_then(Value::as_str) {
+                Some(reference) => {
+                    let parsed = ComponentRef::parse(reference);
+                    let key = parsed.key_in(ComponentSection::Links);
+                    let target = key.and_then(|key| document["components"]["links"].get(key));
+                    (
+                        key.map(String::from),
+                        target.ok_or_else(|| reference.to_owned()),
+                    )
+                }
+                None => (None, Ok(link)),
+            };
+            uses.push(Use {
+                method,
+                at,
+                component,
+                link,
+            })
+        }
+    }
+    uses
+}
+
+/// Problems with the links in `document`:
+/// - broken `$ref`s, except into other documents, which aren't checked.
+/// - links to methods which aren't in the document.
+/// - link params which aren't valid runtime expressions, or name a param or
+///   result field which the method doesn't have.
+pub fn check(document: &Value) -> Vec<String> {
+    let names = methods(document).map(name).collect::<BTreeSet<_>>();
+    let mut problems = vec![];
+    for Use {
+        method, at, link, ..
+    } in uses(document)
+    {
+        let link = match link {
+            Ok(it) => it,
+            Err(reference) => {
+                if !matches!(ComponentRef::parse(&reference), ComponentRef::External(_)) {
+                    problems.push(format!("error resolving $ref '{}' at {}", reference, at))
+                }
+                continue;
+            }
+        };
+        if let Some(target) = link.get("method").and_then(Value::as_str) {
+            if !names.contains(target) {
+                problems.push(format!(
+                    "{} links to {}, which isn't in the document",
+                    at, target
+                ))
+            }
+        }
+        let params = link.get("params").and_then(Value::as_object);
+        for (param, value) in params.into_iter().flatten() {
+            let Some(expression) = value.as_str().filter(|it| it.starts_with('$')) else {
+                continue;
+            };
+            if let Err(e) = self::expression(document, method, expression) {
+                problems.push(format!("{}/params/{}: {}", at, param, e))
+            }
+        }
+    }
+    problems
+}
+
+/// Check that `expression` is well-formed, and names something `method` has
+/// where that can be told.
+fn expression(
+    document: &Value,
+    method: &Map<String, Value>,
+    expression: &str,
+) -> Result<(), String> {
+    let is_name = |it: &str| {
+        !it.is_empty()
+            && it
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+    if let Some(param) = expression.strip_prefix("$params.") {
+        if !is_name(param) {
+            return Err(format!("{} isn't a runtime expression", expression));
+        }
+        let params = method.get("params").and_then(Value::as_array);
+        let found = params
+            .into_iter()
+            .flatten()
+            .any(|it| follow(document, it).get("name").and_then(Value::as_str) == Some(param));
+        return match found {
+            true => Ok(()),
+            false => Err(format!("{} has no param {}", name(method), param)),
+        };
+    }
+    let Some(fields) = expression.strip_prefix("$result") else {
+        return Err(format!(
+            "{} isn't a runtime expression, like $result.field or $params.name",
+            expression
+        ));
+    };
+    let fields = match fields {
+        "" => vec![],
+        it => match it.strip_prefix('.') {
+            Some(it) => it.split('.').collect(),
+            None => vec![""],
+        },
+    };
+    if !fields.iter().all(|it| is_name(it)) {
+        return Err(format!("{} isn't a runtime expression", expression));
+    }
+    let Some(&field) = fields.first() else {
+        return Ok(());
+    };
+    let Some(result) = method.get("result") else {
+        return Err(format!("{} has no result", name(method)));
+    };
+    let schema = follow(document, result)
+        .get("schema")
+        .unwrap_or(&Value::Null);
+    let schema = follow(document, schema);
+    match schema.get("properties").and_then(Value::as_object) {
+        Some(properties) if !properties.contains_key(field) => Err(format!(
+            "the result of {} has no field {}",
+            name(method),
+            field
+        )),
+        // only objects with listed properties can be checked
+        _ => Ok(()),
+    }
+}
+
+/// The methods in both `left` and `right` whose links differ, after
+/// following `$ref`s into `components.links`.
+pub fn diff(left: &Value, right: &Value) -> BTreeMap<String, LinksChange> {
+    fn by_method(document: &Value) -> BTreeMap<&str, Vec<Value>> {
+        let mut links = methods(document)
+            .map(|it| (name(it), vec![]))
+            .collect::<BTreeMap<_, _>>();
+        for Use { method, link, .. } in uses(document) {
+            let link = match link {
+                Ok(it) => it.clone(),
+                Err(reference) => json!({ "$ref": reference }),
+            };
+            links.entry(name(method)).or_default().push(link)
+        }
+        links
+    }
+    let (left, mut right) = (by_method(left), by_method(right));
+    left.into_iter()
+        .filter_map(|(method, left)| {
+            let right = right.remove(method)?;
+            (left != right).then(|| (method.to_owned(), LinksChange { left, right }))
+        })
+        .collect()
+}
+
+/// Copy the links of each method in `to` from the same method in `from`,
+/// with the `components.links` they refer to.
+///
+/// Link components which no method in `to` uses are left out.
+/// Returns a warning for each link to a method which isn't in `to`.
+pub fn carry(from: &Value, to: &mut Value) -> Vec<String> {
+    let kept = methods(to)
+        .map(name)
+        .map(String::from)
+        .collect::<BTreeSet<_>>();
+    let mut links = BTreeMap::new();
+    let mut components = BTreeMap::new();
+    let mut warnings = vec![];
+    for Use {
+        method,
+        at,
+        component,
+        link,
+    } in uses(from)
+    {
+        if !kept.contains(name(method)) {
+            continue;
+        }
+        links
+            .entry(name(method).to_owned())
+            .or_insert_with(|| method["links"].clone());
+        let Ok(link) = link else { continue };
+        if let Some(key) = component {
+            components.insert(key, link.clone());
+        }
+        if let Some(target) = link.get("method").and_then(Value::as_str) {
+            if !kept.contains(target) {
+                warnings.push(format!("{} links to {}, which wasn't selected", at, target))
+            }
+        }
+    }
+    if let Some(methods) = to.get_mut("methods").and_then(Value::as_array_mut) {
+        for method in methods.iter_mut().filter_map(Value::as_object_mut) {
+            if let Some(it) = method
+                .get("name")
+                .and_then(Value::as_str)
+                .and_then(|it| links.remove(it))
+            {
+                method.insert(String::from("links"), it);
+            }
+        }
+    }
+    if !components.is_empty() {
+        if let Some(to) = to.as_object_mut() {
+            let section = to
+                .entry("components")
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+                .and_then(|it| {
+                    it.entry("links")
+                        .or_insert_with(|| Value::Object(Map::new()))
+                        .as_object_mut()
+                });
+            if let Some(section) = section {
+                section.extend(components)
+            }
+        }
+    }
+    warnings
+}
+
+/// The methods in `document` which aren't `$ref`s.
+fn methods(document: &Value) -> impl Iterator<Item = &Map<String, Value>> {
+    document
+        .get("methods")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+        .filter(|it| it.contains_key("name"))
+}
+
+fn name(method: &Map<String, Value>) -> &str {
+    method
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+/// `value`, or what it refers to if it is a `$ref` to a component.
+fn follow<'a>(document: &'a Value, value: &'a Value) -> &'a Value {
+    let target = || match ComponentRef::parse(value.get("$ref")?.as_str()?) {
+        ComponentRef::Component { section, key } => {
+            document.get("components")?.get(section.name())?.get(key)
+        }
+        ComponentRef::External(_) | ComponentRef::Malformed(_) => None,
+    };
+    target().unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked() {
+        let document = json!({
+            "methods": [
+                {
+                    "name": "Filecoin.MpoolPush",
+                    "params": [],
+                    "result": {
+                        "name": "cid",
+                        "schema": { "type": "object", "properties": { "Root": {} } }
+                    },
+                    "links": [
+                        {
+                            "name": "search",
+                            "method": "Filecoin.StateSearchMsg",
+                            "params": { "cid": "$result.Root" }
+                        },
+                        { "$ref": "#/components/links/Search" },
+                        { "$ref": "#/components/links/Missing" },
+                        { "$ref": "common.json#/components/links/Search" },
+                        {
+                            "name": "wait",
+                            "method": "Filecoin.StateWaitMsg",
+                            "params": { "cid": "$result.Leaf" }
+                        }
+                    ]
+                },
+                {
+                    "name": "Filecoin.StateSearchMsg",
+                    "params": [{ "name": "cid", "schema": {} }],
+                    "links": [
+                        {
+                            "name": "push",
+                            "method": "Filecoin.MpoolPush",
+                            "params": { "cid": "$params.cid" }
+                        }
+                    ]
+                }
+            ],
+            "components": {
+                "links": {
+                    "Search": {
+                        "name": "search",
+                        "method": "Filecoin.StateSearchMsg",
+                        "params": { "cid": "$result.Root" }
+                    }
+                }
+            }
+        });
+        assert_eq!(
+            check(&document),
+            [
+                "error resolving $ref '#/components/links/Missing' at \
+                 methods/Filecoin.MpoolPush/links/2",
+                "methods/Filecoin.MpoolPush/links/4 links to Filecoin.StateWaitMsg, \
+                 which isn't in the document",
+                "methods/Filecoin.MpoolPush/links/4/params/cid: \
+                 the result of Filecoin.MpoolPush has no field Leaf",
+            ]
+        );
+    }
+}
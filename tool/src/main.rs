@@ -1,53 +1,307 @@
+mod apply;
+mod blame;
+mod browse;
+mod chains;
+mod check_go;
+mod codegen;
+mod component_ref;
+mod config;
+mod conformance;
+mod coverage;
+mod csv2json;
+mod deprecations;
+mod diagnostic;
+mod docs;
+mod envelopes;
+mod exit;
+mod fix;
 mod gc;
+mod glob;
+mod graph;
+mod inline;
+mod json2csv;
+mod links;
+mod merge;
+mod merge_examples;
+mod mock;
+mod normalize;
 mod openrpc_diff;
+mod progress;
+mod provenance;
+mod query;
+mod redact;
+mod reference_index;
+mod release;
+mod scaffold;
+mod schema_summary;
+mod schema_visit;
+mod seal;
+mod snippets;
+mod source;
+mod split;
+mod stats;
+mod vectors;
+mod verify;
 
-use anyhow::Context as _;
-use clap::Parser;
+use anyhow::{bail, Context as _};
+use chains::resolve_within;
+use clap::{CommandFactory as _, FromArgMatches as _, Parser, ValueEnum as _};
 use itertools::Itertools as _;
-use openrpc_types::{resolve_within, OpenRPC};
+use openrpc_types::{resolved, BrokenReference, OpenRPC};
+use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use source::{load_document, load_document_and_json, load_resolved, FetchOptions, SpecSource};
 use std::{
     collections::{BTreeMap, BTreeSet},
-    fs::File,
-    io,
+    fs,
+    io::{self, IsTerminal as _, Write as _},
     path::{Path, PathBuf},
+    process::ExitCode,
+    time::{Duration, Instant},
 };
+use tracing::debug;
+use tracing_subscriber::EnvFilter;
 
 #[derive(Parser)]
 enum Args {
-    #[command(subcommand)]
-    Openrpc(Openrpc),
-    /// Interpret stdin as a `delimter`-separated series of lines, with a header,
-    /// and print JSON.
+    /// Subcommands related to processing OpenRPC documents.
+    ///
+    /// Documents may be given as a path, `-` for stdin, an `http(s)://` URL,
+    /// or `rpc+http(s)://` to call `rpc.discover` at that endpoint.
+    Openrpc {
+        #[command(flatten)]
+        fetch: FetchOptions,
+        #[command(subcommand)]
+        command: Openrpc,
+    },
+    /// Report which methods in `spec` are called in captured traffic, and
+    /// which params they're called with.
+    ///
+    /// See [`coverage`] for the traffic format.
+    Coverage {
+        #[command(flatten)]
+        fetch: FetchOptions,
+        #[arg(long)]
+        spec: SpecSource,
+        /// A newline-delimited JSON file of requests, which may be gzipped.
+        #[arg(long, required = true)]
+        traffic: Vec<PathBuf>,
+        #[arg(long, value_enum, default_value_t)]
+        format: StatsFormat,
+        /// Fail if fewer than this percentage of methods are called.
+        #[arg(long)]
+        fail_under: Option<f64>,
+    },
+    /// Send the params of each example in `spec` to `remote`, and check the
+    /// result against the example and the method's schema.
+    ///
+    /// Methods tagged `x-mutating`, or with an `x-mutating: true` extension,
+    /// are skipped.
+    VerifyExamples {
+        #[command(flatten)]
+        fetch: FetchOptions,
+        #[arg(long)]
+        spec: SpecSource,
+        /// The JSON-RPC endpoint of the node.
+        #[arg(long)]
+        remote: String,
+        /// Only check methods matching this pattern, where `*` matches
+        /// anything. May be given multiple times.
+        #[arg(long = "method")]
+        methods: Vec<String>,
+        /// A file of method patterns to skip, one per line.
+        #[arg(long)]
+        skip_file: Option<PathBuf>,
+        /// A JSON pointer into results to ignore when comparing, like
+        /// `/Height` or `/Blocks/*/Timestamp`. May be given multiple times.
+        #[arg(long)]
+        ignore: Vec<String>,
+    },
+    /// Run the cases in `cases` against `remote`, and print a report.
+    ///
+    /// See [`conformance`] for the format of cases.
+    /// Fails if any case fails.
+    Conformance {
+        #[command(flatten)]
+        fetch: FetchOptions,
+        #[arg(long)]
+        spec: SpecSource,
+        /// The JSON-RPC endpoint of the node.
+        #[arg(long)]
+        remote: String,
+        #[arg(long)]
+        cases: PathBuf,
+        /// Run this many cases at once.
+        #[arg(long, default_value_t = 1)]
+        parallel: usize,
+        #[arg(long, value_enum, default_value_t)]
+        format: conformance::Format,
+        /// Write the report to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Serve `spec` as a JSON-RPC endpoint, answering each request with the
+    /// result of a matching example.
+    ///
+    /// Params are checked against the method's schemas.
+    /// Methods without examples get a result generated from their schema.
+    Mock {
+        #[command(flatten)]
+        fetch: FetchOptions,
+        #[arg(long)]
+        spec: SpecSource,
+        /// The address to listen on.
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: String,
+        /// Seed for generated results.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Only serve methods with examples, rather than generating results.
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Interpret each of `inputs` as a `delimter`-separated series of lines,
+    /// with a header, and print JSON.
+    ///
+    /// Records are written as they are read, so large inputs aren't buffered.
+    /// Inputs must have the same columns, in any order.
     Csv2Json {
+        /// Read from these files in turn, where `-` is stdin, or from stdin if
+        /// none are given.
+        inputs: Vec<PathBuf>,
+        /// May be an escape like `\t` or `\x1f`, or more than one character,
+        /// like `||`, in which case quotes aren't interpreted.
+        #[arg(short, long, default_value_t = csv2json::Delimiter::Byte(b'\t'))]
+        delimiter: csv2json::Delimiter,
+        /// The character around quoted cells.
+        #[arg(
+            long,
+            default_value_t = Char(AsciiChar::Quotation),
+            conflicts_with = "no_quoting"
+        )]
+        quote: Char,
+        /// Don't interpret quotes.
+        #[arg(long)]
+        no_quoting: bool,
+        /// Trim whitespace around headers and cells.
+        #[arg(long)]
+        trim: bool,
+        /// Print the array on one line.
+        #[arg(long, conflicts_with = "ndjson")]
+        compact: bool,
+        /// Print each record on its own line, instead of an array.
+        #[arg(long)]
+        ndjson: bool,
+        /// Write `true`, `false`, `TRUE` and `FALSE` as booleans, and numbers
+        /// as numbers, rather than strings.
+        ///
+        /// Numbers with leading zeros, like `007`, stay strings.
+        #[arg(long)]
+        infer_types: bool,
+        /// Pin the type of a column, as `column=type`, where type is
+        /// `string`, `boolean` or `number`. May be given multiple times.
+        #[arg(long = "type", value_parser = csv2json::parse_type)]
+        types: Vec<(String, csv2json::Type)>,
+        /// Write a dotted header, like `Limits.MaxParams`, as a key in a nested
+        /// object.
+        #[arg(long)]
+        nested: bool,
+        /// Add a column of this name, with the path each record came from.
+        #[arg(long, value_name = "NAME")]
+        add_source_column: Option<String>,
+        /// Fail on the first record which doesn't match this schema, which is
+        /// `select` for the table read by `openrpc select`, or the path to a
+        /// JSON Schema for each record.
+        #[arg(long)]
+        schema: Option<csv2json::Schema>,
+        /// With `--schema select`, the pattern each `Method` must match,
+        /// where `*` matches anything.
+        #[arg(long, requires = "schema")]
+        method_pattern: Option<String>,
+        /// What to do with a record which can't be read or converted.
+        #[arg(long, value_enum, default_value_t)]
+        on_error: OnError,
+    },
+    /// Interpret `input` as a JSON array of objects, and print a
+    /// `delimiter`-separated series of lines, with a header.
+    ///
+    /// The reverse of `csv2json`.
+    Json2Csv {
+        #[arg(default_value = "-")]
+        input: PathBuf,
         #[arg(short, long, default_value_t = Char(AsciiChar::Tab))]
         delimiter: Char,
+        /// Write only these columns, in this order, rather than every key in
+        /// the order it first appears.
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+        /// Write nested objects and arrays as dotted keys, like
+        /// `Limits.MaxParams`, rather than failing.
+        #[arg(long)]
+        flatten: bool,
+    },
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Print a completion script for `shell`.
+    Completions { shell: clap_complete::Shell },
+    /// Write a manpage for every subcommand to `output_dir`.
+    Man {
+        #[arg(long)]
+        output_dir: PathBuf,
     },
 }
 
-/// Subommands related to processing OpenRPC documents.
 #[derive(Parser)]
 enum Openrpc {
     /// Print the following to stderr:
     /// - duplicate method names
     /// - duplicate parameter names
     /// - bad optional parameters
+    /// - dead $refs to component schemas
+    /// - broken $refs in methods, which are then checked as placeholders
+    /// - links to missing methods, and bad runtime expressions in links
     ///
     /// Does not validate anything else, including:
     /// - that example pairings match schemas
     /// - that Example::value and Example::externalValue are mutually exclusive
-    /// - dead $refs, or JSON Schema $refs
-    /// - links, runtime expressions
+    /// - JSON Schema $refs
     /// - component keys are idents
     /// - error codes are unique
-    ReportErrors { path: PathBuf },
+    ///
+    /// Several documents are checked in parallel, and their findings are
+    /// printed by document, then by rule, each prefixed with its document.
+    ///
+    /// Fails if there are any errors.
+    ReportErrors {
+        #[arg(required = true)]
+        paths: Vec<SpecSource>,
+        /// Keep the findings for each method in this directory, and only
+        /// recheck methods which have changed since.
+        #[arg(long)]
+        findings_cache: Option<PathBuf>,
+        /// Ignore `--findings-cache`.
+        #[arg(long)]
+        no_cache: bool,
+    },
     /// Print a summary of semantic differences between the `left` and `right`
     /// OpenRPC schemas.
-    Diff { left: PathBuf, right: PathBuf },
+    ///
+    /// Methods in both whose links differ are listed under `links`.
+    Diff {
+        left: SpecSource,
+        right: SpecSource,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
     /// Interpret `select` as a table of methods to include in `openrpc`, outputting
     /// a new schema with only the selected methods.
+    ///
+    /// Links on the selected methods are kept, with a warning for each which
+    /// points at a method that wasn't selected.
     Select {
-        openrpc: PathBuf,
+        openrpc: SpecSource,
         select: PathBuf,
         /// Specify a new title for the schema
         #[arg(long)]
@@ -55,79 +309,774 @@ enum Openrpc {
         /// Specify a new version for the schema
         #[arg(long)]
         overwrite_version: Option<String>,
+        /// Prepended to each method name in `select`.
+        #[arg(long, default_value = "Filecoin.")]
+        prefix: String,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print a table of the methods in `spec`, sorted by name, which `select`
+    /// will accept.
+    ///
+    /// Every method is marked for inclusion, so selecting a document with its
+    /// own table is a no-op.
+    Table {
+        spec: SpecSource,
+        #[arg(long, value_enum, default_value_t)]
+        format: TableFormat,
+        #[arg(short, long, default_value_t = Char(AsciiChar::Tab))]
+        delimiter: Char,
+        /// Stripped from the start of each method name.
+        #[arg(long, default_value = "Filecoin.")]
+        prefix: String,
+    },
+    /// Render `spec` as a directory of Markdown files, with component schemas
+    /// in a shared `types.md`.
+    ///
+    /// Fails on broken references rather than rendering empty sections.
+    Docs {
+        spec: SpecSource,
+        /// The directory to write to, created if it doesn't exist.
+        #[arg(long)]
+        output: PathBuf,
+        #[arg(long, value_enum, default_value_t)]
+        split: docs::Split,
+    },
+    #[command(subcommand)]
+    Codegen(Codegen),
+    /// Print a fragment mapping the name of each method in `spec` without
+    /// examples to a generated example pairing, for editing before merging.
+    ///
+    /// Values are derived from the schemas, and are deterministic.
+    ScaffoldExamples {
+        spec: SpecSource,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Insert the example pairings in `fragment`, a map of method names to
+    /// lists of pairings, into the corresponding methods in `spec`, printing
+    /// the new document.
+    ///
+    /// Pairings which don't validate against their method are rejected, and
+    /// methods which aren't in `spec` are skipped, with both reported to stderr.
+    MergeExamples {
+        spec: SpecSource,
+        fragment: PathBuf,
+        #[arg(long, value_enum, default_value_t)]
+        policy: merge_examples::Policy,
+        /// Merge pairings even if they fail validation.
+        #[arg(long)]
+        allow_invalid: bool,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print `spec` in a canonical form, with methods sorted by name, object
+    /// keys sorted, and empty or default-valued fields removed.
+    Normalize {
+        spec: SpecSource,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Don't print anything, but fail if `spec` isn't already normalized.
+        #[arg(long, conflicts_with = "output")]
+        check: bool,
+    },
+    /// Apply `overlay`, a list of operations addressed by method name or
+    /// component schema key, to `spec`, printing the new document.
+    ///
+    /// Fails if the overlay introduces any of the errors in `report-errors`.
+    /// Applying an overlay is idempotent.
+    Apply {
+        spec: SpecSource,
+        overlay: PathBuf,
+        /// Warn about operations whose targets are missing, rather than failing.
+        #[arg(long)]
+        lenient: bool,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Set `info.version` in `new` to the version of `old`, bumped according
+    /// to the changes between them, record the changes, and print the new
+    /// version.
+    Release {
+        old: SpecSource,
+        new: SpecSource,
+        #[arg(long, value_enum, default_value_t)]
+        bump: release::Bump,
+        /// Append the changes to this Markdown file, rather than to the
+        /// `x-changelog` extension of the document.
+        #[arg(long)]
+        changelog: Option<PathBuf>,
+        /// Release even if there are no semantic changes.
+        #[arg(long)]
+        allow_empty: bool,
+        /// Allow a `patch` bump for breaking changes.
+        #[arg(long)]
+        force: bool,
+        /// Write the document to this file, rather than back to `new`.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Print a graph of which methods and component schemas in `spec`
+    /// reference which component schemas.
+    Graph {
+        spec: SpecSource,
+        #[arg(long, value_enum, default_value_t)]
+        format: graph::Format,
+        /// Print a graph of methods instead, with an edge between methods
+        /// weighted by the number of schemas they both reference.
+        #[arg(long, value_enum)]
+        project: Option<Projection>,
+        /// Only include this method, and the schemas it transitively references.
+        #[arg(long)]
+        focus: Option<String>,
+    },
+    /// Print the names of the methods in `spec` which match every condition.
+    Query {
+        spec: SpecSource,
+        #[command(flatten)]
+        conditions: query::Conditions,
+        /// Print the matching methods as a JSON array, rather than their names.
+        #[arg(long, value_enum, default_value_t)]
+        format: QueryFormat,
+    },
+    /// Write the methods in `spec` to a document per group, each with only the
+    /// component schemas it needs, and a `manifest.json` listing them.
+    ///
+    /// `bundle` joins the documents back together.
+    Split {
+        spec: SpecSource,
+        #[arg(long, value_enum, default_value_t)]
+        by: split::By,
+        /// The directory to write to, created if it doesn't exist.
+        #[arg(long)]
+        output_dir: PathBuf,
+        /// Copy schemas needed by several groups into each of them, rather
+        /// than moving them into a shared `common.json`.
+        #[arg(long)]
+        duplicate_shared: bool,
+    },
+    /// Print a single, self-contained document.
+    ///
+    /// If `spec` is a manifest written by `split`, join its parts.
+    Bundle {
+        spec: SpecSource,
+        /// Replace every `$ref` with its target, removing `components`.
+        #[arg(long, conflicts_with = "dereference_depth")]
+        inline_all: bool,
+        /// With `--inline-all`, allow a schema to be inlined this many times
+        /// within itself before replacing it with an `x-cycle-truncated`
+        /// schema, rather than failing on cycles.
+        #[arg(long, requires = "inline_all")]
+        max_cycles: Option<usize>,
+        /// Only replace `$ref`s nested within fewer than this many other
+        /// replaced `$ref`s.
+        #[arg(long)]
+        dereference_depth: Option<usize>,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Write a JSON Schema for the JSON-RPC request and response envelopes of
+    /// each method in `spec`, as `<method>.request.schema.json` and
+    /// `<method>.response.schema.json`.
+    Envelopes {
+        spec: SpecSource,
+        /// The directory to write to, created if it doesn't exist.
+        #[arg(long)]
+        output_dir: PathBuf,
+    },
+    /// Apply mechanical fixes to `spec`, printing the new document, and a
+    /// report of what changed, by rule, to stderr.
+    Fix {
+        spec: SpecSource,
+        /// Only apply these rules. Defaults to all of them.
+        #[arg(long = "rule", value_enum)]
+        rules: Vec<fix::Rule>,
+        /// Print the report to stdout, without printing the document.
+        #[arg(long, conflicts_with = "output")]
+        dry_run: bool,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print summary numbers for `spec`: method, example, deprecation and
+    /// component schema counts, schema sizes and nesting depth.
+    Stats {
+        spec: SpecSource,
+        #[arg(long, value_enum, default_value_t)]
+        format: StatsFormat,
+        /// Print the changes in each number from `spec` to this document instead.
+        #[arg(long)]
+        compare: Option<SpecSource>,
+    },
+    /// Print random JSON-RPC requests for methods in `spec`, as
+    /// newline-delimited JSON, with sequential ids.
+    ///
+    /// Params satisfy their schemas, and are the same for a given `--seed`.
+    GenVectors {
+        spec: SpecSource,
+        /// Only generate requests for methods matching this pattern, where `*`
+        /// matches anything. May be given multiple times.
+        #[arg(long = "method")]
+        methods: Vec<String>,
+        /// How many requests to generate for each method.
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Instead generate requests which each violate a single constraint,
+        /// as `{"request": .., "violates": {"param", "pointer", "constraint"}}`.
+        ///
+        /// Methods with nothing to violate are skipped.
+        #[arg(long)]
+        invalid: bool,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the deprecated methods and params in `spec`, what replaces them,
+    /// and the first version of `--history` they were deprecated in.
+    ///
+    /// Replacements which are deprecated or missing are flagged, as are
+    /// methods removed since the newest historical document without first
+    /// being deprecated.
+    Deprecations {
+        spec: SpecSource,
+        /// Earlier versions of `spec`, oldest first.
+        #[arg(long, num_args = 1..)]
+        history: Vec<SpecSource>,
+        #[arg(long, value_enum, default_value_t)]
+        format: DeprecationsFormat,
+    },
+    /// Compare the component schemas in `spec` with Go struct definitions,
+    /// printing properties missing on either side, incompatible types, and
+    /// `required` disagreeing with `omitempty`.
+    ///
+    /// See [`check_go`] for the format of `--go-types`.
+    CheckGo {
+        spec: SpecSource,
+        #[arg(long)]
+        go_types: PathBuf,
+    },
+    /// Browse the methods and component schemas of `spec` in the terminal.
+    Browse { spec: SpecSource },
+    /// Sign `spec`, adding the signature under an `x-integrity` extension on
+    /// `info`.
+    ///
+    /// The canonical form is signed, so `normalize` doesn't break the seal.
+    Seal {
+        spec: SpecSource,
+        /// An ed25519 private key, as PKCS#8 PEM.
+        #[arg(long)]
+        key: PathBuf,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Check that `spec` is unchanged since it was sealed by `--pubkey`.
+    VerifySeal {
+        spec: SpecSource,
+        /// An ed25519 public key, as PEM.
+        #[arg(long)]
+        pubkey: PathBuf,
+    },
+    /// Print a shell command which calls each method in `spec`, using the
+    /// params of its first example, or placeholders if it has none.
+    Snippets {
+        spec: SpecSource,
+        /// Only print methods matching this pattern, where `*` matches
+        /// anything. May be given multiple times.
+        #[arg(long = "method")]
+        methods: Vec<String>,
+        #[arg(long, value_enum, default_value_t)]
+        format: snippets::Format,
+        /// The shell to quote for.
+        #[arg(long, value_enum, default_value_t)]
+        shell: snippets::Shell,
+        /// The JSON-RPC endpoint to call.
+        #[arg(long, default_value = "http://localhost:1234/rpc/v1")]
+        remote: String,
+        /// Sent as a bearer token in an `Authorization` header.
+        #[arg(long)]
+        auth_token: Option<String>,
+        /// Split commands over several lines, and pretty-print the request.
+        #[arg(long)]
+        pretty: bool,
+    },
+    /// Print the commits at which a method changed semantically, newest
+    /// first, by diffing the document at each commit touching `spec`.
+    ///
+    /// Commits at which `spec` can't be loaded are skipped, with a note on
+    /// stderr.
+    Blame {
+        /// A file in a git repository.
+        spec: PathBuf,
+        /// May be given multiple times.
+        #[arg(long = "method", required_unless_present = "all_methods")]
+        methods: Vec<String>,
+        /// Instead print a tab-separated table of when each method last
+        /// changed.
+        #[arg(long, conflicts_with = "methods")]
+        all_methods: bool,
+    },
+    /// Combine several documents, unioning their methods and components.
+    ///
+    /// Everything else comes from the first document.
+    Merge {
+        #[arg(required = true, num_args = 2..)]
+        specs: Vec<SpecSource>,
+        /// How to resolve a method or component which differs between
+        /// documents.
+        #[arg(long, value_enum, default_value_t)]
+        strategy: merge::Strategy,
+        /// Write a JSON report of every conflict to this file.
+        #[arg(long)]
+        conflicts: Option<PathBuf>,
+        /// Specify a new title for the merged document.
+        #[arg(long)]
+        overwrite_title: Option<String>,
+        /// Specify a new version for the merged document.
+        #[arg(long)]
+        overwrite_version: Option<String>,
+        /// Take `servers` from the document at this (zero-based) position
+        /// instead.
+        #[arg(long)]
+        servers_from: Option<usize>,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Remove methods, extensions, servers and example values from `spec`
+    /// according to `--rules`, then prune unused schemas.
+    ///
+    /// See [`redact::Rules`] for the format of `--rules`.
+    Redact {
+        spec: SpecSource,
+        #[arg(long)]
+        rules: PathBuf,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 }
 
-fn main() -> anyhow::Result<()> {
-    let openrpc = match Args::parse() {
-        Args::Openrpc(subcommand) => subcommand,
+/// Inspect the defaults from `tool.toml`.
+///
+/// See the `--config` flag for where it is looked for.
+#[derive(Parser)]
+enum ConfigCommand {
+    /// Print the value of each flag with a default, and whether it came from
+    /// the tool or the config file.
+    ///
+    /// If `args` are given, like `config show openrpc select a.json b.json`,
+    /// print the values for that invocation instead, including those from the
+    /// command line.
+    Show {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+/// Generate client code from an OpenRPC document.
+#[derive(Parser)]
+enum Codegen {
+    /// Emit a Rust module with a type per component schema, and an `async fn`
+    /// per method on a `Client` which is generic over a `Transport`.
+    ///
+    /// Schemas which can't be mapped fall back to `serde_json::Value`, and are
+    /// summarized on stderr.
+    Rust {
+        spec: SpecSource,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Emit TypeScript definitions with a type per component schema, and a
+    /// `Methods` interface mapping each method name to its params and result.
+    Typescript {
+        spec: SpecSource,
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// See [`exit`] for the exit codes.
+fn main() -> ExitCode {
+    let error_format = early_value("--error-format")
+        .and_then(|it| exit::ErrorFormat::from_str(&it, true).ok())
+        .unwrap_or_default();
+    let Err(e) = run(error_format) else {
+        return ExitCode::SUCCESS;
+    };
+    exit::report(&e, error_format)
+}
+
+/// The value of the flag `long` in the command line, before it is parsed
+/// properly.
+fn early_value(long: &str) -> Option<String> {
+    let mut args = std::env::args();
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix(long) {
+            Some("") => return args.next(),
+            Some(it) => {
+                if let Some(it) = it.strip_prefix('=') {
+                    return Some(it.to_owned());
+                }
+            }
+            None if arg == "--" => break,
+            None => {}
+        }
+    }
+    None
+}
+
+fn run(error_format: exit::ErrorFormat) -> anyhow::Result<()> {
+    let config = config::locate(early_value("--config").map(PathBuf::from))?
+        .map(config::load)
+        .transpose()?;
+    let mut command = Args::command()
+        .arg(
+            clap::Arg::new("config")
+                .long("config")
+                .global(true)
+                .value_name("PATH")
+                .help(format!(
+                    "Take flag defaults from this file, rather than ./{} or \
+                     $XDG_CONFIG_HOME/filecoin-openrpc-tool/config.toml",
+                    config::FILE_NAME
+                )),
+        )
+        .arg(
+            clap::Arg::new("error_format")
+                .long("error-format")
+                .global(true)
+                .value_parser(clap::value_parser!(exit::ErrorFormat))
+                .help("How to print errors to stderr"),
+        )
+        .arg(
+            clap::Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .global(true)
+                .action(clap::ArgAction::Count)
+                .help("Log decisions to stderr, or more with -vv (see also RUST_LOG)"),
+        )
+        .arg(
+            clap::Arg::new("no_progress")
+                .long("no-progress")
+                .global(true)
+                .action(clap::ArgAction::SetTrue)
+                .help("Don't show progress bars, which are only shown on a terminal anyway"),
+        );
+    if let Some(config) = &config {
+        command = config::apply(command, config)?
+    }
+    let matches = match command.clone().try_get_matches() {
+        Ok(it) => it,
+        Err(e) if e.use_stderr() && error_format == exit::ErrorFormat::Json => return Err(e.into()),
+        Err(e) => e.exit(),
+    };
+    // `RUST_LOG` takes precedence over `-v`.
+    let filter = match matches.get_count("verbose") {
+        0 => "off",
+        1 => "tool=debug",
+        _ => "tool=trace",
+    };
+    progress::init(!matches.get_flag("no_progress"));
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| filter.into()))
+        .with_writer(|| progress::Writer)
+        .with_ansi(io::stderr().is_terminal())
+        .init();
+    check_stdin(&mut command, &matches)?;
+    let (fetch, openrpc) = match Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit()) {
+        Args::Openrpc { fetch, command } => (fetch, command),
+        Args::Coverage {
+            fetch,
+            spec,
+            traffic,
+            format,
+            fail_under,
+        } => {
+            let document = load_resolved(&spec, &fetch)?;
+            let mut report = coverage::Report::new(&document);
+            for path in traffic {
+                report.add(&document, &path)?
+            }
+            match format {
+                StatsFormat::Text => print!("{}", report.render()),
+                StatsFormat::Json => serde_json::to_writer_pretty(io::stdout(), &report)?,
+            }
+            if let Some(threshold) = fail_under {
+                if report.percent() < threshold {
+                    bail!(exit::Findings(format!(
+                        "{:.1}% of methods were called, which is under {}%",
+                        report.percent(),
+                        threshold
+                    )))
+                }
+            }
+            return Ok(());
+        }
+        Args::VerifyExamples {
+            fetch,
+            spec,
+            remote,
+            methods,
+            skip_file,
+            ignore,
+        } => {
+            let skip = match skip_file {
+                Some(path) => String::from_utf8(
+                    source::read(&SpecSource::from_path(&path), &FetchOptions::default())?.bytes,
+                )?
+                .lines()
+                .map(str::trim)
+                .filter(|it| !it.is_empty() && !it.starts_with('#'))
+                .map(str::to_owned)
+                .collect(),
+                None => vec![],
+            };
+            let document = load_resolved(&spec, &fetch)?;
+            let outcomes = verify::verify(
+                &document,
+                &verify::Options {
+                    remote: &remote,
+                    headers: &fetch.headers,
+                    methods: &methods,
+                    skip: &skip,
+                    ignore: &ignore,
+                },
+            )?;
+            for outcome in &outcomes {
+                println!("{}", outcome)
+            }
+            let failed = outcomes.iter().filter(|it| !it.verdict.is_ok()).count();
+            if failed != 0 {
+                bail!(exit::Findings(format!(
+                    "{} of {} examples didn't verify",
+                    failed,
+                    outcomes.len()
+                )))
+            }
+            return Ok(());
+        }
+        Args::Conformance {
+            fetch,
+            spec,
+            remote,
+            cases,
+            parallel,
+            format,
+            output,
+        } => {
+            let document = load_resolved(&spec, &fetch)?;
+            let cases = load_json::<Vec<conformance::Case>>(cases)?;
+            conformance::load(&document, &cases)?;
+            let outcomes = conformance::run(&document, &cases, &remote, &fetch.headers, parallel)?;
+            write_output(output.as_deref(), |it| {
+                conformance::report(&outcomes, format, it)
+            })?;
+            let failed = outcomes.iter().filter(|it| !it.failures.is_empty()).count();
+            if failed != 0 {
+                bail!(exit::Findings(format!(
+                    "{} of {} cases failed",
+                    failed,
+                    outcomes.len()
+                )))
+            }
+            return Ok(());
+        }
+        Args::Mock {
+            fetch,
+            spec,
+            listen,
+            seed,
+            strict,
+        } => {
+            let document = load_resolved(&spec, &fetch)?;
+            mock::serve(mock::Mock::new(&document, seed, strict)?, &listen)?;
+            return Ok(());
+        }
+        Args::Config(ConfigCommand::Show { args }) => {
+            let matches = match args.is_empty() {
+                true => None,
+                false => Some(command.clone().try_get_matches_from(
+                    [command.get_name().to_owned()].into_iter().chain(args),
+                )?),
+            };
+            print!(
+                "{}",
+                config::show(&command, config.as_ref(), matches.as_ref())
+            );
+            return Ok(());
+        }
+        Args::Completions { shell } => {
+            let mut command = Args::command();
+            let name = command.get_name().to_owned();
+            clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+            return Ok(());
+        }
+        Args::Man { output_dir } => {
+            fs::create_dir_all(&output_dir)
+                .with_context(|| format!("couldn't create directory {}", output_dir.display()))?;
+            let command = Args::command();
+            let name = command.get_name().to_owned();
+            manpages(command, name, &output_dir)?;
+            return Ok(());
+        }
         Args::Csv2Json {
-            delimiter: Char(delimiter),
+            inputs,
+            delimiter,
+            quote: Char(quote),
+            no_quoting,
+            trim,
+            compact,
+            ndjson,
+            infer_types,
+            types,
+            nested,
+            add_source_column,
+            schema,
+            method_pattern,
+            on_error,
         } => {
-            let mut records = csv::ReaderBuilder::new()
-                .delimiter(delimiter.as_byte())
-                .from_reader(io::stdin())
-                .deserialize::<BTreeMap<String, String>>()
-                .collect::<Result<Vec<_>, _>>()?;
-            for record in &mut records {
-                record.retain(|_k, v| !v.is_empty())
+            let inputs = match inputs.is_empty() {
+                true => vec![PathBuf::from("-")],
+                false => inputs,
+            };
+            let (converter, inputs) = csv2json::open(
+                &inputs,
+                &csv2json::Dialect {
+                    delimiter,
+                    quote: quote.as_byte(),
+                    quoting: !no_quoting,
+                    trim,
+                },
+                csv2json::Options {
+                    infer_types,
+                    types,
+                    nested,
+                    source_column: add_source_column,
+                    validator: schema
+                        .map(|it| csv2json::Validator::new(&it, method_pattern))
+                        .transpose()?,
+                },
+            )?;
+            for column in converter.unknown_columns() {
+                eprintln!("column {} isn't in the schema, so would be ignored", column)
+            }
+            let mut skipped = vec![];
+            let records = inputs
+                .into_iter()
+                .flat_map(|input| input.convert(&converter))
+                .filter_map(|record| match record {
+                    Err(e) if on_error == OnError::Skip && e.is::<csv2json::RowError>() => {
+                        skipped.push(e);
+                        None
+                    }
+                    other => Some(other),
+                });
+            let mut stdout = io::BufWriter::new(io::stdout().lock());
+            match (compact, ndjson) {
+                (_, true) => {
+                    for record in records {
+                        serde_json::to_writer(&mut stdout, &record?)?;
+                        writeln!(stdout)?;
+                    }
+                }
+                (true, false) => write_array(serde_json::Serializer::new(&mut stdout), records)?,
+                (false, false) => {
+                    write_array(serde_json::Serializer::pretty(&mut stdout), records)?
+                }
+            }
+            stdout.flush()?;
+            if !skipped.is_empty() {
+                eprintln!(
+                    "skipped {} records which couldn't be converted:\n{}",
+                    skipped.len(),
+                    skipped.iter().join("\n")
+                )
             }
-            serde_json::to_writer_pretty(io::stdout(), &records)?;
+            return Ok(());
+        }
+        Args::Json2Csv {
+            input,
+            delimiter: Char(delimiter),
+            columns,
+            flatten,
+        } => {
+            json2csv::write(
+                load_json(input)?,
+                &json2csv::Options {
+                    delimiter: delimiter.as_byte(),
+                    columns,
+                    flatten,
+                },
+                io::stdout().lock(),
+            )?;
             return Ok(());
         }
     };
     match openrpc {
-        Openrpc::ReportErrors { path } => {
-            let document = load_json::<OpenRPC>(path)?;
-            let methods = resolve_within(document)?.methods;
-            if let Ok(dups) = nunny::Vec::new(
-                methods
-                    .iter()
-                    .map(|it| it.name.as_str())
-                    .duplicates()
-                    .collect(),
-            ) {
-                eprintln!(
-                    "the following method names are duplicated: {}",
-                    dups.join(", ")
-                )
-            };
-
-            for method in &methods {
-                if let Ok(dups) = nunny::Vec::new(
-                    method
-                        .params
-                        .iter()
-                        .map(|it| it.name.as_str())
-                        .duplicates()
-                        .collect(),
-                ) {
-                    eprintln!(
-                        "the following parameter names on method {} are duplicated: {}",
-                        method.name,
-                        dups.join(", ")
-                    )
-                }
-                if let Some((ix, name)) = method.params.iter().enumerate().find_map(|(ix, it)| {
-                    (!it.required.unwrap_or_default()).then_some((ix, it.name.as_str()))
-                }) {
-                    if let Ok(after) = nunny::Vec::new(
-                        method.params[ix..]
-                            .iter()
-                            .filter(|it| it.required.unwrap_or_default())
-                            .map(|it| it.name.as_str())
-                            .collect(),
-                    ) {
-                        eprintln!("the following required parameters on method {} follow the optional parameter {}: {}", method.name, name, after.join(", "))
+        Openrpc::ReportErrors {
+            paths,
+            findings_cache,
+            no_cache,
+        } => {
+            let cache = findings_cache.filter(|_| !no_cache);
+            let mut documents = paths
+                .par_iter()
+                .map(|path| {
+                    let findings = report_errors_document(path, &fetch, cache.as_deref())?;
+                    anyhow::Ok((path.to_string(), findings))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            documents.sort_by(|(left, _), (right, _)| left.cmp(right));
+            let mut count = 0;
+            for (path, findings) in &documents {
+                for Finding { message, .. } in findings {
+                    match paths.len() {
+                        1 => eprintln!("{}", message),
+                        _ => eprintln!("{}: {}", path, message),
                     }
                 }
+                count += findings.len()
+            }
+            if count != 0 {
+                bail!(exit::Findings(match paths.as_slice() {
+                    [path] => format!("{} has {} errors", path, count),
+                    _ => format!("{} documents have {} errors", paths.len(), count),
+                }))
             }
-
             Ok(())
         }
-        Openrpc::Diff { left, right } => {
-            let summary = openrpc_diff::diff(load_json(left)?, load_json(right)?)?;
-            serde_json::to_writer_pretty(io::stdout(), &summary)?;
+        Openrpc::Diff {
+            left,
+            right,
+            output,
+        } => {
+            // Only the methods which have changed are deserialized.
+            let (left_bytes, right_bytes) =
+                (source::read(&left, &fetch)?, source::read(&right, &fetch)?);
+            let left = openrpc_diff::Lazy::parse(&left_bytes.bytes)
+                .with_context(|| format!("couldn't parse json from {}", left))?;
+            let right = openrpc_diff::Lazy::parse(&right_bytes.bytes)
+                .with_context(|| format!("couldn't parse json from {}", right))?;
+            left_bytes.cache()?;
+            right_bytes.cache()?;
+            let summary = openrpc_diff::diff_changed(
+                &left,
+                &right,
+                fetch.max_ref_depth.unwrap_or(chains::DEFAULT_MAX_DEPTH),
+            )?;
+            write_json(output.as_deref(), &summary)?;
             Ok(())
         }
         Openrpc::Select {
@@ -135,56 +1084,1054 @@ fn main() -> anyhow::Result<()> {
             select,
             overwrite_title,
             overwrite_version,
+            prefix,
+            output,
         } => {
-            let mut openrpc = resolve_within(load_json(openrpc)?)?;
-            let select = load_json::<Vec<Select>>(select)?
+            let (openrpc, json) = load_document_and_json(&openrpc, &fetch)?;
+            let mut openrpc = resolve_within(openrpc)?;
+            self::select(&mut openrpc, load_json(select)?, &prefix)?;
+            if let Some(title) = overwrite_title {
+                openrpc.info.title = title
+            }
+            if let Some(version) = overwrite_version {
+                openrpc.info.version = version
+            }
+            let mut openrpc = serde_json::to_value(openrpc)?;
+            for warning in links::carry(&json, &mut openrpc) {
+                eprintln!("{}", warning)
+            }
+            write_json(output.as_deref(), &openrpc)?;
+            Ok(())
+        }
+        Openrpc::Table {
+            spec,
+            format,
+            delimiter: Char(delimiter),
+            prefix,
+        } => {
+            let rows = load_resolved(&spec, &fetch)?
+                .methods
                 .into_iter()
-                .filter(|it| matches!(it.include, Some(InclusionDirective::Include)))
-                // formatting the name like this is a hack
-                .map(|it| (format!("Filecoin.{}", it.method), it.description))
-                .collect::<BTreeMap<_, _>>();
-            openrpc.methods.retain_mut(|it| match select.get(&it.name) {
-                Some(new_description) => {
-                    if new_description.is_some() && it.description.is_none() {
-                        it.description.clone_from(new_description)
+                .map(|it| TableRow {
+                    method: match it.name.strip_prefix(&prefix) {
+                        Some(stripped) => stripped.to_owned(),
+                        None => it.name.clone(),
+                    },
+                    description: it
+                        .summary
+                        .or_else(|| it.description?.lines().next().map(str::to_owned)),
+                    include: InclusionDirective::Include,
+                    deprecated: it.deprecated.unwrap_or_default(),
+                    tags: it
+                        .tags
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|it| it.name)
+                        .join(","),
+                    has_examples: it.examples.is_some_and(|it| !it.is_empty()),
+                })
+                .sorted_by(|l, r| l.method.cmp(&r.method))
+                .collect::<Vec<_>>();
+            match format {
+                TableFormat::Csv => {
+                    let mut writer = csv::WriterBuilder::new()
+                        .delimiter(delimiter.as_byte())
+                        .from_writer(io::stdout());
+                    for row in rows {
+                        writer.serialize(row)?
                     }
-                    true
-                }
-                None => false,
-            });
-            gc::prune_schemas(&mut openrpc)?;
-            if let Ok(missed) = nunny::Vec::new(
-                select
-                    .keys()
-                    .collect::<BTreeSet<_>>()
-                    .difference(&openrpc.methods.iter().map(|it| &it.name).collect())
+                    writer.flush()?
+                }
+                TableFormat::Json => serde_json::to_writer_pretty(io::stdout(), &rows)?,
+            }
+            Ok(())
+        }
+        Openrpc::Docs {
+            spec,
+            output,
+            split,
+        } => {
+            let files = docs::render(&load_resolved(&spec, &fetch)?, split)?;
+            fs::create_dir_all(&output)
+                .with_context(|| format!("couldn't create directory {}", output.display()))?;
+            for (name, content) in files {
+                let path = output.join(name);
+                fs::write(&path, content)
+                    .with_context(|| format!("couldn't write to file {}", path.display()))?;
+            }
+            Ok(())
+        }
+        Openrpc::Codegen(Codegen::Rust { spec, output }) => {
+            let codegen::rust::Generated { code, fallbacks } =
+                codegen::rust::generate(&load_resolved(&spec, &fetch)?)?;
+            write_output(output.as_deref(), |it| Ok(it.write_all(code.as_bytes())?))?;
+            if let Ok(fallbacks) = nunny::Vec::new(fallbacks) {
+                eprintln!(
+                    "the following schemas fell back to serde_json::Value:\n{}",
+                    fallbacks.join("\n")
+                )
+            }
+            Ok(())
+        }
+        Openrpc::Codegen(Codegen::Typescript { spec, output }) => {
+            let code = codegen::typescript::generate(&load_resolved(&spec, &fetch)?)?;
+            write_output(output.as_deref(), |it| Ok(it.write_all(code.as_bytes())?))?;
+            Ok(())
+        }
+        Openrpc::ScaffoldExamples { spec, output } => {
+            let fragment = scaffold::examples(&load_resolved(&spec, &fetch)?);
+            write_json(output.as_deref(), &fragment)?;
+            Ok(())
+        }
+        Openrpc::MergeExamples {
+            spec,
+            fragment,
+            policy,
+            allow_invalid,
+            output,
+        } => {
+            let mut document = load_document(&spec, &fetch)?;
+            let merge_examples::Report { missing, invalid } =
+                merge_examples::merge(&mut document, load_json(fragment)?, policy, allow_invalid)?;
+            if let Ok(missing) = nunny::Vec::new(missing) {
+                eprintln!(
+                    "the following methods were not present: {}",
+                    missing.join(", ")
+                )
+            }
+            if let Ok(invalid) = nunny::Vec::new(invalid) {
+                eprintln!(
+                    "the following example pairings are invalid{}:\n{}",
+                    if allow_invalid {
+                        ""
+                    } else {
+                        ", and were skipped"
+                    },
+                    invalid.join("\n")
+                )
+            }
+            write_json(output.as_deref(), &document)?;
+            Ok(())
+        }
+        Openrpc::Normalize {
+            spec,
+            output,
+            check,
+        } => {
+            let bytes = source::read(&spec, &fetch)?;
+            let document = source::parse_document(&spec, &bytes.bytes, &fetch)?;
+            bytes.cache()?;
+            let normalized = normalize::to_string(&normalize::normalize(document));
+            if check {
+                if bytes.bytes != normalized.as_bytes() {
+                    bail!(exit::Findings(format!("{} is not normalized", spec)))
+                }
+                return Ok(());
+            }
+            write_output(output.as_deref(), |it| {
+                Ok(it.write_all(normalized.as_bytes())?)
+            })
+        }
+        Openrpc::Apply {
+            spec,
+            overlay,
+            lenient,
+            output,
+        } => {
+            let mut document = load_document::<OpenRPC>(&spec, &fetch)?;
+            let before = report_errors(&resolve_within(document.clone())?.methods);
+            if let Ok(missing) = nunny::Vec::new(apply::apply(&mut document, load_json(overlay)?)) {
+                match lenient {
+                    true => eprintln!(
+                        "the following operations couldn't be applied: {}",
+                        missing.join(", ")
+                    ),
+                    false => bail!(
+                        "the following operations couldn't be applied: {}",
+                        missing.join(", ")
+                    ),
+                }
+            }
+            if let Ok(errors) = nunny::Vec::new(
+                report_errors(&resolve_within(document.clone())?.methods)
+                    .into_iter()
+                    .filter(|it| !before.contains(it))
                     .collect(),
             ) {
-                eprintln!(
-                    "the following selected methods were not present: {}",
-                    missed.iter().join(", ")
+                bail!(exit::Findings(format!(
+                    "the overlay introduced the following errors:\n{}",
+                    errors.join("\n")
+                )))
+            }
+            write_json(output.as_deref(), &document)?;
+            Ok(())
+        }
+        Openrpc::Release {
+            old,
+            new,
+            bump,
+            changelog,
+            allow_empty,
+            force,
+            output,
+        } => {
+            let Some(output) = output.or(match &new {
+                SpecSource::Path(it) => Some(it.clone()),
+                _ => None,
+            }) else {
+                bail!(Args::command().error(
+                    clap::error::ErrorKind::MissingRequiredArgument,
+                    format!("--output is required when {} isn't a file", new)
+                ))
+            };
+            let old = load_document::<OpenRPC>(&old, &fetch)?;
+            let mut document = load_document::<OpenRPC>(&new, &fetch)?;
+            let summary = openrpc_diff::diff(old.clone(), document.clone())?;
+            if release::is_empty(&summary) && !allow_empty {
+                bail!("there are no semantic changes, pass --allow-empty to release anyway")
+            }
+            let level = release::level(&summary, bump);
+            if level == release::Bump::Patch && release::is_breaking(&summary) && !force {
+                bail!("refusing a patch bump for breaking changes, pass --force to override")
+            }
+            let version = release::bump(&old.info.version, level)?;
+            let changes = release::changes(&summary);
+            match changelog {
+                Some(path) => release::append(&path, &release::markdown(&version, &changes))?,
+                None => match document
+                    .extensions
+                    .0
+                    .entry(String::from("x-changelog"))
+                    .or_insert_with(|| serde_json::Value::Array(vec![]))
+                {
+                    serde_json::Value::Array(it) => it.push(serde_json::json!({
+                        "version": version,
+                        "changes": changes,
+                    })),
+                    _ => bail!("the existing x-changelog extension isn't an array"),
+                },
+            }
+            document.info.version.clone_from(&version);
+            write_json(Some(&output), &document)?;
+            println!("{}", version);
+            Ok(())
+        }
+        Openrpc::Graph {
+            spec,
+            format,
+            project,
+            focus,
+        } => {
+            let mut graph = graph::graph(&load_resolved(&spec, &fetch)?)?;
+            if let Some(method) = focus {
+                graph = match graph.focus(&method) {
+                    Some(it) => it,
+                    None => bail!(Args::command().error(
+                        clap::error::ErrorKind::InvalidValue,
+                        format!("no method named {} in {}", method, spec)
+                    )),
+                }
+            }
+            match (project, format) {
+                (None, graph::Format::Dot) => print!("{}", graph.dot()),
+                (None, graph::Format::Json) => {
+                    serde_json::to_writer_pretty(io::stdout(), &graph.json())?
+                }
+                (Some(Projection::Methods), format) => {
+                    let edges = graph.project_methods();
+                    let methods = graph.methods.keys().map(String::as_str).collect::<Vec<_>>();
+                    match format {
+                        graph::Format::Dot => print!("{}", graph::projected_dot(&edges, &methods)),
+                        graph::Format::Json => serde_json::to_writer_pretty(
+                            io::stdout(),
+                            &graph::projected_json(&edges, &methods),
+                        )?,
+                    }
+                }
+            }
+            Ok(())
+        }
+        Openrpc::Query {
+            spec,
+            conditions,
+            format,
+        } => {
+            let document = load_resolved(&spec, &fetch)?;
+            let methods = query::query(&document, &conditions)?;
+            match format {
+                QueryFormat::Names => {
+                    for method in methods {
+                        println!("{}", method.name)
+                    }
+                }
+                QueryFormat::Json => serde_json::to_writer_pretty(io::stdout(), &methods)?,
+            }
+            Ok(())
+        }
+        Openrpc::Split {
+            spec,
+            by,
+            output_dir,
+            duplicate_shared,
+        } => {
+            let (manifest, files) =
+                split::split(load_resolved(&spec, &fetch)?, by, duplicate_shared)?;
+            fs::create_dir_all(&output_dir)
+                .with_context(|| format!("couldn't create directory {}", output_dir.display()))?;
+            for (name, document) in files {
+                let path = output_dir.join(name);
+                write_json(Some(&path), &document)?
+            }
+            let path = output_dir.join(split::MANIFEST);
+            write_json(Some(&path), &manifest)?;
+            Ok(())
+        }
+        Openrpc::Bundle {
+            spec,
+            inline_all,
+            max_cycles,
+            dereference_depth,
+            output,
+        } => {
+            let value = load_document::<serde_json::Value>(&spec, &fetch)?;
+            let document = match value.get("parts") {
+                Some(_) => {
+                    let manifest = serde_json::from_value::<split::Manifest>(value)
+                        .with_context(|| format!("couldn't parse manifest from {}", spec))?;
+                    let dir = match &spec {
+                        SpecSource::Path(it) => it.parent().map(Path::to_owned).unwrap_or_default(),
+                        _ => PathBuf::new(),
+                    };
+                    split::join(&manifest, |file| load_json(dir.join(file)))?
+                }
+                None => serde_path_to_error::deserialize(value)
+                    .with_context(|| format!("couldn't parse json from {}", spec))?,
+            };
+            let limit = match (inline_all, dereference_depth) {
+                (true, _) => Some(inline::Limit::All { cycles: max_cycles }),
+                (false, Some(depth)) => Some(inline::Limit::Depth(depth)),
+                (false, None) => None,
+            };
+            let document = match limit {
+                Some(limit) => {
+                    let mut document = resolve_within(document)?;
+                    inline::inline(&mut document, limit)?;
+                    serde_json::to_value(document)?
+                }
+                None => serde_json::to_value(document)?,
+            };
+            write_json(output.as_deref(), &document)?;
+            Ok(())
+        }
+        Openrpc::Envelopes { spec, output_dir } => {
+            let all = envelopes::envelopes(&load_resolved(&spec, &fetch)?)?;
+            fs::create_dir_all(&output_dir)
+                .with_context(|| format!("couldn't create directory {}", output_dir.display()))?;
+            for (method, envelopes::Envelopes { request, response }) in all {
+                for (suffix, schema) in [("request", request), ("response", response)] {
+                    let path = output_dir.join(format!("{}.{}.schema.json", method, suffix));
+                    write_json(Some(&path), &schema)?
+                }
+            }
+            Ok(())
+        }
+        Openrpc::Fix {
+            spec,
+            mut rules,
+            dry_run,
+            output,
+        } => {
+            if rules.is_empty() {
+                rules = fix::Rule::value_variants().to_vec()
+            }
+            let mut document = load_document(&spec, &fetch)?;
+            let report = fix::fix(&mut document, &rules)?
+                .into_iter()
+                .flat_map(|(rule, changes)| {
+                    changes
+                        .into_iter()
+                        .map(move |it| format!("{}: {}", rule.id(), it))
+                })
+                .collect::<Vec<_>>();
+            if dry_run {
+                for line in report {
+                    println!("{}", line)
+                }
+                return Ok(());
+            }
+            if let Ok(report) = nunny::Vec::new(report) {
+                eprintln!("the following fixes were applied:\n{}", report.join("\n"))
+            }
+            write_json(output.as_deref(), &document)?;
+            Ok(())
+        }
+        Openrpc::Stats {
+            spec,
+            format,
+            compare,
+        } => {
+            let before = load_resolved(&spec, &fetch)?;
+            let after = match compare {
+                Some(it) => Some(load_resolved(&it, &fetch)?),
+                None => None,
+            };
+            match (format, after) {
+                (StatsFormat::Text, None) => print!("{}", stats::render(&stats::stats(&before))),
+                (StatsFormat::Json, None) => {
+                    serde_json::to_writer_pretty(io::stdout(), &stats::stats(&before))?
+                }
+                (StatsFormat::Text, Some(after)) => {
+                    let names = |it: &resolved::OpenRPC| {
+                        it.methods
+                            .iter()
+                            .map(|it| it.name.clone())
+                            .collect::<BTreeSet<_>>()
+                    };
+                    let (before_names, after_names) = (names(&before), names(&after));
+                    if let Ok(removed) =
+                        nunny::Vec::new(before_names.difference(&after_names).cloned().collect())
+                    {
+                        println!("removed methods: {}", removed.join(", "))
+                    }
+                    if let Ok(added) =
+                        nunny::Vec::new(after_names.difference(&before_names).cloned().collect())
+                    {
+                        println!("added methods: {}", added.join(", "))
+                    }
+                    print!(
+                        "{}",
+                        stats::render_delta(&stats::stats(&before), &stats::stats(&after))
+                    )
+                }
+                (StatsFormat::Json, Some(after)) => serde_json::to_writer_pretty(
+                    io::stdout(),
+                    &serde_json::json!({
+                        "before": stats::stats(&before),
+                        "after": stats::stats(&after),
+                    }),
+                )?,
+            }
+            Ok(())
+        }
+        Openrpc::Seal { spec, key, output } => {
+            use ed25519_dalek::pkcs8::DecodePrivateKey as _;
+            let key = ed25519_dalek::SigningKey::read_pkcs8_pem_file(&key)
+                .map_err(|it| anyhow::anyhow!("{}", it))
+                .with_context(|| format!("couldn't load private key {}", key.display()))?;
+            let mut document = load_document::<OpenRPC>(&spec, &fetch)?;
+            seal::seal(&mut document, &key);
+            write_json(output.as_deref(), &document)?;
+            Ok(())
+        }
+        Openrpc::VerifySeal { spec, pubkey } => {
+            use ed25519_dalek::pkcs8::DecodePublicKey as _;
+            let key = ed25519_dalek::VerifyingKey::read_public_key_pem_file(&pubkey)
+                .map_err(|it| anyhow::anyhow!("{}", it))
+                .with_context(|| format!("couldn't load public key {}", pubkey.display()))?;
+            seal::verify(load_document(&spec, &fetch)?, &key)
+        }
+        Openrpc::Snippets {
+            spec,
+            methods,
+            format,
+            shell,
+            remote,
+            auth_token,
+            pretty,
+        } => {
+            let document = load_resolved(&spec, &fetch)?;
+            let schemas = document
+                .components
+                .as_ref()
+                .and_then(|it| it.schemas.clone())
+                .unwrap_or_default();
+            let options = snippets::Options {
+                format,
+                shell,
+                remote: &remote,
+                auth_token: auth_token.as_deref(),
+                pretty,
+            };
+            for method in document.methods.iter().filter(|method| {
+                methods.is_empty() || methods.iter().any(|it| glob::matches(it, &method.name))
+            }) {
+                println!("# {}", method.name);
+                println!(
+                    "{}\n",
+                    snippets::snippet(&snippets::request(method, &schemas), &options)
                 )
             }
+            Ok(())
+        }
+        Openrpc::Blame {
+            spec,
+            methods,
+            all_methods,
+        } => {
+            let blame = blame::blame(&spec, (!all_methods).then_some(&methods[..]))?;
+            for it in &blame.skipped {
+                eprintln!("skipped {}", it)
+            }
+            match all_methods {
+                true => {
+                    for (method, events) in &blame.events {
+                        if let Some(blame::Event { revision, summary }) = events.last() {
+                            println!(
+                                "{}\t{}\t{}\t{}",
+                                method, revision.date, revision.short, summary
+                            )
+                        }
+                    }
+                }
+                false => {
+                    for method in &methods {
+                        let events = blame.events.get(method).map_or(&[][..], Vec::as_slice);
+                        if events.is_empty() {
+                            eprintln!("{} isn't in any revision of {}", method, spec.display())
+                        }
+                        for blame::Event { revision, summary } in events.iter().rev() {
+                            println!(
+                                "{} {} {} {}: {}",
+                                method, revision.date, revision.short, revision.subject, summary
+                            )
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+        Openrpc::Merge {
+            specs,
+            strategy,
+            conflicts,
+            overwrite_title,
+            overwrite_version,
+            servers_from,
+            output,
+        } => {
+            let documents = specs
+                .iter()
+                .map(|it| Ok((it.to_string(), load_document::<OpenRPC>(it, &fetch)?)))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let servers = match servers_from {
+                Some(ix) => match documents.get(ix) {
+                    Some((_, it)) => Some(it.servers.clone()),
+                    None => bail!(Args::command().error(
+                        clap::error::ErrorKind::InvalidValue,
+                        format!(
+                            "--servers-from {} is out of range for {} documents",
+                            ix,
+                            documents.len()
+                        )
+                    )),
+                },
+                None => None,
+            };
+            let before = documents
+                .iter()
+                .map(|(_, it)| Ok(report_errors(&resolve_within(it.clone())?.methods)))
+                .collect::<anyhow::Result<Vec<_>>>()?
+                .concat();
+            let (mut merged, report) = merge::merge(documents, strategy, &mut |conflict| {
+                eprintln!("{}", merge::describe(conflict));
+                loop {
+                    eprint!("keep the [f]irst or [l]ast, or [a]bort? ");
+                    let mut line = String::new();
+                    if io::stdin().read_line(&mut line)? == 0 {
+                        bail!("aborted")
+                    }
+                    match line.trim() {
+                        "f" => return Ok(merge::Side::First),
+                        "l" => return Ok(merge::Side::Last),
+                        "a" => bail!("aborted"),
+                        _ => continue,
+                    }
+                }
+            })?;
+            if let Some(path) = conflicts {
+                write_json(Some(&path), &report)?
+            }
+            if strategy == merge::Strategy::Error && !report.is_empty() {
+                bail!(exit::Findings(format!(
+                    "the documents conflict:\n{}",
+                    report.iter().map(merge::describe).join("\n")
+                )))
+            }
             if let Some(title) = overwrite_title {
-                openrpc.info.title = title
+                merged.info.title = title
             }
             if let Some(version) = overwrite_version {
-                openrpc.info.version = version
+                merged.info.version = version
+            }
+            if let Some(servers) = servers {
+                merged.servers = servers
+            }
+            if let Ok(errors) = nunny::Vec::new(
+                report_errors(&resolve_within(merged.clone())?.methods)
+                    .into_iter()
+                    .filter(|it| !before.contains(it))
+                    .collect(),
+            ) {
+                bail!(exit::Findings(format!(
+                    "the merged document has the following errors:\n{}",
+                    errors.join("\n")
+                )))
+            }
+            write_json(output.as_deref(), &merged)?;
+            Ok(())
+        }
+        Openrpc::Redact {
+            spec,
+            rules,
+            output,
+        } => {
+            let mut document = load_resolved(&spec, &fetch)?;
+            let before = report_errors(&document.methods);
+            let dropped = redact::redact(&mut document, &load_json(rules)?)?;
+            if let Ok(it) = nunny::Vec::new(dropped) {
+                eprintln!("the following methods were redacted: {}", it.join(", "))
+            }
+            if let Ok(errors) = nunny::Vec::new(
+                report_errors(&document.methods)
+                    .into_iter()
+                    .filter(|it| !before.contains(it))
+                    .collect(),
+            ) {
+                bail!(exit::Findings(format!(
+                    "redaction introduced the following errors:\n{}",
+                    errors.join("\n")
+                )))
+            }
+            write_json(output.as_deref(), &document)?;
+            Ok(())
+        }
+        Openrpc::Browse { spec } => browse::browse(&load_resolved(&spec, &fetch)?),
+        Openrpc::CheckGo { spec, go_types } => {
+            let document = load_resolved(&spec, &fetch)?;
+            let mismatches = check_go::check(&document, &load_json(go_types)?);
+            for it in &mismatches {
+                println!("{}", it)
+            }
+            if !mismatches.is_empty() {
+                bail!(exit::Findings(format!(
+                    "found {} mismatches",
+                    mismatches.len()
+                )))
+            }
+            Ok(())
+        }
+        Openrpc::Deprecations {
+            spec,
+            history,
+            format,
+        } => {
+            let document = load_resolved(&spec, &fetch)?;
+            let history = history
+                .iter()
+                .map(|it| load_resolved(it, &fetch))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let report = deprecations::report(&document, &history);
+            if let Ok(it) = nunny::Vec::new(report.removed_without_deprecation.clone()) {
+                eprintln!(
+                    "the following methods were removed without being deprecated: {}",
+                    it.join(", ")
+                )
+            }
+            match format {
+                DeprecationsFormat::Markdown => print!("{}", deprecations::markdown(&report)),
+                DeprecationsFormat::Json => serde_json::to_writer_pretty(io::stdout(), &report)?,
             }
-            serde_json::to_writer_pretty(io::stdout(), &openrpc)?;
             Ok(())
         }
+        Openrpc::GenVectors {
+            spec,
+            methods,
+            count,
+            seed,
+            invalid,
+            output,
+        } => {
+            let document = load_resolved(&spec, &fetch)?;
+            let vectors =
+                vectors::Generator::new(&document, seed)?.generate(&methods, count, invalid)?;
+            write_output(output.as_deref(), |it| {
+                for vector in vectors {
+                    serde_json::to_writer(&mut *it, &vector)?;
+                    writeln!(it)?
+                }
+                Ok(())
+            })
+        }
     }
 }
 
-fn load_json<T: DeserializeOwned>(path: impl AsRef<Path>) -> anyhow::Result<T> {
-    fn imp<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
-        Ok(serde_path_to_error::deserialize(
-            &mut serde_json::Deserializer::from_reader(File::open(path)?),
-        )?)
+/// Keep the methods in `openrpc` which a row of `rows` includes, and the
+/// schemas they need.
+fn select(
+    openrpc: &mut resolved::OpenRPC,
+    rows: Vec<Select>,
+    prefix: &str,
+) -> Result<(), BrokenReference> {
+    let select = rows
+        .into_iter()
+        .enumerate()
+        .filter(|(_, it)| matches!(it.include, Some(InclusionDirective::Include)))
+        .map(|(row, it)| (format!("{}{}", prefix, it.method), (row, it.description)))
+        .collect::<BTreeMap<_, _>>();
+    openrpc.methods.retain_mut(|it| match select.get(&it.name) {
+        Some((row, new_description)) => {
+            debug!(method = it.name, row, "retained");
+            if new_description.is_some() && it.description.is_none() {
+                it.description.clone_from(new_description)
+            }
+            true
+        }
+        None => {
+            debug!(method = it.name, "dropped, no row includes it");
+            false
+        }
+    });
+    gc::prune_schemas(openrpc)?;
+    if let Ok(missed) = nunny::Vec::new(
+        select
+            .keys()
+            .collect::<BTreeSet<_>>()
+            .difference(&openrpc.methods.iter().map(|it| &it.name).collect())
+            .collect(),
+    ) {
+        eprintln!(
+            "the following selected methods were not present: {}",
+            missed.iter().join(", ")
+        )
+    }
+    Ok(())
+}
+
+/// The problems described in [`Openrpc::ReportErrors`].
+fn report_errors(methods: &[resolved::Method]) -> Vec<String> {
+    let timings = &mut Timings::default();
+    let mut errors = duplicate_methods(methods);
+    errors.extend(
+        methods
+            .iter()
+            .flat_map(|it| method_errors(it, None, timings))
+            .map(|it| it.message),
+    );
+    errors
+}
+
+/// The findings of [`Openrpc::ReportErrors`] for the document at `path`,
+/// sorted by rule.
+fn report_errors_document(
+    path: &SpecSource,
+    fetch: &FetchOptions,
+    findings_cache: Option<&Path>,
+) -> anyhow::Result<Vec<Finding>> {
+    let (document, json) = load_document_and_json::<OpenRPC>(path, fetch)?;
+    let mut timings = Timings::default();
+    let traced = timings.time("resolve", || provenance::resolve_within_traced(document))?;
+    let mut findings = traced
+        .issues
+        .iter()
+        .map(|it| Finding::new("resolve", it))
+        .collect::<Vec<_>>();
+    findings.extend(match findings_cache {
+        Some(dir) => report_errors_cached(&traced, dir, &cache_file(path), &mut timings)?,
+        None => report_errors_traced(&traced, &mut timings),
+    });
+    findings.extend(timings.check("dead-references", || gc::dead_references(&traced.document)));
+    findings.extend(timings.check("links", || links::check(&json)));
+    timings.log(path);
+    // stable, so each rule's findings stay in document order
+    findings.sort_by(|left, right| left.rule.cmp(&right.rule));
+    Ok(findings)
+}
+
+/// A problem found by a rule of [`Openrpc::ReportErrors`].
+#[derive(Clone, Serialize, Deserialize)]
+struct Finding {
+    rule: String,
+    message: String,
+}
+
+impl Finding {
+    fn new(rule: &str, message: impl ToString) -> Self {
+        Self {
+            rule: rule.to_owned(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// As [`report_errors`], saying which params came from components.
+fn report_errors_traced(traced: &provenance::Traced, timings: &mut Timings) -> Vec<Finding> {
+    let mut findings = timings.check("duplicate-methods", || {
+        duplicate_methods(&traced.document.methods)
+    });
+    findings.extend(
+        traced
+            .methods()
+            .flat_map(|(method, origins)| method_errors(method, Some(origins), timings)),
+    );
+    findings
+}
+
+/// How long each rule of [`Openrpc::ReportErrors`] took in total, logged
+/// with `-v`.
+#[derive(Default)]
+struct Timings(BTreeMap<&'static str, Duration>);
+
+impl Timings {
+    fn time<T>(&mut self, rule: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let it = f();
+        *self.0.entry(rule).or_default() += start.elapsed();
+        it
+    }
+    /// Run `rule`, labelling what it finds.
+    fn check<I>(&mut self, rule: &'static str, f: impl FnOnce() -> I) -> Vec<Finding>
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        let found = self.time(rule, f);
+        found.into_iter().map(|it| Finding::new(rule, it)).collect()
+    }
+    fn log(&self, path: &SpecSource) {
+        for (rule, elapsed) in &self.0 {
+            debug!(%path, rule, ?elapsed, "checked")
+        }
+    }
+}
+
+/// Bump when [`method_errors`] changes, to invalidate findings caches.
+const RULES_VERSION: u32 = 3;
+
+#[derive(Default, Serialize, Deserialize)]
+struct FindingsCache {
+    version: String,
+    /// Keyed by the hash of each method.
+    methods: BTreeMap<String, Vec<Finding>>,
+}
+
+/// The findings cache for `path`, which is only shared with the same
+/// document.
+fn cache_file(path: &SpecSource) -> String {
+    let hash = Sha256::digest(path.to_string());
+    format!("report-errors-{}.json", hex::encode(&hash[..8]))
+}
+
+/// As [`report_errors_traced`], reusing the findings in `dir/file` for
+/// methods which haven't changed.
+///
+/// The per-method checks only look at the method itself and its origins, so
+/// hashing those is enough.
+/// Checks across methods are always rerun.
+fn report_errors_cached(
+    traced: &provenance::Traced,
+    dir: &Path,
+    file: &str,
+    timings: &mut Timings,
+) -> anyhow::Result<Vec<Finding>> {
+    let path = dir.join(file);
+    let version = format!("{}+{}", env!("CARGO_PKG_VERSION"), RULES_VERSION);
+    let cached = fs::read(&path)
+        .ok()
+        .and_then(|it| serde_json::from_slice::<FindingsCache>(&it).ok())
+        .filter(|it| it.version == version)
+        .unwrap_or_default();
+    let mut findings = timings.check("duplicate-methods", || {
+        duplicate_methods(&traced.document.methods)
+    });
+    let mut fresh = FindingsCache {
+        version,
+        methods: BTreeMap::new(),
+    };
+    for (method, origins) in traced.methods() {
+        let key = hex::encode(Sha256::digest(serde_json::to_vec(&(method, origins))?));
+        let found = match cached.methods.get(&key) {
+            Some(it) => it.clone(),
+            None => method_errors(method, Some(origins), timings),
+        };
+        findings.extend(found.iter().cloned());
+        fresh.methods.insert(key, found);
+    }
+    fs::create_dir_all(dir)
+        .and_then(|()| fs::write(&path, serde_json::to_vec(&fresh)?))
+        .with_context(|| format!("couldn't write findings cache {}", path.display()))?;
+    Ok(findings)
+}
+
+fn duplicate_methods(methods: &[resolved::Method]) -> Vec<String> {
+    match nunny::Vec::new(
+        methods
+            .iter()
+            .map(|it| it.name.as_str())
+            .duplicates()
+            .collect(),
+    ) {
+        Ok(dups) => vec![format!(
+            "the following method names are duplicated: {}",
+            dups.join(", ")
+        )],
+        Err(_) => vec![],
+    }
+}
+
+/// With `origins`, params from components are named with their `$ref`.
+fn method_errors(
+    method: &resolved::Method,
+    origins: Option<&provenance::MethodOrigins>,
+    timings: &mut Timings,
+) -> Vec<Finding> {
+    let param = |ix: usize| {
+        provenance::label(
+            &method.params[ix].name,
+            origins.and_then(|it| it.params.get(ix)),
+        )
+    };
+    let mut errors = vec![];
+    errors.extend(timings.check("duplicate-params", || {
+        let duplicated = method
+            .params
+            .iter()
+            .map(|it| it.name.as_str())
+            .duplicates()
+            .collect::<Vec<_>>();
+        let dups = nunny::Vec::new(
+            (0..method.params.len())
+                .filter(|ix| duplicated.contains(&method.params[*ix].name.as_str()))
+                .map(param)
+                .unique()
+                .collect(),
+        )
+        .ok()?;
+        Some(format!(
+            "the following parameter names on method {} are duplicated: {}",
+            method.name,
+            dups.join(", ")
+        ))
+    }));
+    errors.extend(timings.check("required-after-optional", || {
+        let ix = method
+            .params
+            .iter()
+            .position(|it| !it.required.unwrap_or_default())?;
+        let after = nunny::Vec::new(
+            (ix..method.params.len())
+                .filter(|ix| method.params[*ix].required.unwrap_or_default())
+                .map(param)
+                .collect(),
+        )
+        .ok()?;
+        Some(format!(
+            "the following required parameters on method {} follow the optional parameter {}: {}",
+            method.name,
+            param(ix),
+            after.join(", ")
+        ))
+    }));
+    errors
+}
+
+/// Write `<name>.1` for `command`, and likewise for each subcommand, named
+/// like `tool-openrpc-diff.1`.
+fn manpages(command: clap::Command, name: String, dir: &Path) -> anyhow::Result<()> {
+    let mut command = command.name(name.clone());
+    command.build();
+    for sub in command
+        .get_subcommands()
+        .filter(|it| it.get_name() != "help")
+    {
+        manpages(sub.clone(), format!("{}-{}", name, sub.get_name()), dir)?
+    }
+    let mut roff = vec![];
+    clap_mangen::Man::new(command).render(&mut roff)?;
+    let path = dir.join(format!("{}.1", name));
+    fs::write(&path, roff).with_context(|| format!("couldn't write to file {}", path.display()))
+}
+
+/// Arguments which are written to, where `-` is stdout.
+const OUTPUTS: &[&str] = &["output", "conflicts"];
+
+/// Any input may be `-` for stdin, but only one may be, since it can only be
+/// read once.
+fn check_stdin(command: &mut clap::Command, matches: &clap::ArgMatches) -> Result<(), clap::Error> {
+    let mut stdin = vec![];
+    let mut matches = Some(matches);
+    while let Some(it) = matches {
+        for id in it.ids().filter(|id| !OUTPUTS.contains(&id.as_str())) {
+            let raw = it.try_get_raw(id.as_str()).ok().flatten().into_iter();
+            stdin.extend(raw.flatten().filter(|it| *it == "-").map(|_| id.as_str()));
+        }
+        matches = it.subcommand().map(|(_, it)| it)
+    }
+    match stdin.as_slice() {
+        [first, second, ..] => Err(command.error(
+            clap::error::ErrorKind::ArgumentConflict,
+            format!(
+                "both `{}` and `{}` read from stdin, but only one input may",
+                first, second
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Write to `output`, or stdout if it's [`None`] or `-`.
+///
+/// Files are written to a temporary file in the same directory, which is
+/// renamed over `output` on success, so a failure never leaves a truncated
+/// file.
+/// Inputs are read in full before anything is written, so `output` may be
+/// the same as an input.
+fn write_output(
+    output: Option<&Path>,
+    write: impl FnOnce(&mut dyn io::Write) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let Some(path) = output.filter(|it| *it != Path::new("-")) else {
+        let mut stdout = io::stdout().lock();
+        write(&mut stdout)?;
+        return Ok(stdout.flush()?);
+    };
+    let imp = || {
+        let dir = match path.parent() {
+            Some(it) if !it.as_os_str().is_empty() => it,
+            _ => Path::new("."),
+        };
+        let mut file = io::BufWriter::new(tempfile::NamedTempFile::new_in(dir)?);
+        write(&mut file)?;
+        file.into_inner()?.persist(path)?;
+        anyhow::Ok(())
+    };
+    imp().with_context(|| format!("couldn't write to file {}", path.display()))
+}
+
+/// Write `value` as pretty JSON to `output`, as [`write_output`].
+fn write_json(output: Option<&Path>, value: &impl Serialize) -> anyhow::Result<()> {
+    write_output(output, |it| Ok(serde_json::to_writer_pretty(it, value)?))
+}
+
+/// Serialize `items` as a JSON array, one at a time.
+fn write_array<W: io::Write, F: serde_json::ser::Formatter, T: Serialize>(
+    mut serializer: serde_json::Serializer<W, F>,
+    items: impl IntoIterator<Item = anyhow::Result<T>>,
+) -> anyhow::Result<()> {
+    use serde::ser::{SerializeSeq as _, Serializer as _};
+    let mut seq = serializer.serialize_seq(None)?;
+    for it in items {
+        seq.serialize_element(&it?)?
     }
-    imp::<T>(path.as_ref())
-        .with_context(|| format!("couldn't load json from file {}", path.as_ref().display()))
+    seq.end()?;
+    Ok(())
+}
+
+/// Load JSON from the file at `path`, where `-` is stdin.
+fn load_json<T: DeserializeOwned>(path: impl AsRef<Path>) -> anyhow::Result<T> {
+    load_document(
+        &SpecSource::from_path(path.as_ref()),
+        &FetchOptions::default(),
+    )
 }
 
 #[derive(Serialize, Deserialize)]
@@ -195,6 +2142,62 @@ struct Select {
     method: String,
 }
 
+/// A superset of [`Select`].
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct TableRow {
+    method: String,
+    description: Option<String>,
+    include: InclusionDirective,
+    deprecated: bool,
+    tags: String,
+    has_examples: bool,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum OnError {
+    /// Stop at the first bad record.
+    #[default]
+    Fail,
+    /// Leave bad records out, and list them on stderr at the end.
+    Skip,
+}
+
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+enum TableFormat {
+    /// Delimited by `--delimiter`, with a header.
+    #[default]
+    Csv,
+    Json,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Projection {
+    Methods,
+}
+
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+enum QueryFormat {
+    /// One per line.
+    #[default]
+    Names,
+    Json,
+}
+
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+enum DeprecationsFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+enum StatsFormat {
+    #[default]
+    Text,
+    Json,
+}
+
 #[derive(Serialize, Deserialize)]
 enum InclusionDirective {
     Discussion,
@@ -205,6 +2208,8 @@ enum InclusionDirective {
 use ascii::AsciiChar;
 use std::{fmt, str::FromStr};
 
+/// May be written with the escapes that [`csv2json::unescape`] accepts, like
+/// `\t`.
 #[derive(Clone)]
 struct Char(AsciiChar);
 
@@ -212,12 +2217,122 @@ impl FromStr for Char {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(AsciiChar::from_ascii(char::from_str(s)?)?))
+        let unescaped = csv2json::unescape(s)?;
+        match unescaped.chars().exactly_one().ok().map(AsciiChar::from_ascii) {
+            Some(Ok(it)) => Ok(Self(it)),
+            _ => bail!(
+                "`{}` isn't a single ASCII character, {}",
+                s,
+                csv2json::ACCEPTED
+            ),
+        }
     }
 }
 
 impl fmt::Display for Char {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+        f.write_str(&csv2json::escape(&self.0.as_char().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Resolving is stable, and selecting every method loses nothing,
+    /// including extensions.
+    #[test]
+    fn spec_survives_resolve_and_select() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let mut paths = vec![root.join("spec.json")];
+        for entry in fs::read_dir(root.join("schemas")).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|it| it == "json") {
+                paths.push(path)
+            }
+        }
+        for path in paths {
+            let json = serde_json::from_str::<Value>(&fs::read_to_string(&path).unwrap()).unwrap();
+            let spec = serde_json::from_value::<OpenRPC>(json.clone()).unwrap();
+            let resolved = serde_json::to_value(resolve_within(spec.clone()).unwrap()).unwrap();
+            let again = resolve_within(serde_json::from_value(resolved.clone()).unwrap()).unwrap();
+            assert_eq!(
+                serde_json::to_value(again).unwrap(),
+                resolved,
+                "{}",
+                path.display()
+            );
+
+            let mut selected = resolve_within(spec.clone()).unwrap();
+            let rows = selected
+                .methods
+                .iter()
+                .map(|it| Select {
+                    description: None,
+                    include: Some(InclusionDirective::Include),
+                    method: it.name.clone(),
+                })
+                .collect();
+            select(&mut selected, rows, "").unwrap();
+            let selected = serde_json::to_value(selected).unwrap();
+            assert_eq!(
+                extension_keys(&selected),
+                extension_keys(&json),
+                "{}",
+                path.display()
+            );
+            let summary =
+                openrpc_diff::diff(spec, serde_json::from_value(selected).unwrap()).unwrap();
+            assert!(release::is_empty(&summary), "{}", path.display());
+        }
+    }
+
+    fn extension_keys(value: &Value) -> BTreeSet<&str> {
+        match value {
+            Value::Object(it) => it
+                .iter()
+                .flat_map(|(key, value)| {
+                    let mut keys = extension_keys(value);
+                    if key.starts_with("x-") {
+                        keys.insert(key.as_str());
+                    }
+                    keys
+                })
+                .collect(),
+            Value::Array(it) => it.iter().flat_map(extension_keys).collect(),
+            _ => BTreeSet::new(),
+        }
+    }
+
+    /// Every subcommand can be completed, and has a man page.
+    #[test]
+    fn completions_and_manpages() {
+        let command = Args::command();
+        let name = command.get_name().to_owned();
+        for shell in clap_complete::Shell::value_variants() {
+            let mut out = vec![];
+            clap_complete::generate(*shell, &mut command.clone(), &name, &mut out);
+            let out = String::from_utf8(out).unwrap();
+            for sub in command.get_subcommands() {
+                assert!(out.contains(sub.get_name()), "{} {}", shell, sub.get_name())
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        manpages(command.clone(), name.clone(), dir.path()).unwrap();
+        fn check(command: &clap::Command, name: String, dir: &Path) {
+            let path = dir.join(format!("{}.1", name));
+            let roff = fs::read_to_string(&path).unwrap();
+            assert!(roff.contains(".TH"), "{}", path.display());
+            for sub in command
+                .get_subcommands()
+                .filter(|it| it.get_name() != "help")
+            {
+                check(sub, format!("{}-{}", name, sub.get_name()), dir)
+            }
+        }
+        check(&command, name, dir.path());
     }
 }
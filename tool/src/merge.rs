@@ -0,0 +1,205 @@
+//! Combine several documents into one.
+//!
+//! Methods and each kind of component are unioned by name.
+//! When documents disagree on an entry, a [`Strategy`] decides which wins.
+
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+use openrpc_types::{Components, OpenRPC, ReferenceOr};
+use serde::Serialize;
+
+use crate::verify;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Strategy {
+    /// Fail, after reporting every conflict.
+    #[default]
+    Error,
+    /// Keep the entry from the earlier document.
+    PreferFirst,
+    /// Keep the entry from the later document.
+    PreferLast,
+    /// Print the differences and ask.
+    Interactive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Side {
+    First,
+    Last,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Conflict {
+    /// `method`, or the key within `components`, like `schemas`.
+    pub kind: &'static str,
+    pub key: String,
+    pub first: String,
+    pub last: String,
+    /// JSON pointers at which the entries differ.
+    pub differences: Vec<String>,
+    /// [`None`] with [`Strategy::Error`].
+    pub kept: Option<Side>,
+}
+
+/// Merge `documents`, which are named for reporting, in order.
+///
+/// Everything other than `methods` and `components` comes from the first
+/// document.
+/// `ask` is called for each conflict with [`Strategy::Interactive`].
+pub fn merge(
+    documents: Vec<(String, OpenRPC)>,
+    strategy: Strategy,
+    ask: &mut dyn FnMut(&Conflict) -> anyhow::Result<Side>,
+) -> anyhow::Result<(OpenRPC, Vec<Conflict>)> {
+    let mut merger = Merger {
+        strategy,
+        ask,
+        conflicts: vec![],
+    };
+    let mut documents = documents.into_iter();
+    let Some((first_name, first)) = documents.next() else {
+        bail!("there are no documents to merge")
+    };
+    let mut methods = BTreeMap::new();
+    let mut order = vec![];
+    let mut components = Components::default();
+    let mut components_from = BTreeMap::new();
+    for (name, document) in [(first_name, first.clone())].into_iter().chain(documents) {
+        for method in document.methods {
+            let key = match &method {
+                ReferenceOr::Reference(it) => it.clone(),
+                ReferenceOr::Item(it) => it.name.clone(),
+            };
+            if !methods.contains_key(&key) {
+                order.push(key.clone())
+            }
+            merger.entry(&mut methods, "method", key, &name, method)?
+        }
+        let Some(Components {
+            content_descriptors,
+            schemas,
+            examples,
+            errors,
+            example_pairing_objects,
+            tags,
+            extensions: _,
+        }) = document.components
+        else {
+            continue;
+        };
+        macro_rules! merge_components {
+            ($($field:ident: $kind:literal),* $(,)?) => {$(
+                for (key, it) in $field.into_iter().flatten() {
+                    merger.entry(
+                        components_from.entry($kind).or_insert_with(BTreeMap::new),
+                        $kind,
+                        key.clone(),
+                        &name,
+                        serde_json::to_value(&it)?,
+                    )?;
+                    let existing = components.$field.get_or_insert_with(BTreeMap::new);
+                    if components_from[$kind][&key].0 == name {
+                        existing.insert(key, it);
+                    }
+                }
+            )*};
+        }
+        merge_components! {
+            content_descriptors: "contentDescriptors",
+            schemas: "schemas",
+            examples: "examples",
+            errors: "errors",
+            example_pairing_objects: "examplePairingObjects",
+            tags: "tags",
+        }
+    }
+    if merger.strategy == Strategy::Error && !merger.conflicts.is_empty() {
+        return Ok((first, merger.conflicts));
+    }
+    let merged = OpenRPC {
+        methods: order
+            .into_iter()
+            .map(|it| methods.remove(&it).unwrap().1)
+            .collect(),
+        components: (components != Components::default()).then_some(components),
+        ..first
+    };
+    Ok((merged, merger.conflicts))
+}
+
+struct Merger<'a> {
+    strategy: Strategy,
+    ask: &'a mut dyn FnMut(&Conflict) -> anyhow::Result<Side>,
+    conflicts: Vec<Conflict>,
+}
+
+impl Merger<'_> {
+    /// Insert `value` from the document called `name` into `entries`, which
+    /// records which document each entry came from.
+    fn entry<T: Serialize + PartialEq>(
+        &mut self,
+        entries: &mut BTreeMap<String, (String, T)>,
+        kind: &'static str,
+        key: String,
+        name: &str,
+        value: T,
+    ) -> anyhow::Result<()> {
+        let Some((existing_name, existing)) = entries.get(&key) else {
+            entries.insert(key, (name.to_owned(), value));
+            return Ok(());
+        };
+        if *existing == value {
+            return Ok(());
+        }
+        let mut differences = vec![];
+        verify::compare(
+            &serde_json::to_value(existing)?,
+            &serde_json::to_value(&value)?,
+            &mut vec![],
+            &[],
+            &mut differences,
+        );
+        let mut conflict = Conflict {
+            kind,
+            key: key.clone(),
+            first: existing_name.clone(),
+            last: name.to_owned(),
+            differences,
+            kept: None,
+        };
+        conflict.kept = match self.strategy {
+            Strategy::Error => None,
+            Strategy::PreferFirst => Some(Side::First),
+            Strategy::PreferLast => Some(Side::Last),
+            Strategy::Interactive => Some((self.ask)(&conflict)?),
+        };
+        if conflict.kept == Some(Side::Last) {
+            entries.insert(key, (name.to_owned(), value));
+        }
+        self.conflicts.push(conflict);
+        Ok(())
+    }
+}
+
+/// A line describing `conflict`.
+pub fn describe(conflict: &Conflict) -> String {
+    let Conflict {
+        kind,
+        key,
+        first,
+        last,
+        differences,
+        kept: _,
+    } = conflict;
+    let differences = match differences.iter().all(String::is_empty) {
+        true => String::from("the whole entry"),
+        false => differences.join(", "),
+    };
+    format!(
+        "{} {} differs between {} and {} at {}",
+        kind, key, first, last, differences
+    )
+}
@@ -0,0 +1,181 @@
+//! Insert a fragment of example pairings into a document.
+
+use std::collections::BTreeMap;
+
+use jsonschema::JSONSchema;
+use openrpc_types::{resolved, ContentDescriptor, Example, ExamplePairing, OpenRPC, ReferenceOr};
+use schemars::schema::Schema;
+use serde_json::{json, Value};
+
+use crate::chains::{resolve_within, ResolveError};
+
+/// What to do when a method already has examples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Policy {
+    /// Add the new examples after the existing ones.
+    #[default]
+    Append,
+    /// Discard the existing examples.
+    Replace,
+    /// Leave methods with existing examples untouched.
+    FillMissing,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Methods in the fragment which aren't in the document.
+    pub missing: Vec<String>,
+    /// Pairings which failed validation, and why.
+    pub invalid: Vec<String>,
+}
+
+/// Insert each pairing in `fragment` into the method of the same name in
+/// `document`.
+///
+/// Pairings which don't validate against the method's params and result are
+/// skipped unless `allow_invalid` is set, and are always reported.
+pub fn merge(
+    document: &mut OpenRPC,
+    fragment: BTreeMap<String, Vec<resolved::ExamplePairing>>,
+    policy: Policy,
+    allow_invalid: bool,
+) -> Result<Report, ResolveError> {
+    let resolved = resolve_within(document.clone())?;
+    let components = json!({
+        "schemas": resolved
+            .components
+            .as_ref()
+            .and_then(|it| it.schemas.as_ref())
+            .cloned()
+            .unwrap_or_default()
+    });
+    let mut report = Report::default();
+
+    for (name, pairings) in fragment {
+        let Some((target, signature)) = document
+            .methods
+            .iter_mut()
+            .filter_map(|it| match it {
+                ReferenceOr::Reference(_) => None,
+                ReferenceOr::Item(it) => Some(it),
+            })
+            .find(|it| it.name == name)
+            .zip(resolved.methods.iter().find(|it| it.name == name))
+        else {
+            report.missing.push(name);
+            continue;
+        };
+        let mut accepted = vec![];
+        for pairing in pairings {
+            let errors = validate(&components, signature, &pairing);
+            let valid = errors.is_empty();
+            report.invalid.extend(
+                errors
+                    .into_iter()
+                    .map(|it| format!("{} example `{}`: {}", name, pairing.name, it)),
+            );
+            if valid || allow_invalid {
+                accepted.push(ReferenceOr::Item(unresolve(pairing)))
+            }
+        }
+        if accepted.is_empty() {
+            continue;
+        }
+        let existing = target.examples.get_or_insert_with(Vec::new);
+        match policy {
+            Policy::Append => existing.extend(accepted),
+            Policy::Replace => *existing = accepted,
+            Policy::FillMissing => {
+                if existing.is_empty() {
+                    *existing = accepted
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn validate(
+    components: &Value,
+    method: &resolved::Method,
+    pairing: &resolved::ExamplePairing,
+) -> Vec<String> {
+    let mut errors = vec![];
+    if pairing.params.len() > method.params.len() {
+        errors.push(format!(
+            "has {} params, but the method only has {}",
+            pairing.params.len(),
+            method.params.len()
+        ))
+    }
+    if let Some(missing) = method
+        .params
+        .iter()
+        .skip(pairing.params.len())
+        .find(|it| it.required.unwrap_or_default())
+    {
+        errors.push(format!("missing required param `{}`", missing.name))
+    }
+    let pairs = method
+        .params
+        .iter()
+        .zip(&pairing.params)
+        .map(|(descriptor, example)| (format!("param `{}`", descriptor.name), descriptor, example))
+        .chain(
+            method
+                .result
+                .iter()
+                .zip(&pairing.result)
+                .map(|(descriptor, example)| (String::from("result"), descriptor, example)),
+        );
+    for (what, ContentDescriptor { schema, .. }, Example { value, .. }) in pairs {
+        let Some(value) = value else {
+            continue;
+        };
+        match compile(schema, components) {
+            Ok(compiled) => {
+                if let Err(it) = compiled.validate(value) {
+                    errors.extend(it.map(|it| match it.instance_path.to_string().as_str() {
+                        "" => format!("{what}: {it}"),
+                        path => format!("{what} at {path}: {it}"),
+                    }))
+                }
+            }
+            Err(it) => errors.push(format!("{what} has an invalid schema: {it}")),
+        }
+    }
+    errors
+}
+
+/// Compile `schema` so that `$ref`s into `#/components/schemas` resolve.
+fn compile(schema: &Schema, components: &Value) -> Result<JSONSchema, String> {
+    let mut root = serde_json::to_value(schema).unwrap();
+    match &mut root {
+        Value::Object(it) => {
+            it.insert(String::from("components"), components.clone());
+        }
+        Value::Bool(true) => root = json!({ "components": components }),
+        _ => {}
+    }
+    JSONSchema::compile(&root).map_err(|it| it.to_string())
+}
+
+fn unresolve(
+    resolved::ExamplePairing {
+        name,
+        description,
+        summary,
+        params,
+        result,
+        extensions,
+    }: resolved::ExamplePairing,
+) -> ExamplePairing {
+    ExamplePairing {
+        name,
+        description,
+        summary,
+        params: params.into_iter().map(ReferenceOr::Item).collect(),
+        result: result.map(ReferenceOr::Item),
+        extensions,
+    }
+}
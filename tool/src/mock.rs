@@ -0,0 +1,466 @@
+//! A JSON-RPC server which answers from the examples in a document, so
+//! clients can be written without a synced node.
+//!
+//! Each request is validated against the method's [request
+//! envelope](crate::envelopes).
+//! The result is that of the first example pairing whose params equal the
+//! request's, or else of the first example pairing, or else is
+//! [generated](crate::vectors) from the result schema.
+//! Batches get an array of responses, and notifications get none.
+//!
+//! The server speaks just enough HTTP/1.1 for JSON-RPC clients: a `POST` per
+//! connection, with a `Content-Length` or chunked body of at most
+//! [`MAX_BODY`] bytes.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, BufReader, Read as _, Write as _},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use jsonschema::JSONSchema;
+use openrpc_types::resolved;
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::{vectors, verify};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// Larger request bodies get a `413`, rather than being read into memory.
+const MAX_BODY: usize = 16 * 1024 * 1024;
+const TOO_LARGE: &str = "413 Payload Too Large";
+
+pub struct Mock<'a> {
+    methods: BTreeMap<&'a str, Method<'a>>,
+    generator: vectors::Generator<'a>,
+    strict: bool,
+}
+
+struct Method<'a> {
+    method: &'a resolved::Method,
+    request: JSONSchema,
+    response: JSONSchema,
+}
+
+impl<'a> Mock<'a> {
+    /// With `strict`, methods without examples aren't served, rather than
+    /// generating results for them.
+    pub fn new(document: &'a resolved::OpenRPC, seed: u64, strict: bool) -> anyhow::Result<Self> {
+        let generator = vectors::Generator::new(document, seed)?;
+        let mut methods = BTreeMap::new();
+        for method in &document.methods {
+            let envelopes = &generator.envelopes()[&method.name];
+            methods.insert(
+                method.name.as_str(),
+                Method {
+                    method,
+                    request: verify::compile(&envelopes.request)?,
+                    response: verify::compile(&envelopes.response)?,
+                },
+            );
+        }
+        Ok(Self {
+            methods,
+            generator,
+            strict,
+        })
+    }
+
+    /// The response to the body of an HTTP request, or [`None`] if it only
+    /// has notifications.
+    pub fn respond(&mut self, body: &[u8]) -> Option<Value> {
+        match serde_json::from_slice::<Value>(body) {
+            Err(e) => Some(error(Value::Null, PARSE_ERROR, e.to_string())),
+            Ok(Value::Array(batch)) if batch.is_empty() => Some(error(
+                Value::Null,
+                INVALID_REQUEST,
+                String::from("the batch is empty"),
+            )),
+            Ok(Value::Array(batch)) => {
+                let responses = batch
+                    .iter()
+                    .filter_map(|it| self.one(it))
+                    .collect::<Vec<_>>();
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            Ok(request) => self.one(&request),
+        }
+    }
+
+    fn one(&mut self, request: &Value) -> Option<Value> {
+        let id = request.get("id").cloned();
+        let Some(name) = request.get("method").and_then(Value::as_str) else {
+            return Some(error(
+                id.unwrap_or_default(),
+                INVALID_REQUEST,
+                String::from("the request has no method"),
+            ));
+        };
+        // notifications aren't answered
+        let id = id?;
+        debug!(method = name, %id, "request");
+        let Some(method) = self.methods.get(name) else {
+            return Some(error(
+                id,
+                METHOD_NOT_FOUND,
+                format!("{} isn't in the document", name),
+            ));
+        };
+        let problems = verify::problems(&method.request, request);
+        if !problems.is_empty() {
+            let mut response = error(id, INVALID_PARAMS, String::from("invalid params"));
+            response["error"]["data"] = Value::from(problems);
+            return Some(response);
+        }
+        let params = request.get("params").cloned().unwrap_or(json!([]));
+        let result = match example(method.method, &params) {
+            Some(it) => it,
+            None if self.strict => {
+                return Some(error(
+                    id,
+                    METHOD_NOT_FOUND,
+                    format!("{} has no examples", name),
+                ))
+            }
+            None => match self.generator.result(method.method, &method.response) {
+                Ok(it) => it,
+                Err(e) => return Some(error(id, INTERNAL_ERROR, format!("{:#}", e))),
+            },
+        };
+        Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+}
+
+/// The result of the first example pairing of `method` whose params are
+/// `params`, by position or by name, or else of the first example pairing.
+fn example(method: &resolved::Method, params: &Value) -> Option<Value> {
+    let pairings = method.examples.as_deref().unwrap_or_default();
+    let pairing = pairings
+        .iter()
+        .find(|pairing| {
+            let values = pairing
+                .params
+                .iter()
+                .map(|it| it.value.clone().unwrap_or_default());
+            match params {
+                Value::Array(params) => values.eq(params.iter().cloned()),
+                Value::Object(params) => {
+                    params.len() == pairing.params.len()
+                        && method
+                            .params
+                            .iter()
+                            .zip(values)
+                            .all(|(param, value)| params.get(&param.name) == Some(&value))
+                }
+                _ => false,
+            }
+        })
+        .or(pairings.first())?;
+    Some(
+        pairing
+            .result
+            .as_ref()
+            .and_then(|it| it.value.clone())
+            .unwrap_or_default(),
+    )
+}
+
+fn error(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Answer requests on `listen` until the process is stopped.
+pub fn serve(mut mock: Mock, listen: &str) -> anyhow::Result<()> {
+    let listener =
+        TcpListener::bind(listen).with_context(|| format!("couldn't listen on {}", listen))?;
+    eprintln!("listening on http://{}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        if let Err(e) = stream.and_then(|it| handle(&mut mock, it)) {
+            debug!(error = %e, "dropped a connection")
+        }
+    }
+    Ok(())
+}
+
+fn handle(mock: &mut Mock, stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let post = line.starts_with("POST ");
+    let mut length = 0;
+    let mut transfer_encoding = None;
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let Some((name, value)) = line.trim_end().split_once(':') else {
+            break;
+        };
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value.trim().parse().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "bad Content-Length header")
+            })?
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            transfer_encoding = Some(value.trim().to_owned())
+        }
+    }
+    let body = match transfer_encoding {
+        Some(it) if it.eq_ignore_ascii_case("chunked") => read_chunked(&mut reader)?,
+        Some(_) => Err("411 Length Required"),
+        None if length > MAX_BODY => Err(TOO_LARGE),
+        None => {
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body)?;
+            Ok(body)
+        }
+    };
+    let (status, body) = match (post, body) {
+        (_, Err(status)) => (status, String::new()),
+        (false, Ok(_)) => ("405 Method Not Allowed", String::new()),
+        (true, Ok(body)) => match mock.respond(&body) {
+            Some(it) => ("200 OK", it.to_string()),
+            None => ("204 No Content", String::new()),
+        },
+    };
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// A `Transfer-Encoding: chunked` body, or the status to respond with if it
+/// is too large.
+fn read_chunked(reader: &mut impl BufRead) -> io::Result<Result<Vec<u8>, &'static str>> {
+    let mut body = vec![];
+    let mut line = String::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad chunk size"))?;
+        if size == 0 {
+            break;
+        }
+        if size > MAX_BODY - body.len() {
+            return Ok(Err(TOO_LARGE));
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        line.clear();
+        reader.read_line(&mut line)?;
+    }
+    // trailers, up to an empty line
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        if line.trim_end().is_empty() {
+            break Ok(Ok(body));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read as _, Write as _};
+
+    use openrpc_types::OpenRPC;
+
+    use super::*;
+    use crate::chains::resolve_within;
+
+    fn document() -> resolved::OpenRPC {
+        let document = serde_json::from_value::<OpenRPC>(json!({
+            "openrpc": "1.3.2",
+            "info": { "title": "mock", "version": "0.0.0" },
+            "methods": [
+                {
+                    "name": "Echo",
+                    "params": [
+                        { "name": "value", "required": true, "schema": { "type": "integer" } }
+                    ],
+                    "result": { "name": "value", "schema": { "type": "integer" } },
+                    "examples": [
+                        {
+                            "name": "one",
+                            "params": [{ "name": "value", "value": 1 }],
+                            "result": { "name": "value", "value": 1 }
+                        },
+                        {
+                            "name": "two",
+                            "params": [{ "name": "value", "value": 2 }],
+                            "result": { "name": "value", "value": 2 }
+                        }
+                    ]
+                },
+                {
+                    "name": "Version",
+                    "params": [],
+                    "result": { "name": "version", "schema": { "const": "1.0" } }
+                }
+            ]
+        }))
+        .unwrap();
+        resolve_within(document).unwrap()
+    }
+
+    fn respond(mock: &mut Mock, request: Value) -> Option<Value> {
+        mock.respond(request.to_string().as_bytes())
+    }
+
+    fn request(id: Value, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    /// Send `request` over a connection to [`handle`], returning the status
+    /// line and body of the response.
+    fn exchange(request: &[u8]) -> (String, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(request).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let document = document();
+        handle(&mut Mock::new(&document, 0, false).unwrap(), stream).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_owned(), body.to_owned())
+    }
+
+    #[test]
+    fn examples() {
+        let document = document();
+        let mut mock = Mock::new(&document, 0, false).unwrap();
+        assert_eq!(
+            respond(&mut mock, request(json!("a"), "Echo", json!([2]))),
+            Some(json!({ "jsonrpc": "2.0", "id": "a", "result": 2 }))
+        );
+        // no example matches, so the first is used
+        assert_eq!(
+            respond(&mut mock, request(json!(7), "Echo", json!([3]))),
+            Some(json!({ "jsonrpc": "2.0", "id": 7, "result": 1 }))
+        );
+        assert_eq!(
+            respond(&mut mock, request(json!(8), "Echo", json!({ "value": 2 }))),
+            Some(json!({ "jsonrpc": "2.0", "id": 8, "result": 2 }))
+        );
+    }
+
+    #[test]
+    fn generated() {
+        let document = document();
+        let mut mock = Mock::new(&document, 0, false).unwrap();
+        assert_eq!(
+            respond(&mut mock, request(json!(1), "Version", json!([]))),
+            Some(json!({ "jsonrpc": "2.0", "id": 1, "result": "1.0" }))
+        );
+        let mut strict = Mock::new(&document, 0, true).unwrap();
+        let response = respond(&mut strict, request(json!(1), "Version", json!([]))).unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn errors() {
+        let document = document();
+        let mut mock = Mock::new(&document, 0, false).unwrap();
+        let response = respond(&mut mock, request(json!(1), "Missing", json!([]))).unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        let response = respond(&mut mock, request(json!(2), "Echo", json!(["one"]))).unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        assert_eq!(response["id"], 2);
+        let response = mock.respond(b"{").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+    }
+
+    #[test]
+    fn batches() {
+        let document = document();
+        let mut mock = Mock::new(&document, 0, false).unwrap();
+        let notification = json!({ "jsonrpc": "2.0", "method": "Echo", "params": [1] });
+        assert_eq!(
+            respond(
+                &mut mock,
+                json!([
+                    request(json!(1), "Version", json!([])),
+                    notification.clone(),
+                    request(json!(2), "Echo", json!([1])),
+                ])
+            ),
+            Some(json!([
+                { "jsonrpc": "2.0", "id": 1, "result": "1.0" },
+                { "jsonrpc": "2.0", "id": 2, "result": 1 },
+            ]))
+        );
+        assert_eq!(respond(&mut mock, json!([notification])), None);
+    }
+
+    #[test]
+    fn bodies() {
+        let body = request(json!(1), "Echo", json!([2])).to_string();
+        let expected = json!({ "jsonrpc": "2.0", "id": 1, "result": 2 }).to_string();
+        let (status, response) = exchange(
+            format!(
+                "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .as_bytes(),
+        );
+        assert_eq!(
+            (status.as_str(), response.as_str()),
+            ("HTTP/1.1 200 OK", &*expected)
+        );
+
+        let (head, tail) = body.split_at(10);
+        let (status, response) = exchange(
+            format!(
+                "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n{:x};ext\r\n{}\r\n0\r\n\r\n",
+                head.len(),
+                head,
+                tail.len(),
+                tail
+            )
+            .as_bytes(),
+        );
+        assert_eq!(
+            (status.as_str(), response.as_str()),
+            ("HTTP/1.1 200 OK", &*expected)
+        );
+
+        let (status, _) = exchange(
+            format!(
+                "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+                MAX_BODY + 1
+            )
+            .as_bytes(),
+        );
+        assert_eq!(status, "HTTP/1.1 413 Payload Too Large");
+        let (status, _) = exchange(
+            format!(
+                "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n",
+                MAX_BODY + 1
+            )
+            .as_bytes(),
+        );
+        assert_eq!(status, "HTTP/1.1 413 Payload Too Large");
+        let (status, _) = exchange(b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 411 Length Required");
+    }
+}
@@ -0,0 +1,157 @@
+//! Canonical formatting for OpenRPC documents.
+//!
+//! The canonical form of a document:
+//! - has its methods sorted by name (references sort by their `$ref`).
+//! - has every object's keys sorted, including within schemas and
+//!   specification extensions.
+//! - omits optional arrays and maps which are empty.
+//! - omits fields which are set to their default value, namely
+//!   `deprecated: false` and `required: false`.
+//!   `required: true` is always kept, since the FIP requires it to be explicit.
+//! - is pretty-printed with two-space indentation, with no trailing newline.
+//!
+//! Normalization is idempotent, and doesn't change the semantics of the
+//! document.
+
+use openrpc_types::{Components, ContentDescriptor, Method, OpenRPC, ReferenceOr};
+
+pub fn normalize(mut document: OpenRPC) -> OpenRPC {
+    let OpenRPC {
+        openrpc: _,
+        info: _,
+        servers,
+        methods,
+        components,
+        external_docs: _,
+        extensions: _,
+    } = &mut document;
+    empty_to_none(servers);
+    methods.sort_by(|l, r| sort_key(l).cmp(sort_key(r)));
+    for method in methods {
+        if let ReferenceOr::Item(method) = method {
+            self::method(method)
+        }
+    }
+    if let Some(it) = components {
+        self::components(it);
+        if *it == Components::default() {
+            *components = None
+        }
+    }
+    document
+}
+
+fn sort_key(it: &ReferenceOr<Method>) -> &str {
+    match it {
+        ReferenceOr::Reference(it) => it,
+        ReferenceOr::Item(it) => &it.name,
+    }
+}
+
+fn method(
+    Method {
+        name: _,
+        tags,
+        summary: _,
+        description: _,
+        external_docs: _,
+        params,
+        result,
+        deprecated,
+        servers,
+        errors,
+        param_structure: _,
+        examples,
+        extensions: _,
+    }: &mut Method,
+) {
+    empty_to_none(tags);
+    empty_to_none(servers);
+    empty_to_none(errors);
+    empty_to_none(examples);
+    default_to_none(deprecated);
+    for it in params.iter_mut().chain(result) {
+        if let ReferenceOr::Item(it) = it {
+            content_descriptor(it)
+        }
+    }
+}
+
+fn content_descriptor(
+    ContentDescriptor {
+        name: _,
+        summary: _,
+        description: _,
+        required,
+        schema: _,
+        deprecated,
+        extensions: _,
+    }: &mut ContentDescriptor,
+) {
+    default_to_none(required);
+    default_to_none(deprecated);
+}
+
+fn components(
+    Components {
+        content_descriptors,
+        schemas,
+        examples,
+        errors,
+        example_pairing_objects,
+        tags,
+        extensions: _,
+    }: &mut Components,
+) {
+    for it in content_descriptors
+        .iter_mut()
+        .flat_map(|it| it.values_mut())
+    {
+        content_descriptor(it)
+    }
+    empty_to_none(content_descriptors);
+    empty_to_none(schemas);
+    empty_to_none(examples);
+    empty_to_none(errors);
+    empty_to_none(example_pairing_objects);
+    empty_to_none(tags);
+}
+
+fn empty_to_none<T: IntoIterator>(it: &mut Option<T>)
+where
+    for<'a> &'a T: IntoIterator,
+{
+    if it
+        .as_ref()
+        .is_some_and(|it| it.into_iter().next().is_none())
+    {
+        *it = None
+    }
+}
+
+fn default_to_none<T: Default + PartialEq>(it: &mut Option<T>) {
+    if it.as_ref().is_some_and(|it| *it == T::default()) {
+        *it = None
+    }
+}
+
+/// The canonical text of `document`, which should already be [`normalize`]d.
+pub fn to_string(document: &OpenRPC) -> String {
+    // `serde_json::Map` is sorted, so round-tripping through `Value` sorts all keys
+    serde_json::to_string_pretty(&serde_json::to_value(document).unwrap()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use openrpc_types::OpenRPC;
+
+    use crate::{openrpc_diff, release};
+
+    #[test]
+    fn spec_has_no_semantic_changes() {
+        let spec = serde_json::from_str::<OpenRPC>(include_str!("../../spec.json")).unwrap();
+        let normalized = super::to_string(&super::normalize(spec.clone()));
+        let summary = openrpc_diff::diff(spec, serde_json::from_str(&normalized).unwrap()).unwrap();
+        assert!(release::is_empty(&summary));
+    }
+}
@@ -4,21 +4,36 @@ use std::{
         btree_set::{Difference, Intersection},
         BTreeMap, BTreeSet,
     },
+    fmt,
 };
 
 use itertools::{EitherOrBoth, Itertools as _};
 use nunny::NonEmpty;
 use openrpc_types::{
-    resolve_within, resolved, BrokenReference, ContentDescriptor, OpenRPC, SpecificationExtensions,
+    resolved, BrokenReference, ContentDescriptor, OpenRPC, SpecificationExtensions,
 };
 use schemars::schema::{RootSchema, Schema};
-use serde::Serialize;
-use serde_json::Value;
+use serde::{
+    de::{self, DeserializeSeed, Deserializer, Error as _, MapAccess, SeqAccess, Visitor},
+    Deserialize, Serialize,
+};
+use serde_json::{value::RawValue, Map, Value};
+use sha2::{Digest as _, Sha256};
 pub use summary::*;
 
-pub fn diff(left: OpenRPC, right: OpenRPC) -> Result<Summary, BrokenReference> {
-    let (left_definitions, left_methods) = prepare(left)?;
-    let (right_definitions, right_methods) = prepare(right)?;
+use crate::{
+    chains::{self, resolve_within, ResolveError},
+    component_ref::{ComponentRef, ComponentSection},
+    links, progress,
+    reference_index::ReferenceIndex,
+};
+
+pub fn diff(left: OpenRPC, right: OpenRPC) -> Result<Summary, ResolveError> {
+    let (left, right) = (resolve_within(left)?, resolve_within(right)?);
+    // Diffing schemas is expensive, so skip methods which are unchanged.
+    let (left_fingerprints, right_fingerprints) = (fingerprints(&left)?, fingerprints(&right)?);
+    let (left_definitions, left_methods) = prepare(left);
+    let (right_definitions, right_methods) = prepare(right);
 
     let left_names = left_methods.keys().collect();
     let right_names = right_methods.keys().collect();
@@ -28,8 +43,16 @@ pub fn diff(left: OpenRPC, right: OpenRPC) -> Result<Summary, BrokenReference> {
     let mut methods = BTreeMap::new();
     let mut compatible = Vec::new();
 
+    let bar = progress::Bar::new(common.clone().count(), "diffed", "methods");
     for method in common {
         let method = (*method).clone();
+        bar.show(&method);
+        bar.inc();
+
+        if left_fingerprints[&method] == right_fingerprints[&method] {
+            compatible.push(method);
+            continue;
+        }
 
         let (left_params, left_return) = &left_methods[&method];
         let (right_params, right_return) = &right_methods[&method];
@@ -76,14 +99,319 @@ pub fn diff(left: OpenRPC, right: OpenRPC) -> Result<Summary, BrokenReference> {
             },
         );
     }
+    bar.finish();
     Ok(Summary {
         equivalent: compatible,
         different: methods,
         left: only_left.map(|it| (*it).clone()).collect(),
         right: only_right.map(|it| (*it).clone()).collect(),
+        links: BTreeMap::new(),
+    })
+}
+
+/// A hash of the parts of a method which [`diff`] looks at.
+pub type Fingerprint = [u8; 32];
+
+/// The [`Fingerprint`] of each method in `document`.
+///
+/// Methods with the same fingerprint in both documents are equivalent.
+/// Each component schema is hashed once, and a method's fingerprint includes
+/// the hashes of the schemas it reaches, so nothing is copied.
+pub fn fingerprints(
+    document: &resolved::OpenRPC,
+) -> Result<BTreeMap<String, Fingerprint>, BrokenReference> {
+    let index = ReferenceIndex::new(document);
+    let schemas = document
+        .components
+        .iter()
+        .flat_map(|it| it.schemas.iter().flatten())
+        .map(|(key, schema)| (key.as_str(), hash(schema)))
+        .collect::<BTreeMap<_, _>>();
+    let descriptor = |it: &ContentDescriptor| (it.required.unwrap_or_default(), &it.schema);
+    let mut fingerprints = BTreeMap::new();
+    for resolved::Method {
+        name,
+        params,
+        result,
+        ..
+    } in &document.methods
+    {
+        let reachable = index
+            .reachable(name)?
+            .into_iter()
+            .map(|key| (key, schemas[key]))
+            .collect::<BTreeMap<_, _>>();
+        fingerprints.insert(
+            name.clone(),
+            hash(&(
+                params.iter().map(descriptor).collect::<Vec<_>>(),
+                result.as_ref().map(descriptor),
+                reachable,
+            )),
+        );
+    }
+    Ok(fingerprints)
+}
+
+fn hash(it: &impl Serialize) -> Fingerprint {
+    let mut hasher = Sha256::new();
+    serde_json::to_writer(&mut hasher, it).expect("schemas serialize");
+    hasher.finalize().into()
+}
+
+/// A document with its methods and components left as text, so that
+/// [`diff_changed`] needn't deserialize those which haven't changed.
+pub struct Lazy<'a> {
+    /// Everything but `methods` and `components`, which is small.
+    rest: Map<String, Value>,
+    methods: Vec<&'a RawValue>,
+    /// With the `$ref`s in each.
+    components: BTreeMap<ComponentRef, (&'a RawValue, BTreeSet<String>)>,
+    /// Anything in `components` which isn't a section, like extensions.
+    other_components: Map<String, Value>,
+}
+
+impl<'a> Lazy<'a> {
+    pub fn parse(bytes: &'a [u8]) -> serde_json::Result<Self> {
+        let mut top = serde_json::from_slice::<BTreeMap<String, &RawValue>>(bytes)?;
+        let methods = top
+            .remove("methods")
+            .ok_or_else(|| serde_json::Error::missing_field("methods"))?;
+        let methods = serde_json::from_str(methods.get())?;
+        let mut components = BTreeMap::new();
+        let mut other_components = Map::new();
+        if let Some(raw) = top.remove("components") {
+            for (name, raw) in serde_json::from_str::<BTreeMap<String, &RawValue>>(raw.get())? {
+                let Some(&section) = ComponentSection::ALL.iter().find(|it| it.name() == name)
+                else {
+                    other_components.insert(name, serde_json::from_str(raw.get())?);
+                    continue;
+                };
+                for (key, raw) in serde_json::from_str::<BTreeMap<String, &RawValue>>(raw.get())? {
+                    let mut references = References::default();
+                    references.deserialize(&mut serde_json::Deserializer::from_str(raw.get()))?;
+                    components.insert(ComponentRef::new(section, key), (raw, references.0));
+                }
+            }
+        }
+        let rest = top
+            .into_iter()
+            .map(|(key, raw)| Ok((key, serde_json::from_str(raw.get())?)))
+            .collect::<serde_json::Result<_>>()?;
+        Ok(Self {
+            rest,
+            methods,
+            components,
+            other_components,
+        })
+    }
+
+    /// The text, [`Fingerprint`] and reachable components of each method,
+    /// by name.
+    ///
+    /// [`None`] if a method has no name, a name is used twice, or a `$ref`
+    /// isn't to a component in the document, which [`diff`] is left to
+    /// report.
+    fn methods(&self) -> Option<BTreeMap<String, LazyMethod<'a, '_>>> {
+        #[derive(Deserialize)]
+        struct Named {
+            name: String,
+        }
+        let mut methods = BTreeMap::new();
+        for &raw in &self.methods {
+            let Named { name } = serde_json::from_str(raw.get()).ok()?;
+            let mut references = References::default();
+            references
+                .deserialize(&mut serde_json::Deserializer::from_str(raw.get()))
+                .ok()?;
+            let mut pending = references.0.iter().collect::<Vec<_>>();
+            let mut reachable = BTreeMap::new();
+            while let Some(reference) = pending.pop() {
+                let (reference, (raw, references)) = self
+                    .components
+                    .get_key_value(&ComponentRef::parse(reference))?;
+                if reachable.insert(reference, raw.get()).is_none() {
+                    pending.extend(references)
+                }
+            }
+            let fingerprint = hash(&(
+                raw.get(),
+                reachable
+                    .iter()
+                    .map(|(reference, raw)| (reference.to_string(), raw))
+                    .collect::<Vec<_>>(),
+            ));
+            let reachable = reachable.into_keys().collect();
+            if methods
+                .insert(name, (raw, fingerprint, reachable))
+                .is_some()
+            {
+                return None;
+            }
+        }
+        Some(methods)
+    }
+
+    /// The document with only `methods`, and the `components` in
+    /// `reachable`.
+    fn document<'r>(
+        &self,
+        methods: impl IntoIterator<Item = &'a RawValue>,
+        reachable: impl IntoIterator<Item = &'r ComponentRef>,
+    ) -> serde_json::Result<Value> {
+        let mut components = self.other_components.clone();
+        for reference in reachable {
+            if let (ComponentRef::Component { section, key }, Some((raw, _))) =
+                (reference, self.components.get(reference))
+            {
+                components
+                    .entry(section.name())
+                    .or_insert_with(|| Value::Object(Map::new()))
+                    .as_object_mut()
+                    .expect("sections are objects")
+                    .insert(key.clone(), serde_json::from_str(raw.get())?);
+            }
+        }
+        let mut document = self.rest.clone();
+        document.insert(
+            String::from("methods"),
+            methods
+                .into_iter()
+                .map(|it| serde_json::from_str(it.get()))
+                .collect::<serde_json::Result<_>>()?,
+        );
+        if !components.is_empty() {
+            document.insert(String::from("components"), Value::Object(components));
+        }
+        Ok(Value::Object(document))
+    }
+}
+
+/// The text of a method, its [`Fingerprint`], and the components it reaches.
+type LazyMethod<'a, 'l> = (&'a RawValue, Fingerprint, BTreeSet<&'l ComponentRef>);
+
+/// As [`diff`], with [`links::diff`], of documents whose chained components
+/// haven't been followed yet.
+///
+/// A method whose text, and the text of every component it reaches, is the
+/// same in both documents is equivalent, so only the other methods and the
+/// components they reach are deserialized.
+pub fn diff_changed(
+    left: &Lazy<'_>,
+    right: &Lazy<'_>,
+    max_depth: usize,
+) -> anyhow::Result<Summary> {
+    let (Some(left_methods), Some(right_methods)) = (left.methods(), right.methods()) else {
+        return diff_values(
+            left.document(left.methods.iter().copied(), left.components.keys())?,
+            right.document(right.methods.iter().copied(), right.components.keys())?,
+            max_depth,
+        );
+    };
+    let left_names = left_methods.keys().collect();
+    let right_names = right_methods.keys().collect();
+    let (only_left, common, only_right) = venn(&left_names, &right_names);
+    let (unchanged, changed) = common
+        .copied()
+        .partition::<Vec<_>, _>(|it| left_methods[*it].1 == right_methods[*it].1);
+    let summary = diff_values(
+        reduced(left, &left_methods, &changed)?,
+        reduced(right, &right_methods, &changed)?,
+        max_depth,
+    )?;
+    Ok(Summary {
+        equivalent: unchanged
+            .into_iter()
+            .cloned()
+            .merge(summary.equivalent)
+            .collect(),
+        different: summary.different,
+        left: only_left.map(|it| (*it).clone()).collect(),
+        right: only_right.map(|it| (*it).clone()).collect(),
+        links: summary.links,
     })
 }
 
+/// `document` with only the `changed` methods and the components they
+/// reach.
+fn reduced<'a>(
+    document: &Lazy<'a>,
+    methods: &BTreeMap<String, LazyMethod<'a, '_>>,
+    changed: &[&String],
+) -> serde_json::Result<Value> {
+    document.document(
+        changed.iter().map(|it| methods[*it].0),
+        changed.iter().flat_map(|it| methods[*it].2.iter().copied()),
+    )
+}
+
+fn diff_values(mut left: Value, mut right: Value, max_depth: usize) -> anyhow::Result<Summary> {
+    chains::follow(&mut left, max_depth)?;
+    chains::follow(&mut right, max_depth)?;
+    let mut summary = diff(
+        serde_path_to_error::deserialize(&left)?,
+        serde_path_to_error::deserialize(&right)?,
+    )?;
+    summary.links = links::diff(&left, &right);
+    Ok(summary)
+}
+
+/// The `$ref`s anywhere in some JSON, found without building it.
+#[derive(Default)]
+struct References(BTreeSet<String>);
+
+impl<'de> DeserializeSeed<'de> for &mut References {
+    type Value = ();
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for &mut References {
+    type Value = ();
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("json")
+    }
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while seq.next_element_seed(&mut *self)?.is_some() {}
+        Ok(())
+    }
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key != "$ref" {
+                map.next_value_seed(&mut *self)?;
+                continue;
+            }
+            // A property called `$ref` is an object.
+            match map.next_value::<Value>()? {
+                Value::String(it) => {
+                    self.0.insert(it);
+                }
+                it => it.deserialize_any(&mut *self).map_err(A::Error::custom)?,
+            }
+        }
+        Ok(())
+    }
+}
+
 const NO_DESCRIPTOR: &ContentDescriptor = &ContentDescriptor {
     name: String::new(),
     summary: None,
@@ -94,35 +422,41 @@ const NO_DESCRIPTOR: &ContentDescriptor = &ContentDescriptor {
     extensions: SpecificationExtensions(BTreeMap::new()),
 };
 
+/// The component schemas and methods of `document`, with schema `$ref`s
+/// rewritten to point into the definitions.
 #[allow(clippy::type_complexity)]
 fn prepare(
-    mut document: OpenRPC,
-) -> Result<
-    (
-        BTreeMap<String, Schema>,
-        BTreeMap<String, (Vec<ContentDescriptor>, Option<ContentDescriptor>)>,
-    ),
-    BrokenReference,
-> {
-    rewrite_schema_references::open_rpc(&mut document);
-    let methods = resolve_within(document.clone())?
+    document: resolved::OpenRPC,
+) -> (
+    BTreeMap<String, Schema>,
+    BTreeMap<String, (Vec<ContentDescriptor>, Option<ContentDescriptor>)>,
+) {
+    let methods = document
         .methods
         .into_iter()
         .map(
             |resolved::Method {
                  name,
-                 params,
-                 result,
+                 mut params,
+                 mut result,
                  ..
-             }| (name, (params, result)),
+             }| {
+                params
+                    .iter_mut()
+                    .chain(&mut result)
+                    .for_each(rewrite_schema_references::content_descriptor);
+                (name, (params, result))
+            },
         )
         .collect();
-    let definitions = document
+    let mut definitions = document
         .components
-        .unwrap_or_default()
-        .schemas
+        .and_then(|it| it.schemas)
         .unwrap_or_default();
-    Ok((definitions, methods))
+    definitions
+        .values_mut()
+        .for_each(rewrite_schema_references::schema);
+    (definitions, methods)
 }
 
 #[derive(Debug, Serialize)]
@@ -204,6 +538,17 @@ mod summary {
         pub left: Vec<String>,
         #[serde(skip_serializing_if = "Vec::is_empty")]
         pub right: Vec<String>,
+        /// Filled in by [`links::diff`](crate::links::diff), because links
+        /// aren't in the parsed documents.
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        pub links: BTreeMap<String, LinksChange>,
+    }
+
+    /// The links of a method in both documents, when they differ.
+    #[derive(Serialize)]
+    pub struct LinksChange {
+        pub left: Vec<Value>,
+        pub right: Vec<Value>,
     }
 
     #[derive(Serialize)]
@@ -229,6 +574,9 @@ mod summary {
         pub kind: ChangeKind,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub of: Option<Subject>,
+        /// See [`json_schema_diff::ChangeKind::is_breaking`].
+        #[serde(skip)]
+        pub breaking: bool,
     }
 
     #[derive(Serialize)]
@@ -260,6 +608,7 @@ mod summary {
 
     impl From<json_schema_diff::Change> for Change {
         fn from(value: json_schema_diff::Change) -> Self {
+            let breaking = value.change.is_breaking();
             let json_schema_diff::Change { path, change } = value;
 
             use json_schema_diff::ChangeKind as Th;
@@ -301,6 +650,7 @@ mod summary {
                 path,
                 kind,
                 of: subject,
+                breaking,
             }
         }
     }
@@ -329,70 +679,15 @@ mod summary {
 /// We want to rewrite the former for [`json_schema_diff`].
 mod rewrite_schema_references {
     use either::Either;
-    use openrpc_types::{Components, ContentDescriptor, Method, OpenRPC, ReferenceOr};
+    use openrpc_types::ContentDescriptor;
     use schemars::schema::{
         ArrayValidation, ObjectValidation, Schema, SchemaObject, SingleOrVec, SubschemaValidation,
     };
-    use std::{collections::BTreeMap, iter};
+    use std::iter;
 
-    pub fn open_rpc(node: &mut OpenRPC) {
-        let OpenRPC {
-            openrpc: _,
-            info: _,
-            servers: _,
-            methods,
-            components,
-            external_docs: _,
-            extensions: _,
-        } = node;
-        for method in methods {
-            if let ReferenceOr::Item(Method {
-                name: _,
-                tags: _,
-                summary: _,
-                description: _,
-                external_docs: _,
-                params,
-                result,
-                deprecated: _,
-                servers: _,
-                errors: _,
-                param_structure: _,
-                examples: _,
-                extensions: _,
-            }) = method
-            {
-                params
-                    .iter_mut()
-                    .chain(result)
-                    .filter_map(|it| match it {
-                        ReferenceOr::Reference(_) => None,
-                        ReferenceOr::Item(it) => Some(it),
-                    })
-                    .for_each(content_descriptor)
-            }
-        }
-        if let Some(Components {
-            content_descriptors,
-            schemas,
-            examples: _,
-            errors: _,
-            example_pairing_objects: _,
-            tags: _,
-            extensions: _,
-        }) = components
-        {
-            content_descriptors
-                .iter_mut()
-                .flat_map(BTreeMap::values_mut)
-                .for_each(content_descriptor);
-            schemas
-                .iter_mut()
-                .flat_map(BTreeMap::values_mut)
-                .for_each(schema);
-        }
-    }
-    fn schema(node: &mut Schema) {
+    use crate::component_ref::{self, ComponentRef, ComponentSection};
+
+    pub fn schema(node: &mut Schema) {
         match node {
             Schema::Bool(_) => {}
             Schema::Object(SchemaObject {
@@ -410,8 +705,10 @@ mod rewrite_schema_references {
                 extensions: _,
             }) => {
                 if let Some(reference) = reference {
-                    if let Some(path) = reference.strip_prefix("#/components/schemas/") {
-                        *reference = format!("#/definitions/{}", path)
+                    if let Some(key) =
+                        ComponentRef::parse(reference).key_in(ComponentSection::Schemas)
+                    {
+                        *reference = format!("#/definitions/{}", component_ref::escape(key))
                     }
                 }
                 if let Some(SubschemaValidation {
@@ -473,7 +770,7 @@ mod rewrite_schema_references {
             }
         }
     }
-    fn content_descriptor(node: &mut ContentDescriptor) {
+    pub fn content_descriptor(node: &mut ContentDescriptor) {
         let ContentDescriptor {
             name: _,
             summary: _,
@@ -486,3 +783,68 @@ mod rewrite_schema_references {
         self::schema(schema)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn changed_is_full() {
+        let left = serde_json::from_str::<Value>(include_str!("../../spec.json")).unwrap();
+        let mut right = left.clone();
+        right["methods"][0]["params"][0]["required"] = json!(false);
+        right["methods"][2]["links"] = json!([{ "name": "head", "method": "Filecoin.ChainHead" }]);
+        right["components"]["schemas"]["Message"] = json!({ "type": "string" });
+        let methods = right["methods"].as_array_mut().unwrap();
+        methods.remove(1);
+        methods.push(json!({ "name": "Filecoin.New", "params": [] }));
+
+        let mut full = diff(
+            serde_json::from_value(left.clone()).unwrap(),
+            serde_json::from_value(right.clone()).unwrap(),
+        )
+        .unwrap();
+        full.links = links::diff(&left, &right);
+        assert!(!full.equivalent.is_empty());
+        assert!(!full.different.is_empty());
+        assert!(!full.links.is_empty());
+
+        let (left, right) = (
+            serde_json::to_vec_pretty(&left).unwrap(),
+            serde_json::to_vec_pretty(&right).unwrap(),
+        );
+        let changed = diff_changed(
+            &Lazy::parse(&left).unwrap(),
+            &Lazy::parse(&right).unwrap(),
+            chains::DEFAULT_MAX_DEPTH,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(changed).unwrap(),
+            serde_json::to_value(full).unwrap()
+        );
+    }
+
+    #[test]
+    fn references() {
+        let mut references = References::default();
+        references
+            .deserialize(&mut serde_json::Deserializer::from_str(
+                r##"{
+                    "items": [{ "$ref": "#/components/schemas/A" }],
+                    "properties": { "$ref": { "$ref": "#/components/schemas/B" } },
+                    "examples": [null, 1, -1, 1.5, true, "$ref"]
+                }"##,
+            ))
+            .unwrap();
+        assert_eq!(
+            references.0,
+            BTreeSet::from([
+                String::from("#/components/schemas/A"),
+                String::from("#/components/schemas/B"),
+            ])
+        );
+    }
+}
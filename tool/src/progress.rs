@@ -0,0 +1,101 @@
+//! Progress bars on stderr for passes which are slow on large documents.
+//!
+//! Bars are hidden when stderr isn't a terminal, or under `--no-progress`.
+//! Logs go through [`Writer`], which clears the bars while each line is
+//! written, so the two don't interleave.
+
+use std::{
+    io::{self, IsTerminal as _, Write as _},
+    sync::OnceLock,
+    time::Instant,
+};
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+static BARS: OnceLock<MultiProgress> = OnceLock::new();
+
+/// Call before any [`Bar`] is made, or bars are hidden.
+pub fn init(enabled: bool) {
+    let target = match enabled && io::stderr().is_terminal() {
+        true => ProgressDrawTarget::stderr(),
+        false => ProgressDrawTarget::hidden(),
+    };
+    let _ = BARS.set(MultiProgress::with_draw_target(target));
+}
+
+fn bars() -> &'static MultiProgress {
+    BARS.get_or_init(|| MultiProgress::with_draw_target(ProgressDrawTarget::hidden()))
+}
+
+/// Counts up to a known number of items, showing the one in hand.
+///
+/// The bar is cleared when dropped, so an early return doesn't leave it
+/// behind.
+pub struct Bar {
+    bar: ProgressBar,
+    /// Past tense, like `diffed`.
+    verb: &'static str,
+    /// Plural, like `methods`.
+    noun: &'static str,
+    start: Instant,
+}
+
+impl Bar {
+    pub fn new(len: usize, verb: &'static str, noun: &'static str) -> Self {
+        let bar = bars().add(ProgressBar::new(len as u64));
+        bar.set_style(
+            ProgressStyle::with_template("{prefix} [{bar:30}] {pos}/{len} {wide_msg}")
+                .expect("template is valid")
+                .progress_chars("=> "),
+        );
+        bar.set_prefix(verb);
+        Self {
+            bar,
+            verb,
+            noun,
+            start: Instant::now(),
+        }
+    }
+    /// Show `item` as the one in hand.
+    pub fn show(&self, item: &str) {
+        self.bar.set_message(item.to_owned())
+    }
+    pub fn inc(&self) {
+        self.bar.inc(1)
+    }
+    /// Clear the bar, and print how many items were done, and how long it
+    /// took.
+    pub fn finish(self) {
+        self.bar.finish_and_clear();
+        if !bars().is_hidden() {
+            bars().suspend(|| {
+                eprintln!(
+                    "{} {} {} in {:.1?}",
+                    self.verb,
+                    self.bar.position(),
+                    self.noun,
+                    self.start.elapsed()
+                )
+            })
+        }
+    }
+}
+
+impl Drop for Bar {
+    fn drop(&mut self) {
+        self.bar.finish_and_clear();
+        bars().remove(&self.bar)
+    }
+}
+
+/// Stderr, with the bars cleared while writing, for use as a log writer.
+pub struct Writer;
+
+impl io::Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        bars().suspend(|| io::stderr().write(buf))
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
@@ -0,0 +1,97 @@
+//! Where each part of a resolved document came from.
+//!
+//! [`resolve_within`](crate::chains::resolve_within) replaces references
+//! with what they point to, so findings about a resolved method can't say
+//! whether to fix the method or a component.
+//! [`resolve_within_traced`] resolves the same way, but also records whether
+//! each method, param and result was written inline or referenced.
+//! Broken references don't stop it, see
+//! [`resolve_within_lenient`](crate::chains::resolve_within_lenient).
+
+use std::fmt;
+
+use openrpc_types::{resolved, OpenRPC, ReferenceOr};
+use serde::Serialize;
+
+use crate::chains::{resolve_within_lenient, ResolveError, ResolveIssue};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum Origin {
+    Inline,
+    /// The `$ref`, like `#/components/contentDescriptors/Height`.
+    Component(String),
+}
+
+impl Origin {
+    fn of<T>(it: &ReferenceOr<T>) -> Self {
+        match it {
+            ReferenceOr::Reference(it) => Self::Component(it.clone()),
+            ReferenceOr::Item(_) => Self::Inline,
+        }
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inline => f.write_str("defined inline"),
+            Self::Component(it) => write!(f, "via {}", it),
+        }
+    }
+}
+
+/// `name`, followed by where it came from if that was a component.
+pub fn label(name: &str, origin: Option<&Origin>) -> String {
+    match origin {
+        Some(origin @ Origin::Component(_)) => format!("{} ({})", name, origin),
+        Some(Origin::Inline) | None => name.to_owned(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MethodOrigins {
+    pub method: Origin,
+    /// Empty if the method itself is a reference.
+    pub params: Vec<Origin>,
+    pub result: Option<Origin>,
+}
+
+pub struct Traced {
+    pub document: resolved::OpenRPC,
+    /// In the same order as the methods of `document`.
+    pub origins: Vec<MethodOrigins>,
+    /// Each use of a broken `$ref`, now a placeholder in `document`.
+    pub issues: Vec<ResolveIssue>,
+}
+
+impl Traced {
+    pub fn methods(&self) -> impl Iterator<Item = (&resolved::Method, &MethodOrigins)> {
+        self.document.methods.iter().zip(&self.origins)
+    }
+}
+
+/// As [`resolve_within_lenient`], recording the [`Origin`] of each part.
+pub fn resolve_within_traced(document: OpenRPC) -> Result<Traced, ResolveError> {
+    let origins = document
+        .methods
+        .iter()
+        .map(|method| match method {
+            ReferenceOr::Reference(_) => MethodOrigins {
+                method: Origin::of(method),
+                params: vec![],
+                result: None,
+            },
+            ReferenceOr::Item(it) => MethodOrigins {
+                method: Origin::Inline,
+                params: it.params.iter().map(Origin::of).collect(),
+                result: it.result.as_ref().map(Origin::of),
+            },
+        })
+        .collect();
+    let (document, issues) = resolve_within_lenient(document)?;
+    Ok(Traced {
+        document,
+        origins,
+        issues,
+    })
+}
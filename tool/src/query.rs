@@ -0,0 +1,66 @@
+//! Find methods in a document.
+
+use openrpc_types::{resolved, BrokenReference};
+
+use crate::reference_index::ReferenceIndex;
+
+/// Every given condition must hold for a method to match.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct Conditions {
+    /// Methods whose params or result reference this component schema, even
+    /// indirectly.
+    #[arg(long, value_name = "SCHEMA_KEY")]
+    pub references: Option<String>,
+    /// Methods with this tag.
+    #[arg(long)]
+    pub tag: Option<String>,
+    /// Methods which are deprecated.
+    #[arg(long)]
+    pub deprecated: bool,
+    /// Methods with no examples.
+    #[arg(long)]
+    pub missing_examples: bool,
+    /// Methods with a param of this name.
+    #[arg(long)]
+    pub param_name: Option<String>,
+}
+
+pub fn query<'a>(
+    document: &'a resolved::OpenRPC,
+    conditions: &Conditions,
+) -> Result<Vec<&'a resolved::Method>, BrokenReference> {
+    let Conditions {
+        references,
+        tag,
+        deprecated,
+        missing_examples,
+        param_name,
+    } = conditions;
+    let index = ReferenceIndex::new(document);
+    let mut matches = vec![];
+    for method in &document.methods {
+        if let Some(tag) = tag {
+            if !method.tags.iter().flatten().any(|it| it.name == *tag) {
+                continue;
+            }
+        }
+        if *deprecated && !method.deprecated.unwrap_or_default() {
+            continue;
+        }
+        if *missing_examples && method.examples.as_ref().is_some_and(|it| !it.is_empty()) {
+            continue;
+        }
+        if let Some(name) = param_name {
+            if !method.params.iter().any(|it| it.name == *name) {
+                continue;
+            }
+        }
+        if let Some(key) = references {
+            if !index.reachable(&method.name)?.contains(key.as_str()) {
+                continue;
+            }
+        }
+        matches.push(method)
+    }
+    Ok(matches)
+}
@@ -0,0 +1,132 @@
+//! Remove what can't be published from a document.
+
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+use openrpc_types::resolved;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{gc, glob};
+
+/// On a method, the names of related methods, as a string or an array.
+pub const SEE_ALSO: &str = "x-see-also";
+pub const REDACTED: &str = "<redacted>";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Rules {
+    /// Drop methods whose names match any of these patterns.
+    #[serde(default)]
+    pub drop_methods: Vec<String>,
+    /// Drop methods with any of these extensions set to the given value, like
+    /// `{"x-internal": true}`.
+    #[serde(default)]
+    pub drop_methods_with: BTreeMap<String, Value>,
+    /// Remove these extensions wherever they appear.
+    #[serde(default)]
+    pub strip_extensions: Vec<String>,
+    /// Remove servers whose name or URL matches any of these patterns.
+    #[serde(default)]
+    pub drop_servers: Vec<String>,
+    /// Replace example values for params whose names match any of these
+    /// patterns with [`REDACTED`].
+    #[serde(default)]
+    pub sensitive_params: Vec<String>,
+}
+
+/// Returns the names of the dropped methods.
+///
+/// Fails if a surviving method refers to a dropped one in [`SEE_ALSO`].
+pub fn redact(document: &mut resolved::OpenRPC, rules: &Rules) -> anyhow::Result<Vec<String>> {
+    let Rules {
+        drop_methods,
+        drop_methods_with,
+        strip_extensions,
+        drop_servers,
+        sensitive_params,
+    } = rules;
+    let any = |patterns: &[String], it: &str| patterns.iter().any(|p| glob::matches(p, it));
+
+    let mut dropped = vec![];
+    document.methods.retain(|method| {
+        let drop = any(drop_methods, &method.name)
+            || drop_methods_with
+                .iter()
+                .any(|(key, value)| method.extensions.0.get(key) == Some(value));
+        if drop {
+            dropped.push(method.name.clone())
+        }
+        !drop
+    });
+    for method in &document.methods {
+        let see_also = match method.extensions.0.get(SEE_ALSO) {
+            Some(Value::String(it)) => vec![it.as_str()],
+            Some(Value::Array(it)) => it.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        for it in see_also {
+            if dropped.iter().any(|dropped| dropped == it) {
+                bail!(
+                    "{} refers to redacted method {} in {}",
+                    method.name,
+                    it,
+                    SEE_ALSO
+                )
+            }
+        }
+    }
+
+    let servers = document
+        .servers
+        .iter_mut()
+        .chain(document.methods.iter_mut().flat_map(|it| &mut it.servers));
+    for servers in servers {
+        servers.retain(|it| !any(drop_servers, &it.name) && !any(drop_servers, &it.url))
+    }
+
+    for method in &mut document.methods {
+        for pairing in method.examples.iter_mut().flatten() {
+            for (param, example) in method.params.iter().zip(&mut pairing.params) {
+                if any(sensitive_params, &param.name) && example.value.is_some() {
+                    example.value = Some(Value::String(String::from(REDACTED)))
+                }
+            }
+        }
+    }
+
+    if !strip_extensions.is_empty() {
+        let mut value = serde_json::to_value(&*document)?;
+        strip(&mut value, strip_extensions);
+        *document = serde_json::from_value(value)?;
+    }
+
+    gc::prune_schemas(document)?;
+    Ok(dropped)
+}
+
+/// Remove `keys` from every object in `value`, other than the names of
+/// schema properties.
+fn strip(value: &mut Value, keys: &[String]) {
+    match value {
+        Value::Object(it) => {
+            it.retain(|key, _| !keys.contains(key));
+            for (key, value) in it {
+                match (key.as_str(), value) {
+                    ("properties" | "patternProperties", Value::Object(properties)) => {
+                        for it in properties.values_mut() {
+                            strip(it, keys)
+                        }
+                    }
+                    (_, value) => strip(value, keys),
+                }
+            }
+        }
+        Value::Array(it) => {
+            for it in it {
+                strip(it, keys)
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,343 @@
+//! Who references each component in a resolved document, built once so that
+//! features asking "what uses this?" agree.
+//!
+//! Resolution inlines components into methods, but schemas, including those
+//! of component content descriptors, still `$ref` component schemas.
+//! The keys of every section except links, which aren't modelled, are
+//! indexed, so a `$ref` to the wrong kind of component can be told apart from
+//! a dead one.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use openrpc_types::{resolved, BrokenReference, Components};
+use schemars::schema::Schema;
+use tracing::trace;
+
+use crate::{
+    component_ref::{ComponentRef, ComponentSection},
+    schema_visit::{self, Provenance, Visitor},
+};
+
+/// A `$ref` in a document.
+#[derive(Debug, Clone)]
+pub struct Location<'a> {
+    /// The root schema the `$ref` is in.
+    pub provenance: Provenance<'a>,
+    /// The JSON pointer to the `$ref`, like
+    /// `/methods/3/params/0/schema/items/$ref`.
+    pub pointer: String,
+    /// As written.
+    pub reference: &'a str,
+}
+
+/// Where each `$ref` is used, and what each method and component references
+/// directly.
+pub struct ReferenceIndex<'a> {
+    keys: BTreeMap<ComponentSection, BTreeSet<&'a str>>,
+    uses: BTreeMap<ComponentRef, Vec<Location<'a>>>,
+    methods: BTreeMap<&'a str, BTreeSet<ComponentRef>>,
+    components: BTreeMap<ComponentRef, BTreeSet<ComponentRef>>,
+}
+
+impl<'a> ReferenceIndex<'a> {
+    pub fn new(document: &'a resolved::OpenRPC) -> Self {
+        struct Collect<'a> {
+            index: ReferenceIndex<'a>,
+            /// The root schema being walked, and the pointer to it.
+            root: Option<(Provenance<'a>, String)>,
+        }
+        impl<'a> Collect<'a> {
+            /// What the method or component the root schema is in references
+            /// directly.
+            fn direct(&mut self, provenance: Provenance<'a>) -> &mut BTreeSet<ComponentRef> {
+                match provenance {
+                    Provenance::Param { method, .. } | Provenance::Result { method } => {
+                        self.index.methods.entry(method).or_default()
+                    }
+                    Provenance::ContentDescriptor { key } => self
+                        .index
+                        .components
+                        .entry(ComponentRef::new(ComponentSection::ContentDescriptors, key))
+                        .or_default(),
+                    Provenance::Component { key } => self
+                        .index
+                        .components
+                        .entry(ComponentRef::schema(key))
+                        .or_default(),
+                }
+            }
+        }
+        impl<'a> Visitor<'a> for Collect<'a> {
+            fn root(&mut self, provenance: Provenance<'a>, pointer: &str) {
+                self.direct(provenance);
+                self.root = Some((provenance, pointer.to_owned()))
+            }
+            fn reference(&mut self, reference: &'a str, pointer: &str) {
+                let Some((provenance, root)) = &self.root else {
+                    return;
+                };
+                let (provenance, pointer) = (*provenance, format!("{}{}", root, pointer));
+                let parsed = ComponentRef::parse(reference);
+                self.direct(provenance).insert(parsed.clone());
+                self.index.uses.entry(parsed).or_default().push(Location {
+                    provenance,
+                    pointer,
+                    reference,
+                })
+            }
+        }
+        let mut collect = Collect {
+            index: Self {
+                keys: document.components.as_ref().map(keys).unwrap_or_default(),
+                uses: BTreeMap::new(),
+                methods: BTreeMap::new(),
+                components: BTreeMap::new(),
+            },
+            root: None,
+        };
+        schema_visit::walk_document(document, &mut collect);
+        collect.index
+    }
+
+    /// Each `$ref` in the document, with everywhere it is used.
+    pub fn iter(&self) -> impl Iterator<Item = (&ComponentRef, &[Location<'a>])> {
+        self.uses.iter().map(|(it, uses)| (it, uses.as_slice()))
+    }
+
+    /// Where the component schema `key` is referenced.
+    pub fn uses(&self, key: &str) -> &[Location<'a>] {
+        self.uses
+            .get(&ComponentRef::schema(key))
+            .map_or(&[][..], Vec::as_slice)
+    }
+
+    /// The key of the component schema `reference` points at, if it exists.
+    pub fn schema(&self, reference: &ComponentRef) -> Option<&'a str> {
+        let key = reference.key_in(ComponentSection::Schemas)?;
+        self.keys.get(&ComponentSection::Schemas)?.get(key).copied()
+    }
+
+    /// Whether the component `reference` points at exists, in any section.
+    pub fn contains(&self, reference: &ComponentRef) -> bool {
+        match reference {
+            ComponentRef::Component { section, key } => self
+                .keys
+                .get(section)
+                .is_some_and(|it| it.contains(key.as_str())),
+            ComponentRef::External(_) | ComponentRef::Malformed(_) => false,
+        }
+    }
+
+    /// `$ref`s which aren't to a component schema which exists, with where
+    /// they are used.
+    pub fn dead(&self) -> impl Iterator<Item = (&ComponentRef, &Location<'a>)> {
+        self.iter()
+            .filter(|(it, _)| self.schema(it).is_none())
+            .flat_map(|(it, uses)| uses.iter().map(move |location| (it, location)))
+    }
+
+    /// The `$ref`s in the params and result of `method`, without following
+    /// them.
+    pub fn references(&self, method: &str) -> impl Iterator<Item = &ComponentRef> + '_ {
+        self.methods.get(method).into_iter().flatten()
+    }
+
+    /// The `$ref`s in the component `component`, without following them.
+    pub fn component_references(
+        &self,
+        component: &ComponentRef,
+    ) -> impl Iterator<Item = &ComponentRef> + '_ {
+        self.components.get(component).into_iter().flatten()
+    }
+
+    /// The keys of the component schemas which `method` transitively
+    /// references.
+    pub fn reachable(&self, method: &str) -> Result<BTreeSet<&'a str>, BrokenReference> {
+        self.reachable_from(self.references(method))
+    }
+
+    /// The keys of the component schemas which any of `methods` transitively
+    /// references.
+    pub fn reachable_from_group<'m>(
+        &self,
+        methods: impl IntoIterator<Item = &'m str>,
+    ) -> Result<BTreeSet<&'a str>, BrokenReference> {
+        self.reachable_from(methods.into_iter().flat_map(|it| self.references(it)))
+    }
+
+    /// The keys of the component schemas which any method transitively
+    /// references.
+    pub fn reachable_from_methods(&self) -> Result<BTreeSet<&'a str>, BrokenReference> {
+        self.reachable_from(self.methods.values().flatten())
+    }
+
+    /// The keys of the component schemas which `schema`, which needn't be in
+    /// the document, transitively references.
+    pub fn reachable_from_schema(
+        &self,
+        schema: &Schema,
+    ) -> Result<BTreeSet<&'a str>, BrokenReference> {
+        struct References(Vec<ComponentRef>);
+        impl Visitor<'_> for References {
+            fn reference(&mut self, reference: &str, _: &str) {
+                self.0.push(ComponentRef::parse(reference))
+            }
+        }
+        let mut references = References(vec![]);
+        schema_visit::walk(schema, &mut references);
+        self.reachable_from(&references.0)
+    }
+
+    fn reachable_from<'r>(
+        &'r self,
+        roots: impl IntoIterator<Item = &'r ComponentRef>,
+    ) -> Result<BTreeSet<&'a str>, BrokenReference> {
+        // Each reference, and the schema it was found in, if not a root.
+        let mut pending = roots.into_iter().map(|it| (it, None)).collect::<Vec<_>>();
+        let mut alive = BTreeSet::new();
+        while let Some((reference, via)) = pending.pop() {
+            let key = self
+                .schema(reference)
+                .ok_or_else(|| BrokenReference(reference.to_string()))?;
+            if alive.insert(key) {
+                trace!(schema = key, via = via.unwrap_or("a root"), "alive");
+                pending.extend(
+                    self.component_references(reference)
+                        .map(|it| (it, Some(key))),
+                )
+            }
+        }
+        Ok(alive)
+    }
+}
+
+/// The keys in each section of `components`.
+pub fn keys(components: &Components) -> BTreeMap<ComponentSection, BTreeSet<&str>> {
+    fn of<T>(it: &Option<BTreeMap<String, T>>) -> BTreeSet<&str> {
+        it.iter().flatten().map(|(key, _)| key.as_str()).collect()
+    }
+    let Components {
+        content_descriptors,
+        schemas,
+        examples,
+        errors,
+        example_pairing_objects,
+        tags,
+        extensions: _,
+    } = components;
+    BTreeMap::from([
+        (ComponentSection::Schemas, of(schemas)),
+        (
+            ComponentSection::ContentDescriptors,
+            of(content_descriptors),
+        ),
+        (ComponentSection::Examples, of(examples)),
+        (ComponentSection::Errors, of(errors)),
+        (ComponentSection::Tags, of(tags)),
+        (
+            ComponentSection::ExamplePairingObjects,
+            of(example_pairing_objects),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use openrpc_types::OpenRPC;
+    use serde_json::json;
+
+    use super::*;
+    use crate::chains::resolve_within;
+
+    #[test]
+    fn index() {
+        let document = serde_json::from_value::<OpenRPC>(json!({
+            "openrpc": "1.3.2",
+            "info": { "title": "index", "version": "0.0.0" },
+            "methods": [
+                {
+                    "name": "Get",
+                    "params": [{ "name": "a", "schema": { "$ref": "#/components/schemas/A" } }],
+                    "result": { "name": "b", "schema": { "$ref": "#/components/schemas/B" } }
+                },
+                {
+                    "name": "Wrong",
+                    "params": [],
+                    "result": {
+                        "name": "c",
+                        "schema": { "$ref": "#/components/contentDescriptors/C" }
+                    }
+                }
+            ],
+            "components": {
+                "schemas": {
+                    "A": { "items": { "$ref": "#/components/schemas/B" } },
+                    "B": { "type": "string" },
+                    "Unused": { "$ref": "#/components/schemas/Missing" }
+                },
+                "contentDescriptors": {
+                    "C": { "name": "c", "schema": { "$ref": "#/components/schemas/A" } }
+                }
+            }
+        }))
+        .unwrap();
+        let document = resolve_within(document).unwrap();
+        let index = ReferenceIndex::new(&document);
+
+        assert_eq!(index.reachable("Get").unwrap(), BTreeSet::from(["A", "B"]));
+        assert!(index.reachable("Wrong").is_err());
+        assert_eq!(
+            index.reachable_from_group(["Get", "Missing"]).unwrap(),
+            BTreeSet::from(["A", "B"])
+        );
+        assert_eq!(
+            index
+                .component_references(&ComponentRef::schema("A"))
+                .collect::<Vec<_>>(),
+            [&ComponentRef::schema("B")]
+        );
+
+        let uses = index
+            .uses("A")
+            .iter()
+            .map(|it| (it.provenance.to_string(), it.pointer.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            uses,
+            [
+                (
+                    String::from("param a of method Get"),
+                    "/methods/0/params/0/schema/$ref"
+                ),
+                (
+                    String::from("component content descriptor C"),
+                    "/components/contentDescriptors/C/schema/$ref"
+                ),
+            ]
+        );
+        let uses = index
+            .uses("B")
+            .iter()
+            .map(|it| it.pointer.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            uses,
+            [
+                "/methods/0/result/schema/$ref",
+                "/components/schemas/A/items/$ref"
+            ]
+        );
+
+        let dead = index
+            .dead()
+            .map(|(it, _)| (it.to_string(), index.contains(it)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dead,
+            [
+                (String::from("#/components/schemas/Missing"), false),
+                (String::from("#/components/contentDescriptors/C"), true),
+            ]
+        );
+    }
+}
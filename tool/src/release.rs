@@ -0,0 +1,122 @@
+//! Choose a new `info.version` from the [`Summary`] of changes since the last
+//! release, and describe those changes.
+//!
+//! A change is breaking if:
+//! - a method is removed.
+//! - a parameter becomes required, or a result becomes optional.
+//! - a schema change is [breaking](json_schema_diff::ChangeKind::is_breaking).
+
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use anyhow::{bail, Context as _};
+
+use crate::openrpc_diff::{ContentDescriptorChange, RequiredChange, Summary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Bump {
+    /// `major` for breaking changes, `minor` for other changes, and `patch`
+    /// if there are none.
+    #[default]
+    Auto,
+    Major,
+    Minor,
+    Patch,
+}
+
+pub fn is_empty(summary: &Summary) -> bool {
+    let Summary {
+        equivalent: _,
+        different,
+        left,
+        right,
+        links,
+    } = summary;
+    different.is_empty() && left.is_empty() && right.is_empty() && links.is_empty()
+}
+
+pub fn is_breaking(summary: &Summary) -> bool {
+    let schema = |it: &ContentDescriptorChange| it.changes.iter().any(|it| it.breaking);
+    !summary.left.is_empty()
+        || summary.different.values().any(|it| {
+            it.parameter
+                .values()
+                .any(|it| schema(it) || matches!(it.required, Some(RequiredChange::Right)))
+                || it.result.as_ref().is_some_and(|it| {
+                    schema(it) || matches!(it.required, Some(RequiredChange::Left))
+                })
+        })
+}
+
+/// Resolve [`Bump::Auto`].
+pub fn level(summary: &Summary, bump: Bump) -> Bump {
+    match bump {
+        Bump::Auto if is_breaking(summary) => Bump::Major,
+        Bump::Auto if !is_empty(summary) => Bump::Minor,
+        Bump::Auto => Bump::Patch,
+        it => it,
+    }
+}
+
+/// Bump `version`, which must be of the form `major.minor.patch`.
+pub fn bump(version: &str, level: Bump) -> anyhow::Result<String> {
+    let Some([major, minor, patch]) = version
+        .split('.')
+        .map(str::parse::<u64>)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+        .and_then(|it| <[_; 3]>::try_from(it).ok())
+    else {
+        bail!("version {} isn't of the form `major.minor.patch`", version)
+    };
+    Ok(match level {
+        Bump::Major => format!("{}.0.0", major + 1),
+        Bump::Minor => format!("{}.{}.0", major, minor + 1),
+        Bump::Patch | Bump::Auto => format!("{}.{}.{}", major, minor, patch + 1),
+    })
+}
+
+/// A line per added, removed or changed method.
+pub fn changes(summary: &Summary) -> Vec<String> {
+    summary
+        .right
+        .iter()
+        .map(|it| format!("Added `{}`", it))
+        .chain(summary.left.iter().map(|it| format!("Removed `{}`", it)))
+        .chain(
+            summary
+                .different
+                .keys()
+                .map(|it| format!("Changed `{}`", it)),
+        )
+        .collect()
+}
+
+/// A Markdown section for `version`.
+pub fn markdown(version: &str, changes: &[String]) -> String {
+    let mut out = format!("## {}\n\n", version);
+    match changes.is_empty() {
+        true => out.push_str("No changes.\n"),
+        false => {
+            for change in changes {
+                writeln!(out, "- {}", change).unwrap();
+            }
+        }
+    }
+    out
+}
+
+/// Append `section` to the file at `path`, separated by a blank line.
+pub fn append(path: &Path, section: &str) -> anyhow::Result<()> {
+    let existing = match fs::read_to_string(path) {
+        Ok(it) => it,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(e).with_context(|| format!("couldn't read from file {}", path.display()))
+        }
+    };
+    let text = match existing.is_empty() {
+        true => section.to_owned(),
+        false => format!("{}\n\n{}", existing.trim_end(), section),
+    };
+    fs::write(path, text).with_context(|| format!("couldn't write to file {}", path.display()))
+}
@@ -0,0 +1,185 @@
+//! Synthesize placeholder values and example pairings from schemas.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use openrpc_types::{resolved, Example, SpecificationExtensions};
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use serde_json::{Map, Number, Value};
+
+use crate::component_ref::{ComponentRef, ComponentSection};
+
+/// An [`resolved::ExamplePairing`] for each method in `document` which has no examples.
+///
+/// Pairings are named `generated`, and carry an `x-generated: true` extension.
+pub fn examples(document: &resolved::OpenRPC) -> BTreeMap<String, Vec<resolved::ExamplePairing>> {
+    let empty = BTreeMap::new();
+    let schemas = document
+        .components
+        .as_ref()
+        .and_then(|it| it.schemas.as_ref())
+        .unwrap_or(&empty);
+    document
+        .methods
+        .iter()
+        .filter(|it| it.examples.as_ref().is_none_or(Vec::is_empty))
+        .map(|method| {
+            let example = |name: &str, schema: &Schema| Example {
+                name: Some(name.to_owned()),
+                summary: None,
+                description: None,
+                value: Some(placeholder(schema, schemas)),
+                external_value: None,
+                extensions: SpecificationExtensions::default(),
+            };
+            let pairing = resolved::ExamplePairing {
+                name: String::from("generated"),
+                description: None,
+                summary: None,
+                params: method
+                    .params
+                    .iter()
+                    .map(|it| example(&it.name, &it.schema))
+                    .collect(),
+                result: method
+                    .result
+                    .as_ref()
+                    .map(|it| example(&it.name, &it.schema)),
+                extensions: SpecificationExtensions(BTreeMap::from_iter([(
+                    String::from("x-generated"),
+                    Value::Bool(true),
+                )])),
+            };
+            (method.name.clone(), vec![pairing])
+        })
+        .collect()
+}
+
+/// A deterministic value for `schema`, chosen in order of preference from:
+/// - `const`
+/// - the first of `enum`
+/// - `default`
+/// - a placeholder for the (first non-null) type, with `required` properties
+///   and `minItems` items filled in recursively.
+///
+/// `$ref`s into `#/components/schemas` are followed in `schemas`.
+/// Recursive references, and schemas that admit no value, produce `null`.
+pub fn placeholder(schema: &Schema, schemas: &BTreeMap<String, Schema>) -> Value {
+    imp(schema, schemas, &mut BTreeSet::new())
+}
+
+fn imp<'a>(
+    schema: &'a Schema,
+    schemas: &'a BTreeMap<String, Schema>,
+    visiting: &mut BTreeSet<&'a str>,
+) -> Value {
+    let object = match schema {
+        Schema::Bool(_) => return Value::Null,
+        Schema::Object(it) => it,
+    };
+    let SchemaObject {
+        metadata,
+        instance_type,
+        format: _,
+        enum_values,
+        const_value,
+        subschemas,
+        number,
+        string,
+        array,
+        object,
+        reference,
+        extensions: _,
+    } = object;
+    if let Some(it) = const_value {
+        return it.clone();
+    }
+    if let Some(it) = enum_values.as_ref().and_then(|it| it.first()) {
+        return it.clone();
+    }
+    if let Some(it) = metadata.as_ref().and_then(|it| it.default.as_ref()) {
+        return it.clone();
+    }
+    if let Some(reference) = reference {
+        return match ComponentRef::parse(reference)
+            .key_in(ComponentSection::Schemas)
+            .and_then(|key| schemas.get_key_value(key))
+        {
+            Some((key, child)) if visiting.insert(key.as_str()) => {
+                let value = imp(child, schemas, visiting);
+                visiting.remove(key.as_str());
+                value
+            }
+            _ => Value::Null,
+        };
+    }
+    if let Some(subschemas) = subschemas {
+        if let Some(first) = [&subschemas.one_of, &subschemas.any_of, &subschemas.all_of]
+            .into_iter()
+            .flatten()
+            .flat_map(|it| it.first())
+            .next()
+        {
+            return imp(first, schemas, visiting);
+        }
+    }
+    let ty = match instance_type {
+        None => return Value::Null,
+        Some(SingleOrVec::Single(it)) => **it,
+        Some(SingleOrVec::Vec(it)) => match it.iter().find(|it| **it != InstanceType::Null) {
+            Some(it) => *it,
+            None => InstanceType::Null,
+        },
+    };
+    match ty {
+        InstanceType::Null => Value::Null,
+        InstanceType::Boolean => Value::Bool(false),
+        InstanceType::Integer => Value::from(
+            number
+                .as_ref()
+                .and_then(|it| it.minimum)
+                .map_or(0, |it| it.ceil() as i64),
+        ),
+        InstanceType::Number => Number::from_f64(
+            number
+                .as_ref()
+                .and_then(|it| it.minimum)
+                .unwrap_or_default(),
+        )
+        .map_or(Value::Null, Value::Number),
+        InstanceType::String => Value::String(
+            "x".repeat(
+                string
+                    .as_ref()
+                    .and_then(|it| it.min_length)
+                    .unwrap_or_default() as usize,
+            ),
+        ),
+        InstanceType::Array => {
+            let array = array.as_deref();
+            let min = array.and_then(|it| it.min_items).unwrap_or_default() as usize;
+            Value::Array(match array.and_then(|it| it.items.as_ref()) {
+                Some(SingleOrVec::Single(it)) => {
+                    (0..min).map(|_| imp(it, schemas, visiting)).collect()
+                }
+                Some(SingleOrVec::Vec(it)) => {
+                    it.iter().map(|it| imp(it, schemas, visiting)).collect()
+                }
+                None => vec![Value::Null; min],
+            })
+        }
+        InstanceType::Object => Value::Object(match object.as_deref() {
+            Some(object) => object
+                .required
+                .iter()
+                .map(|key| {
+                    let value = match object.properties.get(key) {
+                        Some(it) => imp(it, schemas, visiting),
+                        None => Value::Null,
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+            None => Map::new(),
+        }),
+    }
+}
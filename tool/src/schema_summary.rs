@@ -0,0 +1,253 @@
+//! Short, single-line renderings of schemas for people, like `array of Cid`,
+//! `string (enum: bls, secp256k1)` or `TipSetKey`.
+//!
+//! Docs, the browser and other human-facing output share this, so the
+//! wording is the same everywhere.
+//!
+//! - `$ref`s to component schemas are rendered as their key.
+//! - nested schemas with a `title` are rendered as the title.
+//! - `oneOf` and `anyOf` are joined with ` | `, and `allOf` with ` & `.
+//! - anything nested more than `depth` levels is rendered as `…`.
+
+use std::collections::BTreeMap;
+
+use itertools::Itertools as _;
+use schemars::schema::{InstanceType, Schema, SingleOrVec};
+use serde_json::Value;
+
+use crate::component_ref::{ComponentRef, ComponentSection};
+
+pub const DEFAULT_DEPTH: usize = 3;
+
+/// As [`summarize_with`], to [`DEFAULT_DEPTH`], with component keys as they
+/// are.
+pub fn summarize(schema: &Schema, components: &BTreeMap<String, Schema>) -> String {
+    summarize_with(schema, components, DEFAULT_DEPTH, &str::to_owned)
+}
+
+/// Render `schema`, showing at most `depth` levels of nesting, and each
+/// component key with `key`.
+///
+/// `$ref`s which don't point into `components` are shown as they are.
+pub fn summarize_with(
+    schema: &Schema,
+    components: &BTreeMap<String, Schema>,
+    depth: usize,
+    key: &dyn Fn(&str) -> String,
+) -> String {
+    Summarizer { components, key }.summarize(schema, depth, true)
+}
+
+struct Summarizer<'a> {
+    components: &'a BTreeMap<String, Schema>,
+    key: &'a dyn Fn(&str) -> String,
+}
+
+impl Summarizer<'_> {
+    fn summarize(&self, schema: &Schema, depth: usize, root: bool) -> String {
+        let object = match schema {
+            Schema::Bool(true) => return String::from("any"),
+            Schema::Bool(false) => return String::from("never"),
+            Schema::Object(it) => it,
+        };
+        if let Some(reference) = &object.reference {
+            let parsed = ComponentRef::parse(reference);
+            return match parsed.key_in(ComponentSection::Schemas) {
+                Some(key) if self.components.contains_key(key) => (self.key)(key),
+                _ => reference.clone(),
+            };
+        }
+        if depth == 0 {
+            return String::from("…");
+        }
+        let title = object.metadata.as_ref().and_then(|it| it.title.as_ref());
+        if let Some(title) = title.filter(|_| !root) {
+            return title.clone();
+        }
+        let nested = |it: &Schema| self.summarize(it, depth - 1, false);
+        if let Some(it) = &object.const_value {
+            return value(it);
+        }
+        let types = match &object.instance_type {
+            None => vec![],
+            Some(SingleOrVec::Single(it)) => vec![**it],
+            Some(SingleOrVec::Vec(it)) => it.clone(),
+        };
+        if let Some(values) = &object.enum_values {
+            let values = values.iter().map(value).join(", ");
+            return match types.as_slice() {
+                [] => format!("enum: {}", values),
+                types => format!(
+                    "{} (enum: {})",
+                    types.iter().map(|it| instance_type(*it)).join(" | "),
+                    values
+                ),
+            };
+        }
+        if let Some(subschemas) = &object.subschemas {
+            for (alternatives, sep) in [
+                (&subschemas.one_of, " | "),
+                (&subschemas.any_of, " | "),
+                (&subschemas.all_of, " & "),
+            ] {
+                if let Some(alternatives) = alternatives {
+                    return alternatives.iter().map(nested).join(sep);
+                }
+            }
+        }
+        if types.is_empty() {
+            return String::from("any");
+        }
+        types
+            .into_iter()
+            .map(|ty| match ty {
+                InstanceType::Array => match object.array.as_ref().and_then(|it| it.items.as_ref())
+                {
+                    Some(SingleOrVec::Single(it)) => format!("array of {}", nested(&**it)),
+                    Some(SingleOrVec::Vec(it)) => format!("[{}]", it.iter().map(nested).join(", ")),
+                    None => String::from("array"),
+                },
+                InstanceType::Object => match object.object.as_ref() {
+                    Some(it) if it.properties.is_empty() => match &it.additional_properties {
+                        Some(values) => format!("map of {}", nested(&**values)),
+                        None => String::from("object"),
+                    },
+                    _ => String::from("object"),
+                },
+                other => match &object.format {
+                    Some(format) => format!("{} ({})", instance_type(other), format),
+                    None => String::from(instance_type(other)),
+                },
+            })
+            .join(" | ")
+    }
+}
+
+/// Strings as they are, anything else as JSON.
+fn value(it: &Value) -> String {
+    match it {
+        Value::String(it) => it.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn instance_type(it: InstanceType) -> &'static str {
+    match it {
+        InstanceType::Null => "null",
+        InstanceType::Boolean => "boolean",
+        InstanceType::Object => "object",
+        InstanceType::Array => "array",
+        InstanceType::Number => "number",
+        InstanceType::String => "string",
+        InstanceType::Integer => "integer",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema(it: Value) -> Schema {
+        serde_json::from_value(it).unwrap()
+    }
+
+    #[test]
+    fn golden() {
+        let components =
+            BTreeMap::from([(String::from("Cid"), schema(json!({ "type": "object" })))]);
+        for (it, expected) in [
+            (json!(true), "any"),
+            (json!(false), "never"),
+            (json!({}), "any"),
+            (json!({ "const": 1 }), "1"),
+            (json!({ "const": "bls" }), "bls"),
+            (json!({ "$ref": "#/components/schemas/Cid" }), "Cid"),
+            (
+                json!({ "$ref": "#/components/schemas/Missing" }),
+                "#/components/schemas/Missing",
+            ),
+            (
+                json!({ "type": "string", "format": "byte" }),
+                "string (byte)",
+            ),
+            (json!({ "type": ["string", "null"] }), "string | null"),
+            (
+                json!({ "type": "string", "enum": ["bls", "secp256k1"] }),
+                "string (enum: bls, secp256k1)",
+            ),
+            (json!({ "enum": [1, "two"] }), "enum: 1, two"),
+            (
+                json!({ "type": "array", "items": { "$ref": "#/components/schemas/Cid" } }),
+                "array of Cid",
+            ),
+            (
+                json!({ "type": "array", "items": [{ "type": "string" }, { "type": "integer" }] }),
+                "[string, integer]",
+            ),
+            (json!({ "type": "array" }), "array"),
+            (
+                json!({ "type": "object", "additionalProperties": { "type": "integer" } }),
+                "map of integer",
+            ),
+            (
+                json!({ "type": "object", "properties": { "a": {} } }),
+                "object",
+            ),
+            (
+                json!({ "oneOf": [{ "type": "integer", "format": "uint64" }, { "type": "null" }] }),
+                "integer (uint64) | null",
+            ),
+            (
+                json!({
+                    "allOf": [
+                        { "$ref": "#/components/schemas/Cid" },
+                        { "title": "Extra", "type": "object" }
+                    ]
+                }),
+                "Cid & Extra",
+            ),
+            // only nested titles are used
+            (json!({ "title": "Root", "type": "object" }), "object"),
+            (
+                json!({
+                    "type": "array",
+                    "items": {
+                        "type": "array",
+                        "items": { "type": "array", "items": { "type": "integer" } }
+                    }
+                }),
+                "array of array of array of …",
+            ),
+        ] {
+            assert_eq!(
+                summarize(&schema(it.clone()), &components),
+                expected,
+                "{}",
+                it
+            );
+        }
+    }
+
+    #[test]
+    fn keys() {
+        let components =
+            BTreeMap::from([(String::from("Cid"), schema(json!({ "type": "object" })))]);
+        let it =
+            schema(json!({ "type": "array", "items": { "$ref": "#/components/schemas/Cid" } }));
+        assert_eq!(
+            summarize_with(&it, &components, DEFAULT_DEPTH, &|key| format!(
+                "[`{}`](#{})",
+                key,
+                key.to_lowercase()
+            )),
+            "array of [`Cid`](#cid)"
+        );
+        // `$ref`s are shown at any depth
+        assert_eq!(
+            summarize_with(&it, &components, 1, &str::to_owned),
+            "array of Cid"
+        );
+    }
+}
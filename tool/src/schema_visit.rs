@@ -0,0 +1,418 @@
+//! Walk schema trees with callbacks, rather than each traversal destructuring
+//! [`Schema`]s itself.
+//!
+//! Walks use an explicit stack, so deeply nested schemas can't overflow, and
+//! don't follow `$ref`s.
+
+use std::{fmt, iter};
+
+use openrpc_types::resolved;
+use schemars::schema::{
+    ArrayValidation, ObjectValidation, Schema, SchemaObject, SingleOrVec, SubschemaValidation,
+};
+
+use crate::component_ref;
+
+/// Where a root schema in a document is.
+#[derive(Debug, Clone, Copy)]
+pub enum Provenance<'a> {
+    Param { method: &'a str, param: &'a str },
+    Result { method: &'a str },
+    ContentDescriptor { key: &'a str },
+    Component { key: &'a str },
+}
+
+impl fmt::Display for Provenance<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Provenance::Param { method, param } => {
+                write!(f, "param {} of method {}", param, method)
+            }
+            Provenance::Result { method } => write!(f, "result of method {}", method),
+            Provenance::ContentDescriptor { key } => {
+                write!(f, "component content descriptor {}", key)
+            }
+            Provenance::Component { key } => write!(f, "component schema {}", key),
+        }
+    }
+}
+
+/// Callbacks for [`walk`] and [`walk_document`], which all do nothing by
+/// default.
+pub trait Visitor<'a> {
+    /// Before walking each root schema in a document, with the JSON pointer to
+    /// it, like `/methods/3/params/0/schema`.
+    fn root(&mut self, _provenance: Provenance<'a>, _pointer: &str) {}
+    /// Each schema, including the root, with its depth starting at `1`.
+    fn schema(&mut self, _schema: &'a Schema, _depth: usize) {}
+    /// Each `$ref`, with the JSON pointer to it from the schema being walked,
+    /// like `/items/$ref`.
+    fn reference(&mut self, _reference: &'a str, _pointer: &str) {}
+}
+
+/// Visit `schema` and everything nested within it, in pre-order.
+///
+/// Children are as [`children`].
+pub fn walk<'a>(schema: &'a Schema, visitor: &mut (impl Visitor<'a> + ?Sized)) {
+    let mut stack = vec![(schema, 1, String::new())];
+    while let Some((schema, depth, pointer)) = stack.pop() {
+        visitor.schema(schema, depth);
+        if let Schema::Object(SchemaObject {
+            reference: Some(it),
+            ..
+        }) = schema
+        {
+            visitor.reference(it, &format!("{}/$ref", pointer))
+        }
+        let start = stack.len();
+        stack.extend(
+            children_with_pointers(schema)
+                .into_iter()
+                .map(|(it, child)| (child, depth + 1, format!("{}{}", pointer, it))),
+        );
+        stack[start..].reverse();
+    }
+}
+
+/// Visit the schema of every param, result and component content descriptor,
+/// and every component schema, in `document` once, after calling
+/// [`Visitor::root`] with where it is.
+pub fn walk_document<'a>(
+    document: &'a resolved::OpenRPC,
+    visitor: &mut (impl Visitor<'a> + ?Sized),
+) {
+    for (ix, method) in document.methods.iter().enumerate() {
+        for (param_ix, param) in method.params.iter().enumerate() {
+            let provenance = Provenance::Param {
+                method: &method.name,
+                param: &param.name,
+            };
+            visitor.root(
+                provenance,
+                &format!("/methods/{}/params/{}/schema", ix, param_ix),
+            );
+            walk(&param.schema, visitor)
+        }
+        if let Some(result) = &method.result {
+            let provenance = Provenance::Result {
+                method: &method.name,
+            };
+            visitor.root(provenance, &format!("/methods/{}/result/schema", ix));
+            walk(&result.schema, visitor)
+        }
+    }
+    let components = document.components.as_ref();
+    for (key, descriptor) in components
+        .iter()
+        .flat_map(|it| it.content_descriptors.iter().flatten())
+    {
+        let pointer = format!(
+            "/components/contentDescriptors/{}/schema",
+            component_ref::escape(key)
+        );
+        visitor.root(Provenance::ContentDescriptor { key }, &pointer);
+        walk(&descriptor.schema, visitor)
+    }
+    for (key, schema) in components.iter().flat_map(|it| it.schemas.iter().flatten()) {
+        let pointer = format!("/components/schemas/{}", component_ref::escape(key));
+        visitor.root(Provenance::Component { key }, &pointer);
+        walk(schema, visitor)
+    }
+}
+
+/// The subschemas nested directly within `schema`, not following `$ref`s.
+pub fn children(schema: &Schema) -> impl Iterator<Item = &Schema> {
+    children_with_pointers(schema).into_iter().map(|(_, it)| it)
+}
+
+/// As [`children`], with the JSON pointer to each from `schema`, like
+/// `/properties/a`.
+pub fn children_with_pointers(schema: &Schema) -> Vec<(String, &Schema)> {
+    let object = match schema {
+        Schema::Bool(_) => return vec![],
+        Schema::Object(it) => it,
+    };
+    let SchemaObject {
+        metadata: _,
+        instance_type: _,
+        format: _,
+        enum_values: _,
+        const_value: _,
+        subschemas,
+        number: _,
+        string: _,
+        array,
+        object,
+        reference: _,
+        extensions: _,
+    } = object;
+    let mut children = vec![];
+    if let Some(SubschemaValidation {
+        all_of,
+        any_of,
+        one_of,
+        not,
+        if_schema,
+        then_schema,
+        else_schema,
+    }) = subschemas.as_deref()
+    {
+        for (keyword, it) in [("allOf", all_of), ("anyOf", any_of), ("oneOf", one_of)] {
+            for (ix, it) in it.iter().flatten().enumerate() {
+                children.push((format!("/{}/{}", keyword, ix), it))
+            }
+        }
+        for (keyword, it) in [
+            ("not", not),
+            ("if", if_schema),
+            ("then", then_schema),
+            ("else", else_schema),
+        ] {
+            if let Some(it) = it {
+                children.push((format!("/{}", keyword), &**it))
+            }
+        }
+    }
+    if let Some(ArrayValidation {
+        items,
+        additional_items,
+        max_items: _,
+        min_items: _,
+        unique_items: _,
+        contains,
+    }) = array.as_deref()
+    {
+        match items {
+            Some(SingleOrVec::Single(it)) => children.push((String::from("/items"), &**it)),
+            Some(SingleOrVec::Vec(it)) => {
+                for (ix, it) in it.iter().enumerate() {
+                    children.push((format!("/items/{}", ix), it))
+                }
+            }
+            None => {}
+        }
+        for (keyword, it) in [
+            ("additionalItems", additional_items),
+            ("contains", contains),
+        ] {
+            if let Some(it) = it {
+                children.push((format!("/{}", keyword), &**it))
+            }
+        }
+    }
+    if let Some(ObjectValidation {
+        max_properties: _,
+        min_properties: _,
+        required: _,
+        properties,
+        pattern_properties,
+        additional_properties,
+        property_names,
+    }) = object.as_deref()
+    {
+        for (keyword, it) in [
+            ("properties", properties),
+            ("patternProperties", pattern_properties),
+        ] {
+            for (key, it) in it {
+                children.push((format!("/{}/{}", keyword, component_ref::escape(key)), it))
+            }
+        }
+        for (keyword, it) in [
+            ("additionalProperties", additional_properties),
+            ("propertyNames", property_names),
+        ] {
+            if let Some(it) = it {
+                children.push((format!("/{}", keyword), &**it))
+            }
+        }
+    }
+    children
+}
+
+/// The mutable counterpart to [`children`].
+pub fn children_mut(schema: &mut Schema) -> Vec<&mut Schema> {
+    let object = match schema {
+        Schema::Bool(_) => return vec![],
+        Schema::Object(it) => it,
+    };
+    let SchemaObject {
+        metadata: _,
+        instance_type: _,
+        format: _,
+        enum_values: _,
+        const_value: _,
+        subschemas,
+        number: _,
+        string: _,
+        array,
+        object,
+        reference: _,
+        extensions: _,
+    } = object;
+    let mut children = vec![];
+    if let Some(SubschemaValidation {
+        all_of,
+        any_of,
+        one_of,
+        not,
+        if_schema,
+        then_schema,
+        else_schema,
+    }) = subschemas.as_deref_mut()
+    {
+        children.extend(
+            iter::empty()
+                .chain(all_of.iter_mut().flatten())
+                .chain(any_of.iter_mut().flatten())
+                .chain(one_of.iter_mut().flatten())
+                .chain(not.as_deref_mut())
+                .chain(if_schema.as_deref_mut())
+                .chain(then_schema.as_deref_mut())
+                .chain(else_schema.as_deref_mut()),
+        )
+    }
+    if let Some(ArrayValidation {
+        items,
+        additional_items,
+        max_items: _,
+        min_items: _,
+        unique_items: _,
+        contains,
+    }) = array.as_deref_mut()
+    {
+        match items {
+            Some(SingleOrVec::Single(it)) => children.push(&mut **it),
+            Some(SingleOrVec::Vec(it)) => children.extend(it),
+            None => {}
+        }
+        children.extend(
+            additional_items
+                .as_deref_mut()
+                .into_iter()
+                .chain(contains.as_deref_mut()),
+        )
+    }
+    if let Some(ObjectValidation {
+        max_properties: _,
+        min_properties: _,
+        required: _,
+        properties,
+        pattern_properties,
+        additional_properties,
+        property_names,
+    }) = object.as_deref_mut()
+    {
+        children.extend(
+            properties
+                .values_mut()
+                .chain(pattern_properties.values_mut())
+                .chain(additional_properties.as_deref_mut())
+                .chain(property_names.as_deref_mut()),
+        )
+    }
+    children
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn title(schema: &Schema) -> &str {
+        match schema {
+            Schema::Object(SchemaObject {
+                metadata: Some(it), ..
+            }) => it.title.as_deref().unwrap(),
+            _ => panic!("untitled"),
+        }
+    }
+
+    /// Each keyword which holds subschemas is a child, titled with the
+    /// keyword.
+    #[test]
+    fn every_subschema_is_a_child() {
+        let keywords = [
+            "allOf",
+            "anyOf",
+            "oneOf",
+            "not",
+            "if",
+            "then",
+            "else",
+            "items",
+            "additionalItems",
+            "contains",
+            "properties",
+            "patternProperties",
+            "additionalProperties",
+            "propertyNames",
+        ];
+        let mut schema = serde_json::from_value::<Schema>(json!({
+            "allOf": [{ "title": "allOf" }],
+            "anyOf": [{ "title": "anyOf" }],
+            "oneOf": [{ "title": "oneOf" }],
+            "not": { "title": "not" },
+            "if": { "title": "if" },
+            "then": { "title": "then" },
+            "else": { "title": "else" },
+            "items": { "title": "items" },
+            "additionalItems": { "title": "additionalItems" },
+            "contains": { "title": "contains" },
+            "properties": { "a": { "title": "properties" } },
+            "patternProperties": { "^a": { "title": "patternProperties" } },
+            "additionalProperties": { "title": "additionalProperties" },
+            "propertyNames": { "title": "propertyNames" },
+        }))
+        .unwrap();
+        assert_eq!(children(&schema).map(title).collect::<Vec<_>>(), keywords);
+        assert_eq!(
+            children_with_pointers(&schema)
+                .into_iter()
+                .map(|(pointer, _)| pointer)
+                .collect::<Vec<_>>(),
+            [
+                "/allOf/0",
+                "/anyOf/0",
+                "/oneOf/0",
+                "/not",
+                "/if",
+                "/then",
+                "/else",
+                "/items",
+                "/additionalItems",
+                "/contains",
+                "/properties/a",
+                "/patternProperties/^a",
+                "/additionalProperties",
+                "/propertyNames",
+            ]
+        );
+        assert_eq!(
+            children_mut(&mut schema)
+                .into_iter()
+                .map(|it| title(it))
+                .collect::<Vec<_>>(),
+            keywords
+        );
+
+        struct Titles(Vec<String>);
+        impl Visitor<'_> for Titles {
+            fn schema(&mut self, schema: &Schema, depth: usize) {
+                if depth == 2 {
+                    self.0.push(title(schema).to_owned())
+                }
+            }
+        }
+        let mut titles = Titles(vec![]);
+        walk(&schema, &mut titles);
+        assert_eq!(titles.0, keywords);
+
+        let tuple = serde_json::from_value::<Schema>(json!({
+            "items": [{ "title": "0" }, { "title": "1" }],
+        }))
+        .unwrap();
+        assert_eq!(children(&tuple).map(title).collect::<Vec<_>>(), ["0", "1"]);
+    }
+}
@@ -0,0 +1,88 @@
+//! Sign a document, so mirrors can be checked against the publisher.
+//!
+//! The SHA-256 of the [canonical form](crate::normalize) of the document,
+//! without the seal, is signed with ed25519.
+//! The seal is stored in [`INTEGRITY`] on `info`:
+//! ```json
+//! { "algorithm": "ed25519", "digest": "sha256:<hex>", "signature": "<hex>", "publicKey": "<hex>" }
+//! ```
+//! Since the digest is of the canonical form, reformatting a sealed document
+//! doesn't break the seal.
+
+use anyhow::{bail, Context as _};
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier as _, VerifyingKey};
+use openrpc_types::OpenRPC;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+use crate::{exit, normalize};
+
+pub const INTEGRITY: &str = "x-integrity";
+const ALGORITHM: &str = "ed25519";
+const DIGEST_PREFIX: &str = "sha256:";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Seal {
+    algorithm: String,
+    digest: String,
+    signature: String,
+    public_key: String,
+}
+
+/// Replace any existing seal on `document`.
+pub fn seal(document: &mut OpenRPC, key: &SigningKey) {
+    document.info.extensions.0.remove(INTEGRITY);
+    let digest = digest(document);
+    let seal = Seal {
+        algorithm: String::from(ALGORITHM),
+        digest: format!("{}{}", DIGEST_PREFIX, hex::encode(digest)),
+        signature: hex::encode(key.sign(&digest).to_bytes()),
+        public_key: hex::encode(key.verifying_key().to_bytes()),
+    };
+    document
+        .info
+        .extensions
+        .0
+        .insert(String::from(INTEGRITY), serde_json::to_value(seal).unwrap());
+}
+
+/// Check that `document` is unchanged since it was sealed by `key`.
+pub fn verify(mut document: OpenRPC, key: &VerifyingKey) -> anyhow::Result<()> {
+    let Some(seal) = document.info.extensions.0.remove(INTEGRITY) else {
+        bail!(exit::Findings(format!(
+            "the document has no {} extension on info",
+            INTEGRITY
+        )))
+    };
+    let Seal {
+        algorithm,
+        digest: expected,
+        signature,
+        public_key: _,
+    } = serde_json::from_value(seal)
+        .with_context(|| format!("couldn't parse the {} extension", INTEGRITY))?;
+    if algorithm != ALGORITHM {
+        bail!("unsupported signature algorithm {}", algorithm)
+    }
+    let digest = digest(&document);
+    if expected != format!("{}{}", DIGEST_PREFIX, hex::encode(digest)) {
+        bail!(exit::Findings(String::from(
+            "the document has changed since it was sealed"
+        )))
+    }
+    let signature = hex::decode(&signature)
+        .ok()
+        .and_then(|it| Signature::from_slice(&it).ok())
+        .context("couldn't parse the signature")?;
+    key.verify(&digest, &signature).map_err(|_| {
+        exit::Findings(String::from("the signature doesn't match the public key")).into()
+    })
+}
+
+fn digest(document: &OpenRPC) -> [u8; 32] {
+    Sha256::digest(normalize::to_string(&normalize::normalize(
+        document.clone(),
+    )))
+    .into()
+}
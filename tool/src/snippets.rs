@@ -0,0 +1,124 @@
+//! Copy-pasteable shell commands which call each method.
+
+use std::collections::BTreeMap;
+
+use openrpc_types::{resolved, ParamStructure};
+use schemars::schema::Schema;
+use serde_json::{json, Value};
+
+use crate::scaffold;
+
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+pub enum Format {
+    #[default]
+    Curl,
+    Httpie,
+}
+
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+pub enum Shell {
+    /// POSIX shells, like `sh`, `bash` and `zsh`.
+    #[default]
+    Sh,
+    Fish,
+}
+
+pub struct Options<'a> {
+    pub format: Format,
+    pub shell: Shell,
+    pub remote: &'a str,
+    pub auth_token: Option<&'a str>,
+    /// Split the command over several lines, and pretty-print the request.
+    pub pretty: bool,
+}
+
+/// The request for `method`, using the params of its first example, or
+/// placeholders for its required params if it has none.
+pub fn request(method: &resolved::Method, schemas: &BTreeMap<String, Schema>) -> Value {
+    let values = match method.examples.iter().flatten().next() {
+        Some(pairing) => pairing
+            .params
+            .iter()
+            .map(|it| it.value.clone().unwrap_or_default())
+            .collect::<Vec<_>>(),
+        None => {
+            let len = method
+                .params
+                .iter()
+                .rposition(|it| it.required.unwrap_or_default())
+                .map_or(0, |it| it + 1);
+            method.params[..len]
+                .iter()
+                .map(|it| scaffold::placeholder(&it.schema, schemas))
+                .collect()
+        }
+    };
+    let params = match method.param_structure {
+        Some(ParamStructure::ByName) => Value::Object(
+            method
+                .params
+                .iter()
+                .map(|it| it.name.clone())
+                .zip(values)
+                .collect(),
+        ),
+        _ => Value::Array(values),
+    };
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method.name,
+        "params": params,
+    })
+}
+
+pub fn snippet(request: &Value, options: &Options) -> String {
+    let Options {
+        format,
+        shell,
+        remote,
+        auth_token,
+        pretty,
+    } = *options;
+    let body = match pretty {
+        true => serde_json::to_string_pretty(request).unwrap(),
+        false => request.to_string(),
+    };
+    let quote = |it: &str| quote(shell, it);
+    let mut words = match format {
+        Format::Curl => vec![
+            String::from("curl"),
+            String::from("--request POST"),
+            format!("--header {}", quote("Content-Type: application/json")),
+        ],
+        Format::Httpie => vec![String::from("http"), format!("--raw {}", quote(&body))],
+    };
+    if let Some(token) = auth_token {
+        words.push(match format {
+            Format::Curl => format!(
+                "--header {}",
+                quote(&format!("Authorization: Bearer {}", token))
+            ),
+            Format::Httpie => quote(&format!("Authorization:Bearer {}", token)),
+        })
+    }
+    match format {
+        Format::Curl => {
+            words.push(format!("--data {}", quote(&body)));
+            words.push(quote(remote))
+        }
+        Format::Httpie => words.insert(2, format!("POST {}", quote(remote))),
+    }
+    words.join(match pretty {
+        true => " \\\n  ",
+        false => " ",
+    })
+}
+
+/// A single-quoted word.
+fn quote(shell: Shell, it: &str) -> String {
+    match shell {
+        Shell::Sh => format!("'{}'", it.replace('\'', r"'\''")),
+        Shell::Fish => format!("'{}'", it.replace('\\', r"\\").replace('\'', r"\'")),
+    }
+}
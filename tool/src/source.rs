@@ -0,0 +1,410 @@
+//! Where to load an OpenRPC document from.
+
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, Read as _},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{bail, Context as _};
+use openrpc_types::{resolved, OpenRPC};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use sha2::{Digest as _, Sha256};
+
+use crate::{chains, diagnostic};
+
+/// Parsed from a command-line argument:
+/// - `-` is stdin.
+/// - `http://...` and `https://...` are fetched with a `GET`.
+/// - `rpc+http://...` and `rpc+https://...` call `rpc.discover` at the
+///   endpoint, using the result.
+/// - Anything else is a file path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecSource {
+    Path(PathBuf),
+    Stdin,
+    Url(String),
+    Discover(String),
+}
+
+impl FromStr for SpecSource {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "-" => Self::Stdin,
+            s if s.starts_with("http://") || s.starts_with("https://") => Self::Url(s.to_owned()),
+            s if s.starts_with("rpc+http://") || s.starts_with("rpc+https://") => {
+                Self::Discover(s["rpc+".len()..].to_owned())
+            }
+            s => Self::Path(PathBuf::from(s)),
+        })
+    }
+}
+
+impl SpecSource {
+    /// A local file, where `-` is stdin.
+    pub fn from_path(path: &Path) -> Self {
+        match path == Path::new("-") {
+            true => Self::Stdin,
+            false => Self::Path(path.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for SpecSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecSource::Path(it) => write!(f, "file {}", it.display()),
+            SpecSource::Stdin => f.write_str("stdin"),
+            SpecSource::Url(it) => write!(f, "url {}", it),
+            SpecSource::Discover(it) => write!(f, "rpc.discover at {}", it),
+        }
+    }
+}
+
+/// Options for [`SpecSource`]s which are fetched over the network.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct FetchOptions {
+    /// Add an HTTP header to network requests, as `key:value`.
+    #[arg(long = "header", global = true, value_parser = parse_header)]
+    pub headers: Vec<(String, String)>,
+    /// Cache documents fetched from the network in this directory, and don't
+    /// refetch them.
+    #[arg(long, global = true)]
+    pub cache: Option<PathBuf>,
+    /// Follow a component which is itself a `$ref` through at most this many
+    /// references.
+    #[arg(long, global = true, value_name = "N")]
+    pub max_ref_depth: Option<usize>,
+}
+
+fn parse_header(s: &str) -> anyhow::Result<(String, String)> {
+    match s.split_once(':') {
+        Some((k, v)) if !k.trim().is_empty() => Ok((k.trim().to_owned(), v.trim().to_owned())),
+        _ => bail!("expected a header in the form `key:value`"),
+    }
+}
+
+/// Deserialize the JSON at `source`.
+///
+/// Errors distinguish between failing to reach `source`, `source` responding
+/// with a non-success status, and failing to parse the response.
+///
+/// Chained components in an OpenRPC document are [followed](chains::follow)
+/// first.
+pub fn load_document<T: DeserializeOwned>(
+    source: &SpecSource,
+    options: &FetchOptions,
+) -> anyhow::Result<T> {
+    let bytes = read(source, options)?;
+    let document = parse_document(source, &bytes.bytes, options)?;
+    bytes.cache()?;
+    Ok(document)
+}
+
+/// As [`load_document`], then [`resolve_within`](chains::resolve_within).
+///
+/// A `$ref` which doesn't exist is [located](diagnostic::locate_reference) in
+/// the text of `source`, which isn't kept afterwards.
+pub fn load_resolved(
+    source: &SpecSource,
+    options: &FetchOptions,
+) -> anyhow::Result<resolved::OpenRPC> {
+    let bytes = read(source, options)?;
+    let document = parse_document::<OpenRPC>(source, &bytes.bytes, options)?;
+    bytes.cache()?;
+    chains::resolve_within(document).map_err(|e| {
+        let e = anyhow::Error::new(e);
+        match std::str::from_utf8(&bytes.bytes) {
+            Ok(text) => diagnostic::locate_reference(e, &source.to_string(), text),
+            Err(_) => e,
+        }
+    })
+}
+
+/// As [`load_document`], with `bytes` already [`read`] from `source`.
+///
+/// The text is only parsed once, unless `T` can't be deserialized from it,
+/// when it is parsed again to point at where.
+pub fn parse_document<T: DeserializeOwned>(
+    source: &SpecSource,
+    bytes: &[u8],
+    options: &FetchOptions,
+) -> anyhow::Result<T> {
+    let Ok(mut document) = serde_json::from_slice::<Value>(bytes) else {
+        return deserialize_text(source, bytes);
+    };
+    let followed = follow_chains(&mut document, options)
+        .with_context(|| format!("couldn't resolve references in {}", source))?;
+    match serde_path_to_error::deserialize(document) {
+        Ok(it) => Ok(it),
+        Err(_) if !followed => deserialize_text(source, bytes),
+        // the text doesn't match the document any more, so there's nowhere to
+        // point at
+        Err(e) => {
+            Err(anyhow::Error::new(e).context(format!("couldn't parse json from {}", source)))
+        }
+    }
+}
+
+/// Deserialize `T` straight from `bytes`, so that errors have a line and
+/// column.
+fn deserialize_text<T: DeserializeOwned>(source: &SpecSource, bytes: &[u8]) -> anyhow::Result<T> {
+    serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(bytes)).map_err(
+        |e| {
+            let (line, column) = (e.inner().line(), e.inner().column());
+            let excerpt = match (line, std::str::from_utf8(bytes)) {
+                (1.., Ok(text)) => {
+                    format!(
+                        "\n{}",
+                        diagnostic::excerpt(&source.to_string(), text, line, column)
+                    )
+                }
+                _ => String::new(),
+            };
+            anyhow::Error::new(e).context(format!("couldn't parse json from {}{}", source, excerpt))
+        },
+    )
+}
+
+/// As [`load_document`], also returning the JSON which `T` was parsed from,
+/// for the parts of a document which `T` doesn't model, like links.
+///
+/// `source` is only read once, so stdin works, and nothing is refetched.
+pub fn load_document_and_json<T: DeserializeOwned>(
+    source: &SpecSource,
+    options: &FetchOptions,
+) -> anyhow::Result<(T, Value)> {
+    let json = load_document::<Value>(source, options)?;
+    let document = serde_path_to_error::deserialize(&json)
+        .with_context(|| format!("couldn't parse json from {}", source))?;
+    Ok((document, json))
+}
+
+/// Follow chained components, if `document` is an OpenRPC document,
+/// returning whether it was rewritten.
+fn follow_chains(
+    document: &mut Value,
+    options: &FetchOptions,
+) -> Result<bool, chains::ResolveError> {
+    if document.get("openrpc").is_none() {
+        return Ok(false);
+    }
+    let max_depth = options.max_ref_depth.unwrap_or(chains::DEFAULT_MAX_DEPTH);
+    chains::follow(document, max_depth)
+}
+
+/// The raw bytes at a [`SpecSource`], from [`read`].
+#[must_use = "bytes fetched from the network are only cached by `Bytes::cache`"]
+pub struct Bytes {
+    pub bytes: Vec<u8>,
+    /// Where to cache `bytes`, if they were fetched.
+    cache: Option<PathBuf>,
+}
+
+impl Bytes {
+    /// Write the bytes to [`FetchOptions::cache`], if they were fetched.
+    ///
+    /// Call this once they have parsed, so that a response which isn't a
+    /// document isn't reused.
+    pub fn cache(&self) -> anyhow::Result<()> {
+        if let Some(path) = &self.cache {
+            fs::create_dir_all(path.parent().unwrap())
+                .and_then(|()| fs::write(path, &self.bytes))
+                .with_context(|| format!("couldn't write to cache file {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// The raw bytes at `source`, from [`FetchOptions::cache`] if they're there.
+pub fn read(source: &SpecSource, options: &FetchOptions) -> anyhow::Result<Bytes> {
+    let FetchOptions { headers, cache, .. } = options;
+    let cached = match (source, cache) {
+        (SpecSource::Url(_) | SpecSource::Discover(_), Some(dir)) => {
+            let mut hasher = Sha256::new();
+            hasher.update(source.to_string());
+            for (k, v) in headers {
+                hasher.update(format!("\n{k}:{v}"));
+            }
+            let path = dir.join(format!("{}.json", hex::encode(hasher.finalize())));
+            if let Ok(bytes) = fs::read(&path) {
+                return Ok(Bytes { bytes, cache: None });
+            }
+            Some(path)
+        }
+        _ => None,
+    };
+    let bytes = match source {
+        SpecSource::Path(path) => {
+            let mut bytes = vec![];
+            File::open(path)
+                .and_then(|mut it| it.read_to_end(&mut bytes))
+                .with_context(|| format!("couldn't read from {}", source))?;
+            bytes
+        }
+        SpecSource::Stdin => {
+            let mut bytes = vec![];
+            io::stdin()
+                .read_to_end(&mut bytes)
+                .with_context(|| format!("couldn't read from {}", source))?;
+            bytes
+        }
+        SpecSource::Url(url) => {
+            let request = headers
+                .iter()
+                .fold(ureq::get(url), |req, (k, v)| req.set(k, v));
+            body(request.call(), source)?
+        }
+        SpecSource::Discover(url) => {
+            let mut response = call(url, headers, "rpc.discover", json!([]))
+                .with_context(|| format!("couldn't fetch from {}", source))?;
+            if let Some(error) = response.get("error") {
+                bail!(RemoteError(format!(
+                    "{} returned an error: {}",
+                    source, error
+                )))
+            }
+            match response.get_mut("result") {
+                Some(result) => serde_json::to_vec(&result.take()).unwrap(),
+                None => bail!(RemoteError(format!(
+                    "{} returned neither a result nor an error",
+                    source
+                ))),
+            }
+        }
+    };
+    Ok(Bytes {
+        bytes,
+        cache: cached,
+    })
+}
+
+/// A remote was reached, but didn't respond with what was asked for.
+#[derive(Debug)]
+pub struct RemoteError(pub String);
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for RemoteError {}
+
+/// Make a JSON-RPC request to `url`, returning the whole response object.
+pub fn call(
+    url: &str,
+    headers: &[(String, String)],
+    method: &str,
+    params: Value,
+) -> anyhow::Result<Value> {
+    let request = headers
+        .iter()
+        .fold(ureq::post(url), |req, (k, v)| req.set(k, v));
+    let response = body(
+        request.set("Content-Type", "application/json").send_string(
+            &json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "method": method,
+                "params": params
+            })
+            .to_string(),
+        ),
+        url,
+    )?;
+    serde_json::from_slice(&response).with_context(|| format!("couldn't parse json from {}", url))
+}
+
+fn body(
+    response: Result<ureq::Response, ureq::Error>,
+    source: impl fmt::Display,
+) -> anyhow::Result<Vec<u8>> {
+    match response {
+        Ok(response) => {
+            let mut bytes = vec![];
+            response
+                .into_reader()
+                .read_to_end(&mut bytes)
+                .with_context(|| format!("network error reading from {}", source))?;
+            Ok(bytes)
+        }
+        Err(ureq::Error::Status(status, response)) => bail!(RemoteError(format!(
+            "{} responded with HTTP status {} {}",
+            source,
+            status,
+            response.status_text()
+        ))),
+        Err(ureq::Error::Transport(transport)) => {
+            Err(transport).with_context(|| format!("network error fetching {}", source))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead as _, BufReader, Write as _},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    /// Respond to one request with `body`, returning the url to request.
+    fn serve(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/spec.json", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > "\r\n".len() {
+                line.clear()
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap()
+        });
+        url
+    }
+
+    #[test]
+    fn cached_once_parsed() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = FetchOptions {
+            cache: Some(dir.path().to_owned()),
+            ..FetchOptions::default()
+        };
+        let cached = || fs::read_dir(dir.path()).unwrap().count();
+
+        let source = SpecSource::Url(serve("<html>"));
+        assert!(load_document::<Value>(&source, &options).is_err());
+        assert_eq!(cached(), 0);
+
+        let source = SpecSource::Url(serve(r#"{ "title": "spec" }"#));
+        let document = load_document::<Value>(&source, &options).unwrap();
+        assert_eq!(document, json!({ "title": "spec" }));
+        assert_eq!(cached(), 1);
+        // served from the cache, since the server only responds once
+        assert_eq!(load_document::<Value>(&source, &options).unwrap(), document);
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_text() {
+        let text = b"{\n  \"openrpc\": \"1.3.2\",\n  \"info\": 1\n}";
+        let error = parse_document::<OpenRPC>(&SpecSource::Stdin, text, &FetchOptions::default())
+            .unwrap_err();
+        let message = format!("{:?}", error);
+        assert!(message.contains("--> stdin:3:"), "{}", message);
+    }
+}
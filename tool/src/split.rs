@@ -0,0 +1,342 @@
+//! Maintain a document as several smaller documents, and join them back
+//! together.
+//!
+//! Each part contains a group of methods, and only the component schemas
+//! they need.
+//! Schemas needed by more than one group are either duplicated into each
+//! part, or moved into a shared `common.json`, which parts reference as
+//! `common.json#/components/schemas/<key>`.
+//!
+//! A `manifest.json` lists the parts, and is the input to [`join`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::bail;
+use openrpc_types::{resolved, Components, OpenRPC, ReferenceOr};
+use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
+use schemars::schema::Schema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    component_ref::{ComponentRef, ComponentSection},
+    reference_index::ReferenceIndex,
+};
+
+pub const MANIFEST: &str = "manifest.json";
+pub const COMMON: &str = "common.json";
+const UNGROUPED: &str = "ungrouped";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum By {
+    /// The first tag of each method.
+    #[default]
+    Tag,
+    /// The leading word of the method name after any `.`, e.g `Chain` for
+    /// `Filecoin.ChainHead`.
+    Prefix,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub parts: Vec<Part>,
+    /// The file containing schemas shared between parts, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub common: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Part {
+    pub group: String,
+    /// Relative to the manifest.
+    pub file: String,
+    pub methods: Vec<String>,
+}
+
+/// The manifest, and the documents it lists, by file name.
+pub fn split(
+    document: resolved::OpenRPC,
+    by: By,
+    duplicate_shared: bool,
+) -> anyhow::Result<(Manifest, BTreeMap<String, resolved::OpenRPC>)> {
+    let needs = {
+        let index = ReferenceIndex::new(&document);
+        let mut groups = BTreeMap::<String, Vec<&str>>::new();
+        for method in &document.methods {
+            groups
+                .entry(group(method, by))
+                .or_default()
+                .push(&method.name)
+        }
+        let mut needs = BTreeMap::new();
+        for (group, methods) in groups {
+            let reachable = index.reachable_from_group(methods)?;
+            needs.insert(
+                group,
+                reachable
+                    .into_iter()
+                    .map(String::from)
+                    .collect::<BTreeSet<_>>(),
+            );
+        }
+        needs
+    };
+
+    let resolved::OpenRPC {
+        openrpc,
+        info,
+        servers,
+        methods,
+        components,
+        external_docs,
+        extensions,
+    } = document;
+    let schemas = components.and_then(|it| it.schemas).unwrap_or_default();
+
+    let mut groups = BTreeMap::<String, Vec<resolved::Method>>::new();
+    for method in methods {
+        groups.entry(group(&method, by)).or_default().push(method)
+    }
+
+    let shared = match duplicate_shared {
+        true => BTreeSet::new(),
+        false => schemas
+            .keys()
+            .filter(|key| needs.values().filter(|it| it.contains(*key)).count() > 1)
+            .cloned()
+            .collect::<BTreeSet<_>>(),
+    };
+
+    let document = |methods, schemas: BTreeMap<String, Schema>| resolved::OpenRPC {
+        openrpc: openrpc.clone(),
+        info: info.clone(),
+        servers: servers.clone(),
+        methods,
+        components: (!schemas.is_empty()).then(|| Components {
+            schemas: Some(schemas),
+            ..Components::default()
+        }),
+        external_docs: external_docs.clone(),
+        extensions: extensions.clone(),
+    };
+
+    let mut manifest = Manifest {
+        parts: vec![],
+        common: None,
+    };
+    let mut files = BTreeMap::new();
+    for (group, mut methods) in groups {
+        let file = format!("{}.json", file_stem(&group));
+        if file == COMMON || file == MANIFEST || files.contains_key(&file) {
+            bail!(
+                "group {} would be written to {}, which is already used",
+                group,
+                file
+            )
+        }
+        for method in &mut methods {
+            for it in method.params.iter_mut().chain(&mut method.result) {
+                it.schema = externalize(&it.schema, &shared)
+            }
+        }
+        let part_schemas = schemas
+            .iter()
+            .filter(|(key, _)| needs[&group].contains(*key) && !shared.contains(*key))
+            .map(|(key, schema)| (key.clone(), externalize(schema, &shared)))
+            .collect();
+        manifest.parts.push(Part {
+            group,
+            file: file.clone(),
+            methods: methods.iter().map(|it| it.name.clone()).collect(),
+        });
+        files.insert(file, document(methods, part_schemas));
+    }
+    if !shared.is_empty() {
+        manifest.common = Some(String::from(COMMON));
+        files.insert(
+            String::from(COMMON),
+            document(
+                vec![],
+                schemas
+                    .into_iter()
+                    .filter(|(key, _)| shared.contains(key))
+                    .collect(),
+            ),
+        );
+    }
+    Ok((manifest, files))
+}
+
+/// Reassemble the documents in a [`Manifest`], which are loaded by `load`.
+///
+/// Top-level fields other than `methods` and `components` are taken from the
+/// first part.
+///
+/// Documents are loaded in parallel, but joined in manifest order, so
+/// conflicts are reported deterministically.
+pub fn join(
+    manifest: &Manifest,
+    load: impl Fn(&str) -> anyhow::Result<OpenRPC> + Sync,
+) -> anyhow::Result<OpenRPC> {
+    if manifest.parts.is_empty() {
+        bail!("the manifest has no parts")
+    }
+    let files = manifest
+        .common
+        .iter()
+        .chain(manifest.parts.iter().map(|it| &it.file))
+        .collect::<Vec<_>>();
+    let documents = files
+        .par_iter()
+        .map(|it| load(it))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let mut joined = OpenRPC {
+        methods: vec![],
+        components: None,
+        ..documents[usize::from(manifest.common.is_some())].clone()
+    };
+    let mut schemas = BTreeMap::new();
+    for (file, document) in files.into_iter().zip(documents) {
+        let OpenRPC {
+            methods,
+            components,
+            ..
+        } = document;
+        joined.methods.extend(methods);
+        for (key, schema) in components.and_then(|it| it.schemas).unwrap_or_default() {
+            let schema = internalize(&schema);
+            match schemas.get(&key) {
+                Some(existing) if *existing != schema => {
+                    bail!("schema {} in {} conflicts with another part", key, file)
+                }
+                _ => {
+                    schemas.insert(key, schema);
+                }
+            }
+        }
+    }
+    for method in &mut joined.methods {
+        if let ReferenceOr::Item(method) = method {
+            for it in method.params.iter_mut().chain(&mut method.result) {
+                if let ReferenceOr::Item(it) = it {
+                    it.schema = internalize(&it.schema)
+                }
+            }
+        }
+    }
+    if !schemas.is_empty() {
+        joined.components = Some(Components {
+            schemas: Some(schemas),
+            ..Components::default()
+        })
+    }
+    Ok(joined)
+}
+
+fn group(method: &resolved::Method, by: By) -> String {
+    match by {
+        By::Tag => method
+            .tags
+            .iter()
+            .flatten()
+            .next()
+            .map(|it| it.name.clone()),
+        By::Prefix => {
+            let name = method.name.rsplit('.').next().unwrap_or_default();
+            let end = name
+                .char_indices()
+                .skip(1)
+                .find(|(_, c)| c.is_ascii_uppercase())
+                .map_or(name.len(), |(ix, _)| ix);
+            Some(name[..end].to_owned()).filter(|it| !it.is_empty())
+        }
+    }
+    .unwrap_or_else(|| String::from(UNGROUPED))
+}
+
+fn file_stem(group: &str) -> String {
+    group
+        .chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect()
+}
+
+/// Point `$ref`s to `shared` schemas at [`COMMON`].
+fn externalize(schema: &Schema, shared: &BTreeSet<String>) -> Schema {
+    rewrite(schema, |reference| {
+        let parsed = ComponentRef::parse(reference);
+        shared
+            .contains(parsed.key_in(ComponentSection::Schemas)?)
+            .then(|| format!("{}{}", COMMON, parsed))
+    })
+}
+
+/// The inverse of [`externalize`].
+fn internalize(schema: &Schema) -> Schema {
+    rewrite(schema, |reference| {
+        reference.strip_prefix(COMMON).map(str::to_owned)
+    })
+}
+
+fn rewrite(schema: &Schema, f: impl Fn(&str) -> Option<String>) -> Schema {
+    fn imp(value: &mut Value, f: &impl Fn(&str) -> Option<String>) {
+        match value {
+            Value::Object(it) => {
+                for (key, value) in it {
+                    match (key.as_str(), &value) {
+                        ("$ref", Value::String(reference)) => {
+                            if let Some(new) = f(reference) {
+                                *value = Value::String(new)
+                            }
+                        }
+                        _ => imp(value, f),
+                    }
+                }
+            }
+            Value::Array(it) => {
+                for it in it {
+                    imp(it, f)
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut value = serde_json::to_value(schema).unwrap();
+    imp(&mut value, &f);
+    serde_json::from_value(value).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chains::resolve_within, openrpc_diff, release};
+
+    #[test]
+    fn round_trip() {
+        let spec = serde_json::from_str::<OpenRPC>(include_str!("../../spec.json")).unwrap();
+        for by in [By::Tag, By::Prefix] {
+            for duplicate_shared in [false, true] {
+                let (manifest, files) =
+                    split(resolve_within(spec.clone()).unwrap(), by, duplicate_shared).unwrap();
+                assert_eq!(manifest.common.is_none(), duplicate_shared);
+                let joined = join(&manifest, |file| {
+                    Ok(serde_json::from_value(serde_json::to_value(&files[file])?)?)
+                })
+                .unwrap();
+                let summary = openrpc_diff::diff(spec.clone(), joined).unwrap();
+                assert!(
+                    release::is_empty(&summary),
+                    "{:?} {}: {}",
+                    by,
+                    duplicate_shared,
+                    serde_json::to_string(&summary).unwrap()
+                );
+            }
+        }
+    }
+}
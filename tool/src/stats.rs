@@ -0,0 +1,206 @@
+//! Summary numbers for a document.
+//!
+//! - Schema size is the length of the schema's compact JSON serialization.
+//! - Schema depth counts nested subschemas (see [`schema_visit::children`]),
+//!   starting at `1`, and doesn't follow `$ref`s.
+//! - `refs` counts `$ref`s within schemas, in methods, component content
+//!   descriptors and component schemas.
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use itertools::Itertools as _;
+use openrpc_types::resolved;
+use schemars::schema::Schema;
+use serde::Serialize;
+
+use crate::schema_visit::{self, Visitor};
+
+const LARGEST: usize = 5;
+
+#[derive(Debug, Default, Serialize)]
+pub struct Stats {
+    pub methods: usize,
+    pub methods_per_tag: BTreeMap<String, usize>,
+    /// Maps a number of params to the number of methods with that many.
+    pub params_per_method: BTreeMap<usize, usize>,
+    pub with_examples: usize,
+    pub without_examples: usize,
+    pub deprecated_methods: usize,
+    pub deprecated_params: usize,
+    pub schemas: usize,
+    pub schemas_size: usize,
+    pub refs: usize,
+    pub max_depth: usize,
+    /// The largest component schemas, and their sizes.
+    pub largest_schemas: Vec<(String, usize)>,
+}
+
+pub fn stats(document: &resolved::OpenRPC) -> Stats {
+    let mut stats = Stats {
+        methods: document.methods.len(),
+        ..Stats::default()
+    };
+    for method in &document.methods {
+        for tag in method.tags.iter().flatten() {
+            *stats.methods_per_tag.entry(tag.name.clone()).or_default() += 1
+        }
+        *stats
+            .params_per_method
+            .entry(method.params.len())
+            .or_default() += 1;
+        match method.examples.as_ref().is_some_and(|it| !it.is_empty()) {
+            true => stats.with_examples += 1,
+            false => stats.without_examples += 1,
+        }
+        if method.deprecated.unwrap_or_default() {
+            stats.deprecated_methods += 1
+        }
+        for descriptor in method.params.iter().chain(&method.result) {
+            if descriptor.deprecated.unwrap_or_default() {
+                stats.deprecated_params += 1
+            }
+        }
+    }
+    let schemas = document
+        .components
+        .iter()
+        .flat_map(|it| it.schemas.iter().flatten())
+        .map(|(key, schema)| (key.clone(), serde_json::to_string(schema).unwrap().len()))
+        .collect::<Vec<_>>();
+    schema_visit::walk_document(document, &mut stats);
+    stats.schemas = schemas.len();
+    stats.schemas_size = schemas.iter().map(|(_, size)| size).sum();
+    stats.largest_schemas = schemas
+        .into_iter()
+        .sorted_by(|(lk, ls), (rk, rs)| rs.cmp(ls).then(lk.cmp(rk)))
+        .take(LARGEST)
+        .collect();
+    stats
+}
+
+impl Visitor<'_> for Stats {
+    fn schema(&mut self, _: &Schema, depth: usize) {
+        self.max_depth = self.max_depth.max(depth)
+    }
+    fn reference(&mut self, _: &str, _: &str) {
+        self.refs += 1
+    }
+}
+
+/// Human-readable `stats`.
+pub fn render(stats: &Stats) -> String {
+    let Stats {
+        methods,
+        methods_per_tag,
+        params_per_method,
+        with_examples,
+        without_examples,
+        deprecated_methods,
+        deprecated_params,
+        schemas,
+        schemas_size,
+        refs,
+        max_depth,
+        largest_schemas,
+    } = stats;
+    let mut out = String::new();
+    writeln!(out, "methods: {methods}").unwrap();
+    for (tag, count) in methods_per_tag {
+        writeln!(out, "  tagged {tag}: {count}").unwrap();
+    }
+    for (params, count) in params_per_method {
+        writeln!(out, "  with {params} params: {count}").unwrap();
+    }
+    writeln!(out, "  with examples: {with_examples}").unwrap();
+    writeln!(out, "  without examples: {without_examples}").unwrap();
+    writeln!(out, "  deprecated: {deprecated_methods}").unwrap();
+    writeln!(out, "deprecated params: {deprecated_params}").unwrap();
+    writeln!(out, "component schemas: {schemas}").unwrap();
+    writeln!(out, "  total size: {schemas_size}").unwrap();
+    for (key, size) in largest_schemas {
+        writeln!(out, "  {key}: {size}").unwrap();
+    }
+    writeln!(out, "refs: {refs}").unwrap();
+    writeln!(out, "max schema depth: {max_depth}").unwrap();
+    out
+}
+
+/// Human-readable changes from `before` to `after`, omitting anything which
+/// is unchanged.
+pub fn render_delta(before: &Stats, after: &Stats) -> String {
+    let mut out = String::new();
+    let mut line = |what: &str, before: usize, after: usize| {
+        if before != after {
+            writeln!(
+                out,
+                "{what}: {before} -> {after} ({:+})",
+                after as i128 - before as i128
+            )
+            .unwrap()
+        }
+    };
+    line("methods", before.methods, after.methods);
+    for tag in before
+        .methods_per_tag
+        .keys()
+        .chain(after.methods_per_tag.keys())
+        .unique()
+        .sorted()
+    {
+        line(
+            &format!("methods tagged {tag}"),
+            before.methods_per_tag.get(tag).copied().unwrap_or_default(),
+            after.methods_per_tag.get(tag).copied().unwrap_or_default(),
+        )
+    }
+    for params in before
+        .params_per_method
+        .keys()
+        .chain(after.params_per_method.keys())
+        .unique()
+        .sorted()
+    {
+        line(
+            &format!("methods with {params} params"),
+            before
+                .params_per_method
+                .get(params)
+                .copied()
+                .unwrap_or_default(),
+            after
+                .params_per_method
+                .get(params)
+                .copied()
+                .unwrap_or_default(),
+        )
+    }
+    line(
+        "methods with examples",
+        before.with_examples,
+        after.with_examples,
+    );
+    line(
+        "methods without examples",
+        before.without_examples,
+        after.without_examples,
+    );
+    line(
+        "deprecated methods",
+        before.deprecated_methods,
+        after.deprecated_methods,
+    );
+    line(
+        "deprecated params",
+        before.deprecated_params,
+        after.deprecated_params,
+    );
+    line("component schemas", before.schemas, after.schemas);
+    line(
+        "component schemas size",
+        before.schemas_size,
+        after.schemas_size,
+    );
+    line("refs", before.refs, after.refs);
+    line("max schema depth", before.max_depth, after.max_depth);
+    out
+}
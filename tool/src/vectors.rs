@@ -0,0 +1,720 @@
+//! Random JSON-RPC requests for differential testing.
+//!
+//! Params are generated from their schemas, respecting `const`, `enum`,
+//! `pattern`, numeric bounds, lengths, `required` and `minItems`/`maxItems`.
+//! Generation is deterministic for a given seed.
+//!
+//! Every request is checked against the method's [request envelope](crate::envelopes):
+//! valid vectors must pass, and invalid vectors must fail, with only the error
+//! for the constraint they are annotated with.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context as _};
+use jsonschema::{Draft, JSONSchema};
+use openrpc_types::{resolved, ParamStructure};
+use rand::{seq::SliceRandom as _, Rng as _, SeedableRng as _};
+use rand_chacha::ChaCha8Rng;
+use schemars::schema::{
+    ArrayValidation, InstanceType, NumberValidation, ObjectValidation, Schema, SchemaObject,
+    SingleOrVec, StringValidation,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Number, Value};
+
+use crate::{
+    component_ref::{ComponentRef, ComponentSection},
+    envelopes, scaffold,
+};
+
+/// Beyond this many nested schemas, values are [`scaffold::placeholder`]s,
+/// so recursive schemas terminate.
+const MAX_DEPTH: usize = 6;
+/// How many times to regenerate a vector before giving up.
+const ATTEMPTS: usize = 32;
+/// Characters for unconstrained strings, including some awkward ones.
+const ALPHABET: &[char] = &[
+    'a', 'b', 'z', 'A', 'Z', '0', '9', ' ', '-', '_', '.', '/', '"', '\\', 'é', '✓', '🦀',
+];
+
+/// A line of output.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Vector {
+    Valid(Value),
+    Invalid { request: Value, violates: Violation },
+}
+
+/// The single constraint an invalid vector violates.
+#[derive(Debug, Serialize)]
+pub struct Violation {
+    pub param: String,
+    /// A JSON pointer into the param, or absent if the param is missing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    /// The JSON Schema keyword, or `required` for a missing param.
+    pub constraint: &'static str,
+}
+
+pub struct Generator<'a> {
+    document: &'a resolved::OpenRPC,
+    schemas: BTreeMap<String, Schema>,
+    envelopes: BTreeMap<String, envelopes::Envelopes>,
+    rng: ChaCha8Rng,
+    id: u64,
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(document: &'a resolved::OpenRPC, seed: u64) -> anyhow::Result<Self> {
+        Ok(Self {
+            document,
+            schemas: document
+                .components
+                .as_ref()
+                .and_then(|it| it.schemas.clone())
+                .unwrap_or_default(),
+            envelopes: envelopes::envelopes(document)?,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            id: 0,
+        })
+    }
+
+    /// `count` vectors for each method whose name matches one of `patterns`,
+    /// or every method if there are none.
+    pub fn generate(
+        &mut self,
+        patterns: &[String],
+        count: usize,
+        invalid: bool,
+    ) -> anyhow::Result<Vec<Vector>> {
+        let mut vectors = vec![];
+        let document = self.document;
+        for method in document.methods.iter().filter(|method| {
+            patterns.is_empty()
+                || patterns
+                    .iter()
+                    .any(|it| crate::glob::matches(it, &method.name))
+        }) {
+            let validator = JSONSchema::options()
+                .with_draft(Draft::Draft202012)
+                .compile(&self.envelopes[&method.name].request)
+                .map_err(|it| anyhow::anyhow!("{}", it))?;
+            for _ in 0..count {
+                let vector = match invalid {
+                    false => self.valid(method, &validator),
+                    true => self.invalid(method, &validator),
+                }
+                .with_context(|| format!("couldn't generate a vector for {}", method.name))?;
+                if let Some(mut vector) = vector {
+                    self.id += 1;
+                    let (Vector::Valid(request) | Vector::Invalid { request, .. }) = &mut vector;
+                    request["id"] = Value::from(self.id);
+                    vectors.push(vector)
+                }
+            }
+        }
+        Ok(vectors)
+    }
+
+    /// The [envelopes](crate::envelopes) of each method, which vectors are
+    /// checked against.
+    pub fn envelopes(&self) -> &BTreeMap<String, envelopes::Envelopes> {
+        &self.envelopes
+    }
+
+    /// A result for `method`, which `validator`, compiled from its response
+    /// envelope, accepts.
+    pub fn result(
+        &mut self,
+        method: &resolved::Method,
+        validator: &JSONSchema,
+    ) -> anyhow::Result<Value> {
+        for _ in 0..ATTEMPTS {
+            let result = match &method.result {
+                Some(it) => self.value(&it.schema, 0)?,
+                None => Value::Null,
+            };
+            if validator.is_valid(&json!({ "jsonrpc": "2.0", "id": 0, "result": result })) {
+                return Ok(result);
+            }
+        }
+        bail!("no valid result after {} attempts", ATTEMPTS)
+    }
+
+    fn valid(
+        &mut self,
+        method: &resolved::Method,
+        validator: &JSONSchema,
+    ) -> anyhow::Result<Option<Vector>> {
+        for _ in 0..ATTEMPTS {
+            let params = self.params(method)?;
+            let request = self.request(method, Value::from(params));
+            if validator.is_valid(&request) {
+                return Ok(Some(Vector::Valid(request)));
+            }
+        }
+        bail!("no valid params after {} attempts", ATTEMPTS)
+    }
+
+    /// [`None`] if the method has no constraint that can be violated.
+    fn invalid(
+        &mut self,
+        method: &resolved::Method,
+        validator: &JSONSchema,
+    ) -> anyhow::Result<Option<Vector>> {
+        if method.params.is_empty() {
+            return Ok(None);
+        }
+        for _ in 0..ATTEMPTS {
+            let params = self.params(method)?;
+            let mut candidates = vec![];
+            for (ix, (param, value)) in method.params.iter().zip(&params).enumerate() {
+                if param.required.unwrap_or_default() {
+                    candidates.push((ix, None, "required", None))
+                }
+                for (pointer, constraint, value) in self.mutations(&param.schema, value, 0) {
+                    candidates.push((ix, Some(pointer), constraint, Some(value)))
+                }
+            }
+            candidates.shuffle(&mut self.rng);
+            for (ix, pointer, constraint, value) in candidates {
+                let mut params = params.clone();
+                match (&pointer, value) {
+                    (Some(pointer), Some(value)) => match params[ix].pointer_mut(pointer) {
+                        Some(it) => *it = value,
+                        None => continue,
+                    },
+                    // A missing param is all those after it missing too.
+                    _ => params.truncate(ix),
+                }
+                let request = self.request(method, Value::from(params));
+                let (instance, keyword) =
+                    expected_error(method, ix, pointer.as_deref(), constraint);
+                if violates_only(validator, &request, &instance, keyword) {
+                    return Ok(Some(Vector::Invalid {
+                        request,
+                        violates: Violation {
+                            param: method.params[ix].name.clone(),
+                            pointer,
+                            constraint,
+                        },
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Positional params, with a random number of trailing optional params.
+    fn params(&mut self, method: &resolved::Method) -> anyhow::Result<Vec<Value>> {
+        let min = method
+            .params
+            .iter()
+            .rposition(|it| it.required.unwrap_or_default())
+            .map_or(0, |it| it + 1);
+        let len = self.rng.gen_range(min..=method.params.len());
+        method.params[..len]
+            .iter()
+            .map(|it| self.value(&it.schema, 0))
+            .collect()
+    }
+
+    /// The id is filled in once the request is accepted.
+    fn request(&self, method: &resolved::Method, params: Value) -> Value {
+        let params = match (method.param_structure, params) {
+            (Some(ParamStructure::ByName), Value::Array(it)) => Value::Object(
+                method
+                    .params
+                    .iter()
+                    .map(|it| it.name.clone())
+                    .zip(it)
+                    .collect(),
+            ),
+            (_, it) => it,
+        };
+        json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": method.name,
+            "params": params,
+        })
+    }
+
+    fn value(&mut self, schema: &Schema, depth: usize) -> anyhow::Result<Value> {
+        if depth > MAX_DEPTH {
+            return Ok(scaffold::placeholder(schema, &self.schemas));
+        }
+        let object = match schema {
+            Schema::Bool(true) => return Ok(self.scalar()),
+            Schema::Bool(false) => bail!("a schema admits no values"),
+            Schema::Object(it) => it,
+        };
+        let SchemaObject {
+            instance_type,
+            enum_values,
+            const_value,
+            subschemas,
+            number,
+            string,
+            array,
+            object,
+            reference,
+            ..
+        } = object;
+        if let Some(it) = const_value {
+            return Ok(it.clone());
+        }
+        if let Some(it) = enum_values.as_ref().and_then(|it| it.choose(&mut self.rng)) {
+            return Ok(it.clone());
+        }
+        if let Some(reference) = reference {
+            let target = self.target(reference)?;
+            return self.value(&target, depth + 1);
+        }
+        if let Some(subschemas) = subschemas {
+            if let Some(all) = &subschemas.all_of {
+                let mut merged = Map::new();
+                for it in all {
+                    match self.value(it, depth + 1)? {
+                        Value::Object(it) => merged.extend(it),
+                        other => return Ok(other),
+                    }
+                }
+                return Ok(Value::Object(merged));
+            }
+            if let Some(it) = [&subschemas.one_of, &subschemas.any_of]
+                .into_iter()
+                .flatten()
+                .next()
+                .and_then(|it| it.choose(&mut self.rng))
+            {
+                return self.value(it, depth + 1);
+            }
+        }
+        let ty = match instance_type {
+            Some(SingleOrVec::Single(it)) => **it,
+            Some(SingleOrVec::Vec(it)) => match it.choose(&mut self.rng) {
+                Some(it) => *it,
+                None => bail!("a schema has an empty `type`"),
+            },
+            None if object.is_some() => InstanceType::Object,
+            None if array.is_some() => InstanceType::Array,
+            None if string.is_some() => InstanceType::String,
+            None if number.is_some() => InstanceType::Number,
+            None => return Ok(self.scalar()),
+        };
+        Ok(match ty {
+            InstanceType::Null => Value::Null,
+            InstanceType::Boolean => Value::Bool(self.rng.gen()),
+            InstanceType::Integer => self.integer(number.as_deref(), object_format(schema)),
+            InstanceType::Number => self.number(number.as_deref()),
+            InstanceType::String => self.string(string.as_deref())?,
+            InstanceType::Array => self.array(array.as_deref(), depth)?,
+            InstanceType::Object => self.object(object.as_deref(), depth)?,
+        })
+    }
+
+    fn scalar(&mut self) -> Value {
+        match self.rng.gen_range(0..4) {
+            0 => Value::Null,
+            1 => Value::Bool(self.rng.gen()),
+            2 => self.integer(None, None),
+            _ => self.string(None).unwrap_or_default(),
+        }
+    }
+
+    /// Bounds come from the schema, then the Go type in `format`, then `i64`.
+    /// Boundary values are favoured.
+    fn integer(&mut self, number: Option<&NumberValidation>, format: Option<&str>) -> Value {
+        let (mut lo, mut hi) = match format {
+            Some("uint8") => (0, u8::MAX as i128),
+            Some("uint16") => (0, u16::MAX as i128),
+            Some("uint32") => (0, u32::MAX as i128),
+            Some("uint64") => (0, u64::MAX as i128),
+            Some("int8") => (i8::MIN as i128, i8::MAX as i128),
+            Some("int16") => (i16::MIN as i128, i16::MAX as i128),
+            Some("int32") => (i32::MIN as i128, i32::MAX as i128),
+            _ => (i64::MIN as i128, i64::MAX as i128),
+        };
+        if let Some(NumberValidation {
+            maximum,
+            exclusive_maximum,
+            minimum,
+            exclusive_minimum,
+            ..
+        }) = number
+        {
+            if let Some(it) = minimum {
+                lo = lo.max(it.ceil() as i128)
+            }
+            if let Some(it) = exclusive_minimum {
+                lo = lo.max(it.floor() as i128 + 1)
+            }
+            if let Some(it) = maximum {
+                hi = hi.min(it.floor() as i128)
+            }
+            if let Some(it) = exclusive_maximum {
+                hi = hi.min(it.ceil() as i128 - 1)
+            }
+        }
+        let it = match self.rng.gen_range(0..8) {
+            0 => lo,
+            1 => hi,
+            2 => 0.clamp(lo, hi.max(lo)),
+            _ if hi > lo => self.rng.gen_range(lo..=hi),
+            _ => lo,
+        };
+        match (i64::try_from(it), u64::try_from(it)) {
+            (Ok(it), _) => Value::from(it),
+            (_, Ok(it)) => Value::from(it),
+            _ => Value::from(0),
+        }
+    }
+
+    fn number(&mut self, number: Option<&NumberValidation>) -> Value {
+        let lo = number
+            .and_then(|it| it.minimum.or(it.exclusive_minimum))
+            .unwrap_or(-1e9);
+        let hi = number
+            .and_then(|it| it.maximum.or(it.exclusive_maximum))
+            .unwrap_or(1e9);
+        let it = match hi > lo {
+            true => self.rng.gen_range(lo..hi),
+            false => lo,
+        };
+        Number::from_f64(it).map_or(Value::Null, Value::Number)
+    }
+
+    fn string(&mut self, string: Option<&StringValidation>) -> anyhow::Result<Value> {
+        if let Some(pattern) = string.and_then(|it| it.pattern.as_ref()) {
+            // Patterns are unanchored, so a string matching the whole pattern
+            // matches with or without anchors.
+            let mut inner = pattern.strip_prefix('^').unwrap_or(pattern);
+            if !inner.ends_with("\\$") {
+                inner = inner.strip_suffix('$').unwrap_or(inner)
+            }
+            let generator = rand_regex::Regex::compile(inner, 8)
+                .with_context(|| format!("unsupported pattern {}", pattern))?;
+            return Ok(Value::String(self.rng.sample(&generator)));
+        }
+        let min = string.and_then(|it| it.min_length).unwrap_or_default() as usize;
+        let max = string
+            .and_then(|it| it.max_length)
+            .map_or(min + 16, |it| it as usize);
+        let len = self.rng.gen_range(min..=max.max(min));
+        Ok(Value::String(
+            (0..len)
+                .map(|_| *ALPHABET.choose(&mut self.rng).unwrap())
+                .collect(),
+        ))
+    }
+
+    fn array(&mut self, array: Option<&ArrayValidation>, depth: usize) -> anyhow::Result<Value> {
+        let min = array.and_then(|it| it.min_items).unwrap_or_default() as usize;
+        let max = array
+            .and_then(|it| it.max_items)
+            .map_or(min + 3, |it| it as usize);
+        let len = match depth < MAX_DEPTH {
+            true => self.rng.gen_range(min..=max.max(min)),
+            false => min,
+        };
+        Ok(Value::Array(match array.and_then(|it| it.items.as_ref()) {
+            Some(SingleOrVec::Single(it)) => (0..len)
+                .map(|_| self.value(it, depth + 1))
+                .collect::<Result<_, _>>()?,
+            Some(SingleOrVec::Vec(it)) => it
+                .iter()
+                .map(|it| self.value(it, depth + 1))
+                .collect::<Result<_, _>>()?,
+            None => (0..len).map(|_| self.scalar()).collect(),
+        }))
+    }
+
+    /// Required properties, and each optional property with even odds.
+    fn object(&mut self, object: Option<&ObjectValidation>, depth: usize) -> anyhow::Result<Value> {
+        let Some(ObjectValidation {
+            required,
+            properties,
+            additional_properties,
+            ..
+        }) = object
+        else {
+            return Ok(Value::Object(Map::new()));
+        };
+        let mut map = Map::new();
+        for (key, schema) in properties {
+            if required.contains(key) || (depth < MAX_DEPTH && self.rng.gen()) {
+                map.insert(key.clone(), self.value(schema, depth + 1)?);
+            }
+        }
+        for key in required {
+            if !map.contains_key(key) {
+                let value = match additional_properties {
+                    Some(it) => self.value(it, depth + 1)?,
+                    None => self.scalar(),
+                };
+                map.insert(key.clone(), value);
+            }
+        }
+        Ok(Value::Object(map))
+    }
+
+    /// Changes to `value` which each violate a single constraint in `schema`,
+    /// as a pointer into `value`, the keyword, and the replacement.
+    fn mutations(
+        &mut self,
+        schema: &Schema,
+        value: &Value,
+        depth: usize,
+    ) -> Vec<(String, &'static str, Value)> {
+        let mut mutations = vec![];
+        let Schema::Object(object) = schema else {
+            return mutations;
+        };
+        if depth > MAX_DEPTH {
+            return mutations;
+        }
+        if let Some(reference) = &object.reference {
+            return match self.target(reference) {
+                Ok(target) => self.mutations(&target, value, depth + 1),
+                Err(_) => mutations,
+            };
+        }
+        if object.subschemas.is_some() {
+            // Which branch a value violates is ambiguous.
+            return mutations;
+        }
+        let SchemaObject {
+            instance_type,
+            enum_values,
+            const_value,
+            number,
+            string,
+            array,
+            object,
+            ..
+        } = object;
+        let mut push = |constraint, value| mutations.push((String::new(), constraint, value));
+        if let Some(SingleOrVec::Single(ty)) = instance_type {
+            push(
+                "type",
+                match **ty {
+                    InstanceType::Null | InstanceType::Boolean => json!("true"),
+                    InstanceType::Integer => json!(0.5),
+                    InstanceType::Number => json!("1"),
+                    InstanceType::String => json!(1),
+                    InstanceType::Array => json!({}),
+                    InstanceType::Object => json!([]),
+                },
+            )
+        }
+        if let (Some(values), Value::String(it)) = (enum_values, value) {
+            let mut new = it.clone();
+            while values.contains(&Value::String(new.clone())) {
+                new.push('x')
+            }
+            push("enum", Value::String(new))
+        }
+        if let (Some(_), Value::String(it)) = (const_value, value) {
+            push("const", Value::String(format!("{}x", it)))
+        }
+        if let (Some(number), Value::Number(_)) = (number, value) {
+            if let Some(it) = number.minimum {
+                push("minimum", json!(it - 1.0))
+            }
+            if let Some(it) = number.maximum {
+                push("maximum", json!(it + 1.0))
+            }
+            if let Some(it) = number.exclusive_minimum {
+                push("exclusiveMinimum", json!(it))
+            }
+            if let Some(it) = number.exclusive_maximum {
+                push("exclusiveMaximum", json!(it))
+            }
+        }
+        if let (Some(string), Value::String(it)) = (string, value) {
+            if let Some(min) = string.min_length.filter(|it| *it > 0) {
+                push(
+                    "minLength",
+                    Value::String(it.chars().take(min as usize - 1).collect()),
+                )
+            }
+            if let Some(max) = string.max_length {
+                push("maxLength", Value::String("x".repeat(max as usize + 1)))
+            }
+            if string.pattern.is_some() {
+                push("pattern", Value::String(format!("{}\u{0}", it)))
+            }
+        }
+        if let (Some(array), Value::Array(it)) = (array, value) {
+            if let Some(min) = array.min_items.filter(|it| *it > 0) {
+                push(
+                    "minItems",
+                    Value::Array(it.iter().take(min as usize - 1).cloned().collect()),
+                )
+            }
+            if let (Some(max), Some(first)) = (array.max_items, it.first()) {
+                push(
+                    "maxItems",
+                    Value::Array(vec![first.clone(); max as usize + 1]),
+                )
+            }
+        }
+        if let (Some(object), Value::Object(it)) = (object, value) {
+            if let Some(key) = object.required.iter().find(|key| it.contains_key(*key)) {
+                let mut it = it.clone();
+                it.remove(key);
+                push("required", Value::Object(it))
+            }
+            if let (Some(Schema::Bool(false)), false) = (
+                object.additional_properties.as_deref(),
+                it.contains_key("unexpected"),
+            ) {
+                let mut it = it.clone();
+                it.insert(String::from("unexpected"), Value::Null);
+                push("additionalProperties", Value::Object(it))
+            }
+        }
+        if let (Some(array), Value::Array(it)) = (array, value) {
+            if let (Some(SingleOrVec::Single(schema)), Some(first)) = (&array.items, it.first()) {
+                for (pointer, constraint, value) in self.mutations(schema, first, depth + 1) {
+                    mutations.push((format!("/0{}", pointer), constraint, value))
+                }
+            }
+        }
+        if let (Some(object), Value::Object(it)) = (object, value) {
+            for (key, value) in it {
+                if let Some(schema) = object.properties.get(key) {
+                    let key = key.replace('~', "~0").replace('/', "~1");
+                    for (pointer, constraint, value) in self.mutations(schema, value, depth + 1) {
+                        mutations.push((format!("/{}{}", key, pointer), constraint, value))
+                    }
+                }
+            }
+        }
+        mutations
+    }
+
+    fn target(&self, reference: &str) -> anyhow::Result<Schema> {
+        match ComponentRef::parse(reference)
+            .key_in(ComponentSection::Schemas)
+            .and_then(|it| self.schemas.get(it))
+        {
+            Some(it) => Ok(it.clone()),
+            None => bail!("broken reference: {}", reference),
+        }
+    }
+}
+
+/// Where the error for an invalid vector is reported, and its keyword, or
+/// just the `anyOf` over both param structures if there is no
+/// `paramStructure`.
+fn expected_error(
+    method: &resolved::Method,
+    ix: usize,
+    pointer: Option<&str>,
+    constraint: &'static str,
+) -> (String, &'static str) {
+    let param = match method.param_structure {
+        Some(ParamStructure::ByPosition) => ix.to_string(),
+        Some(ParamStructure::ByName) => {
+            method.params[ix].name.replace('~', "~0").replace('/', "~1")
+        }
+        Some(ParamStructure::Either) | None => return (String::from("/params"), "anyOf"),
+    };
+    match (pointer, method.param_structure) {
+        (Some(pointer), _) => (format!("/params/{}{}", param, pointer), constraint),
+        (None, Some(ParamStructure::ByPosition)) => (String::from("/params"), "minItems"),
+        (None, _) => (String::from("/params"), "required"),
+    }
+}
+
+/// Whether `request` fails validation, only with `keyword` at `instance`.
+fn violates_only(validator: &JSONSchema, request: &Value, instance: &str, keyword: &str) -> bool {
+    match validator.validate(request) {
+        Ok(()) => false,
+        Err(mut errors) => errors.all(|it| {
+            it.instance_path.to_string() == instance
+                && it.schema_path.to_string().rsplit('/').next() == Some(keyword)
+        }),
+    }
+}
+
+fn object_format(schema: &Schema) -> Option<&str> {
+    match schema {
+        Schema::Object(it) => it.format.as_deref(),
+        Schema::Bool(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openrpc_types::OpenRPC;
+
+    use super::*;
+    use crate::chains::resolve_within;
+
+    fn spec() -> resolved::OpenRPC {
+        let spec = serde_json::from_str::<OpenRPC>(include_str!("../../spec.json")).unwrap();
+        resolve_within(spec).unwrap()
+    }
+
+    fn generate(document: &resolved::OpenRPC, seed: u64, invalid: bool) -> Value {
+        let vectors = Generator::new(document, seed)
+            .unwrap()
+            .generate(&[], 2, invalid)
+            .unwrap();
+        serde_json::to_value(vectors).unwrap()
+    }
+
+    #[test]
+    fn seeded() {
+        let spec = spec();
+        for invalid in [false, true] {
+            assert_eq!(generate(&spec, 1, invalid), generate(&spec, 1, invalid));
+            assert_ne!(generate(&spec, 1, invalid), generate(&spec, 2, invalid));
+        }
+    }
+
+    #[test]
+    fn invalid_violates_only_its_constraint() {
+        let spec = spec();
+        let mut generator = Generator::new(&spec, 0).unwrap();
+        let vectors = generator.generate(&[], 4, true).unwrap();
+        assert!(!vectors.is_empty());
+        for vector in vectors {
+            let Vector::Invalid { request, violates } = vector else {
+                panic!("a valid vector")
+            };
+            let method = spec
+                .methods
+                .iter()
+                .find(|it| it.name == request["method"])
+                .unwrap();
+            let ix = method
+                .params
+                .iter()
+                .position(|it| it.name == violates.param)
+                .unwrap();
+            // every method in the spec is by-position
+            let (instance, keyword) = match &violates.pointer {
+                Some(pointer) => (format!("/params/{}{}", ix, pointer), violates.constraint),
+                None => (String::from("/params"), "minItems"),
+            };
+            let validator = JSONSchema::options()
+                .with_draft(Draft::Draft202012)
+                .compile(&generator.envelopes()[&method.name].request)
+                .unwrap();
+            let errors = match validator.validate(&request) {
+                Ok(()) => panic!("{} is valid", request),
+                Err(errors) => errors
+                    .map(|it| (it.instance_path.to_string(), it.schema_path.to_string()))
+                    .collect::<Vec<_>>(),
+            };
+            for (at, schema) in errors {
+                assert_eq!(at, instance, "{}", request);
+                assert!(schema.ends_with(&format!("/{}", keyword)), "{}", request);
+            }
+        }
+    }
+}
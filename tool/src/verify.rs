@@ -0,0 +1,300 @@
+//! Check the examples in a document against a live node.
+//!
+//! The params of each example pairing are sent to the node, and the result is
+//! validated against the method's [response envelope](crate::envelopes), and
+//! compared to the example's result.
+
+use std::fmt;
+
+use anyhow::Context as _;
+use jsonschema::{Draft, JSONSchema};
+use openrpc_types::{resolved, ParamStructure};
+use serde_json::Value;
+
+use crate::{envelopes, glob, progress, source};
+
+/// Methods with this tag or extension change the node's state, so are skipped.
+pub const MUTATING: &str = "x-mutating";
+
+pub struct Options<'a> {
+    pub remote: &'a str,
+    pub headers: &'a [(String, String)],
+    /// Only check methods matching one of these, or all methods if empty.
+    pub methods: &'a [String],
+    /// Skip methods matching any of these.
+    pub skip: &'a [String],
+    /// JSON pointers to ignore when comparing results, where a `*` in a
+    /// segment matches any run of characters.
+    pub ignore: &'a [String],
+}
+
+pub struct Outcome {
+    pub method: String,
+    pub example: String,
+    pub verdict: Verdict,
+}
+
+pub enum Verdict {
+    Same,
+    /// The example has no result to compare.
+    Unchecked,
+    /// The live result is valid, but differs from the example at these
+    /// pointers.
+    Different(Vec<String>),
+    /// The live response doesn't match the schema.
+    Invalid(Vec<String>),
+    /// The node returned an error, or couldn't be reached.
+    Failed(String),
+}
+
+impl Verdict {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Verdict::Same | Verdict::Unchecked)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            method,
+            example,
+            verdict,
+        } = self;
+        match verdict {
+            Verdict::Same => write!(f, "same       {} ({})", method, example),
+            Verdict::Unchecked => {
+                write!(f, "unchecked  {} ({}): no example result", method, example)
+            }
+            Verdict::Different(it) => {
+                write!(f, "different  {} ({}): {}", method, example, it.join(", "))
+            }
+            Verdict::Invalid(it) => {
+                write!(f, "invalid    {} ({}): {}", method, example, it.join("; "))
+            }
+            Verdict::Failed(it) => write!(f, "failed     {} ({}): {}", method, example, it),
+        }
+    }
+}
+
+pub fn verify(document: &resolved::OpenRPC, options: &Options) -> anyhow::Result<Vec<Outcome>> {
+    let envelopes = envelopes::envelopes(document)?;
+    let mut outcomes = vec![];
+    let methods = document.methods.iter().filter(|method| {
+        (options.methods.is_empty()
+            || options
+                .methods
+                .iter()
+                .any(|it| glob::matches(it, &method.name)))
+            && !options
+                .skip
+                .iter()
+                .any(|it| glob::matches(it, &method.name))
+            && !is_mutating(method)
+    });
+    let bar = progress::Bar::new(
+        methods
+            .clone()
+            .map(|it| it.examples.iter().flatten().count())
+            .sum(),
+        "verified",
+        "examples",
+    );
+    for method in methods {
+        bar.show(&method.name);
+        let validator = compile(&envelopes[&method.name].response)?;
+        for pairing in method.examples.iter().flatten() {
+            let values = pairing
+                .params
+                .iter()
+                .map(|it| it.value.clone().unwrap_or_default());
+            let params = match method.param_structure {
+                Some(ParamStructure::ByName) => Value::Object(
+                    method
+                        .params
+                        .iter()
+                        .map(|it| it.name.clone())
+                        .zip(values)
+                        .collect(),
+                ),
+                _ => Value::Array(values.collect()),
+            };
+            let verdict = match source::call(options.remote, options.headers, &method.name, params)
+                .with_context(|| format!("couldn't call {}", method.name))
+            {
+                Err(e) => Verdict::Failed(format!("{:#}", e)),
+                Ok(response) => match (response.get("error"), problems(&validator, &response)) {
+                    (Some(error), _) => Verdict::Failed(format!("returned an error: {}", error)),
+                    (None, problems) if !problems.is_empty() => Verdict::Invalid(problems),
+                    (None, _) => match pairing.result.as_ref().and_then(|it| it.value.as_ref()) {
+                        None => Verdict::Unchecked,
+                        Some(expected) => {
+                            let mut differences = vec![];
+                            compare(
+                                expected,
+                                response.get("result").unwrap_or(&Value::Null),
+                                &mut vec![],
+                                options.ignore,
+                                &mut differences,
+                            );
+                            match differences.is_empty() {
+                                true => Verdict::Same,
+                                false => Verdict::Different(differences),
+                            }
+                        }
+                    },
+                },
+            };
+            outcomes.push(Outcome {
+                method: method.name.clone(),
+                example: pairing.name.clone(),
+                verdict,
+            });
+            bar.inc()
+        }
+    }
+    bar.finish();
+    Ok(outcomes)
+}
+
+/// Compile an [envelope](crate::envelopes).
+pub fn compile(envelope: &Value) -> anyhow::Result<JSONSchema> {
+    JSONSchema::options()
+        .with_draft(Draft::Draft202012)
+        .compile(envelope)
+        .map_err(|it| anyhow::anyhow!("{}", it))
+}
+
+/// Each way `message` doesn't match `validator`, empty if it does.
+pub fn problems(validator: &JSONSchema, message: &Value) -> Vec<String> {
+    match validator.validate(message) {
+        Ok(()) => vec![],
+        Err(errors) => errors
+            .map(|it| format!("{} at {}", it, it.instance_path))
+            .collect(),
+    }
+}
+
+pub fn is_mutating(method: &resolved::Method) -> bool {
+    method.tags.iter().flatten().any(|it| it.name == MUTATING)
+        || method.extensions.0.get(MUTATING) == Some(&Value::Bool(true))
+}
+
+/// Push the pointers where `expected` and `actual` differ to `differences`,
+/// skipping those matching `ignore`.
+///
+/// A key missing from one side differs, even from `null`.
+pub fn compare(
+    expected: &Value,
+    actual: &Value,
+    path: &mut Vec<String>,
+    ignore: &[String],
+    differences: &mut Vec<String>,
+) {
+    if ignore.iter().any(|it| ignored(it, path)) {
+        return;
+    }
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for key in expected
+                .keys()
+                .chain(actual.keys().filter(|it| !expected.contains_key(*it)))
+            {
+                path.push(key.replace('~', "~0").replace('/', "~1"));
+                match (expected.get(key), actual.get(key)) {
+                    (Some(expected), Some(actual)) => {
+                        compare(expected, actual, path, ignore, differences)
+                    }
+                    _ => {
+                        if !ignore.iter().any(|it| ignored(it, path)) {
+                            differences.push(pointer(path))
+                        }
+                    }
+                }
+                path.pop();
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (ix, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                path.push(ix.to_string());
+                compare(expected, actual, path, ignore, differences);
+                path.pop();
+            }
+        }
+        (expected, actual) => {
+            if expected != actual {
+                differences.push(pointer(path))
+            }
+        }
+    }
+}
+
+fn pointer(path: &[String]) -> String {
+    path.iter().map(|it| format!("/{}", it)).collect()
+}
+
+fn ignored(pattern: &str, path: &[String]) -> bool {
+    let segments = pattern.split('/').skip(1).collect::<Vec<_>>();
+    segments.len() == path.len()
+        && segments
+            .iter()
+            .zip(path)
+            .all(|(pattern, segment)| glob::matches(pattern, segment))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn check(expected: Value, actual: Value, ignore: &[&str], pointers: &[&str]) {
+        let ignore = ignore
+            .iter()
+            .map(|it| String::from(*it))
+            .collect::<Vec<_>>();
+        let mut differences = vec![];
+        compare(&expected, &actual, &mut vec![], &ignore, &mut differences);
+        assert_eq!(differences, pointers, "{} {}", expected, actual)
+    }
+
+    #[test]
+    fn compared() {
+        check(json!({ "a": [1, 2] }), json!({ "a": [1, 2] }), &[], &[]);
+        check(
+            json!({ "a": 1, "b": { "c": 2 } }),
+            json!({ "a": 1, "b": { "c": 3 } }),
+            &[],
+            &["/b/c"],
+        );
+        check(json!({ "a": [1, 2] }), json!({ "a": [1] }), &[], &["/a"]);
+        check(
+            json!({ "a": [1, 2] }),
+            json!({ "a": [1, 3] }),
+            &[],
+            &["/a/1"],
+        );
+        check(json!({ "a/b": 1 }), json!({ "a/b": 2 }), &[], &["/a~1b"]);
+        // missing isn't null
+        check(json!({ "a": null }), json!({}), &[], &["/a"]);
+        check(json!({}), json!({ "a": null }), &[], &["/a"]);
+        check(
+            json!({ "b": { "c": 2, "d": 1 } }),
+            json!({ "b": { "c": 3 }, "e": 1 }),
+            &["/b/*"],
+            &["/e"],
+        );
+        check(json!({ "a": 1 }), json!({}), &["/a"], &[]);
+    }
+
+    #[test]
+    fn ignore() {
+        let path = |it: &[&str]| it.iter().map(|it| String::from(*it)).collect::<Vec<_>>();
+        assert!(ignored("/a/*/c", &path(&["a", "x", "c"])));
+        assert!(ignored("/a/b*", &path(&["a", "bc"])));
+        assert!(ignored("", &path(&[])));
+        // the whole path must match
+        assert!(!ignored("/a/*", &path(&["a"])));
+        assert!(!ignored("/a", &path(&["a", "b"])));
+        assert!(!ignored("/a/b", &path(&["a", "c"])));
+    }
+}
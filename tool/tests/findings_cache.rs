@@ -0,0 +1,88 @@
+//! `openrpc report-errors --findings-cache`, which reuses the findings for
+//! methods which haven't changed.
+
+use std::{fs, path::Path};
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+/// Run `report-errors` on `document`, returning stderr.
+fn report_errors(dir: &Path, document: &Value, args: &[&str]) -> String {
+    let path = dir.join("spec.json");
+    fs::write(&path, document.to_string()).unwrap();
+    let assert = Command::cargo_bin("tool")
+        .unwrap()
+        .args(["openrpc", "report-errors"])
+        .arg(&path)
+        .arg("--findings-cache")
+        .arg(dir.join("cache"))
+        .args(args)
+        .assert()
+        .code(1);
+    String::from_utf8(assert.get_output().stderr.clone()).unwrap()
+}
+
+fn method(name: &str) -> Value {
+    json!({
+        "name": name,
+        "params": [
+            { "name": "a", "required": false, "schema": {} },
+            { "name": "b", "required": true, "schema": {} }
+        ]
+    })
+}
+
+fn document(methods: Vec<Value>) -> Value {
+    json!({
+        "openrpc": "1.3.2",
+        "info": { "title": "cache", "version": "0.0.0" },
+        "methods": methods
+    })
+}
+
+#[test]
+fn hits_keep_cross_method_findings() {
+    let dir = tempfile::tempdir().unwrap();
+    let before = report_errors(
+        dir.path(),
+        &document(vec![method("Filecoin.A"), method("Filecoin.B")]),
+        &[],
+    );
+    assert!(!before.contains("duplicated"), "{}", before);
+
+    // Both methods are unchanged, so their findings come from the cache.
+    let after = report_errors(
+        dir.path(),
+        &document(vec![
+            method("Filecoin.A"),
+            method("Filecoin.B"),
+            method("Filecoin.A"),
+        ]),
+        &[],
+    );
+    assert!(
+        after.contains("the following method names are duplicated: Filecoin.A"),
+        "{}",
+        after
+    );
+    assert!(
+        after.contains("on method Filecoin.B follow the optional parameter a: b"),
+        "{}",
+        after
+    );
+}
+
+#[test]
+fn no_cache_is_identical() {
+    let dir = tempfile::tempdir().unwrap();
+    let document = document(vec![
+        method("Filecoin.A"),
+        method("Filecoin.B"),
+        method("Filecoin.A"),
+    ]);
+    let cold = report_errors(dir.path(), &document, &[]);
+    let warm = report_errors(dir.path(), &document, &[]);
+    let uncached = report_errors(dir.path(), &document, &["--no-cache"]);
+    assert_eq!(cold, uncached);
+    assert_eq!(warm, uncached);
+}
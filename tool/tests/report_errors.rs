@@ -0,0 +1,81 @@
+//! `openrpc report-errors` on several documents, which are checked in
+//! parallel.
+
+use std::{fs, path::Path};
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+/// Run `report-errors` on `files` in `dir`, returning the lines of findings.
+fn report_errors(dir: &Path, files: &[&str]) -> Vec<String> {
+    let assert = Command::cargo_bin("tool")
+        .unwrap()
+        .args(["openrpc", "report-errors"])
+        .args(files.iter().map(|it| dir.join(it)))
+        .assert()
+        .code(1);
+    String::from_utf8(assert.get_output().stderr.clone())
+        .unwrap()
+        .lines()
+        .filter(|it| it.starts_with("file "))
+        .map(String::from)
+        .collect()
+}
+
+fn document(methods: Vec<Value>) -> Value {
+    json!({
+        "openrpc": "1.3.2",
+        "info": { "title": "report", "version": "0.0.0" },
+        "methods": methods
+    })
+}
+
+/// Has an optional param before a required one, and a dead `$ref`.
+fn method(name: &str) -> Value {
+    json!({
+        "name": name,
+        "params": [
+            { "name": "a", "required": false, "schema": {} },
+            { "name": "b", "required": true, "schema": { "$ref": "#/components/schemas/Missing" } }
+        ]
+    })
+}
+
+#[test]
+fn sorted_by_document_then_rule() {
+    let dir = tempfile::tempdir().unwrap();
+    let files = ["a.json", "b.json", "c.json"];
+    for (file, methods) in files.into_iter().zip([
+        vec![method("Filecoin.A")],
+        vec![method("Filecoin.B"), method("Filecoin.B")],
+        vec![method("Filecoin.C")],
+    ]) {
+        fs::write(dir.path().join(file), document(methods).to_string()).unwrap();
+    }
+
+    let lines = report_errors(dir.path(), &files);
+    let position = |file: &str, needle: &str| {
+        lines
+            .iter()
+            .position(|it| it.contains(file) && it.contains(needle))
+            .unwrap_or_else(|| panic!("no {} in {}: {:#?}", needle, file, lines))
+    };
+    for file in files {
+        // rules are ordered by name, not by when they ran
+        assert!(position(file, "dead $ref") < position(file, "follow the optional"));
+    }
+    assert!(position("b.json", "dead $ref") < position("b.json", "duplicated"));
+    assert!(position("b.json", "duplicated") < position("b.json", "follow the optional"));
+    let files_in_order = lines
+        .iter()
+        .map(|line| files.iter().position(|it| line.contains(it)).unwrap())
+        .collect::<Vec<_>>();
+    assert!(files_in_order.windows(2).all(|it| it[0] <= it[1]));
+
+    for _ in 0..4 {
+        assert_eq!(
+            report_errors(dir.path(), &["c.json", "a.json", "b.json"]),
+            lines
+        );
+    }
+}
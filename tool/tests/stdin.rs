@@ -0,0 +1,105 @@
+//! Run the binary with documents piped through stdin, as `-`.
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+fn tool() -> Command {
+    Command::cargo_bin("tool").unwrap()
+}
+
+fn document(result: Value) -> String {
+    json!({
+        "openrpc": "1.3.2",
+        "info": { "title": "stdin", "version": "0.0.0" },
+        "methods": [
+            {
+                "name": "Filecoin.Version",
+                "params": [],
+                "result": { "name": "version", "schema": result }
+            },
+            {
+                "name": "Filecoin.ChainHead",
+                "params": [],
+                "result": { "name": "head", "schema": { "type": "object" } }
+            }
+        ],
+        "components": { "schemas": { "Version": { "type": "string" } } }
+    })
+    .to_string()
+}
+
+#[test]
+fn report_errors() {
+    let clean = document(json!({ "$ref": "#/components/schemas/Version" }));
+    tool()
+        .args(["openrpc", "report-errors", "-"])
+        .write_stdin(clean)
+        .assert()
+        .success();
+
+    let dead = document(json!({ "$ref": "#/components/schemas/Missing" }));
+    let assert = tool()
+        .args(["openrpc", "report-errors", "-"])
+        .write_stdin(dead)
+        .assert()
+        .code(1);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(
+        stderr.contains("has dead $ref #/components/schemas/Missing"),
+        "{}",
+        stderr
+    );
+
+    tool()
+        .args(["openrpc", "report-errors", "-"])
+        .write_stdin("{")
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn normalize() {
+    let spec = document(json!({ "$ref": "#/components/schemas/Version" }));
+    let assert = tool()
+        .args(["openrpc", "normalize", "-"])
+        .write_stdin(spec.clone())
+        .assert()
+        .success();
+    let normalized = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert_ne!(normalized, spec);
+
+    let methods = serde_json::from_str::<Value>(&normalized).unwrap()["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|it| it["name"].as_str().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(methods, ["Filecoin.ChainHead", "Filecoin.Version"]);
+
+    tool()
+        .args(["openrpc", "normalize", "--check", "-"])
+        .write_stdin(normalized)
+        .assert()
+        .success();
+    tool()
+        .args(["openrpc", "normalize", "--check", "-"])
+        .write_stdin(spec)
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn only_one_stdin() {
+    let spec = document(json!({ "$ref": "#/components/schemas/Version" }));
+    let assert = tool()
+        .args(["openrpc", "diff", "-", "-"])
+        .write_stdin(spec)
+        .assert()
+        .code(2);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(
+        stderr.contains("both `left` and `right` read from stdin"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,67 @@
+//! Arguments which are only found to be invalid after parsing still exit with
+//! the usage code, `2`.
+
+use std::fs;
+
+use assert_cmd::Command;
+use serde_json::json;
+
+fn tool() -> Command {
+    Command::cargo_bin("tool").unwrap()
+}
+
+fn document(title: &str) -> String {
+    json!({
+        "openrpc": "1.3.2",
+        "info": { "title": title, "version": "0.0.0" },
+        "methods": [{ "name": "Filecoin.Version", "params": [] }]
+    })
+    .to_string()
+}
+
+fn assert_usage(command: &mut Command, message: &str) {
+    let assert = command.assert().code(2);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(stderr.contains(message), "{}", stderr);
+}
+
+#[test]
+fn release_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let old = dir.path().join("old.json");
+    fs::write(&old, document("old")).unwrap();
+    assert_usage(
+        tool()
+            .args(["openrpc", "release"])
+            .arg(&old)
+            .arg("-")
+            .write_stdin(document("new")),
+        "--output is required",
+    );
+}
+
+#[test]
+fn merge_servers_from() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = ["a.json", "b.json"].map(|it| {
+        let path = dir.path().join(it);
+        fs::write(&path, document(it)).unwrap();
+        path
+    });
+    assert_usage(
+        tool()
+            .args(["openrpc", "merge", "--servers-from", "2"])
+            .args(paths),
+        "--servers-from 2 is out of range",
+    );
+}
+
+#[test]
+fn graph_focus() {
+    assert_usage(
+        tool()
+            .args(["openrpc", "graph", "-", "--focus", "Filecoin.Missing"])
+            .write_stdin(document("graph")),
+        "no method named Filecoin.Missing",
+    );
+}